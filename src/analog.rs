@@ -0,0 +1,210 @@
+//! Reading the analog voltage output of the sensor with an ADC.
+//!
+//! The analog output provides an alternative (or backup) measurement path to
+//! the serial interface. According to the data sheet, the output voltage
+//! ranges linearly from 0.4 V at 0 ppm to 2.0 V at the upper limit of the
+//! configured detection range.
+//!
+//! This module is only available with the `analog` feature.
+
+use core::convert::TryFrom;
+#[cfg(feature = "display")]
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+use embedded_hal::adc::{Channel, OneShot};
+
+/// Output voltage in millivolts corresponding to 0 ppm.
+pub const ZERO_MILLIVOLTS: u32 = 400;
+/// Output voltage in millivolts corresponding to the upper limit of the
+/// detection range.
+pub const FULL_SCALE_MILLIVOLTS: u32 = 2000;
+
+/// Converts the output voltage in millivolts to the CO₂ concentration in ppm
+/// for the given detection range (upper limit) in ppm.
+///
+/// Voltages outside of the output range are clamped to 0 ppm and
+/// `range_ppm` respectively.
+pub fn millivolts_to_ppm(millivolts: u32, range_ppm: u16) -> u16 {
+    let millivolts = millivolts.clamp(ZERO_MILLIVOLTS, FULL_SCALE_MILLIVOLTS);
+    ((millivolts - ZERO_MILLIVOLTS) * u32::from(range_ppm)
+        / (FULL_SCALE_MILLIVOLTS - ZERO_MILLIVOLTS)) as u16
+}
+
+/// Supported resolutions of the ADC in bits.
+pub const RESOLUTION_BITS: core::ops::RangeInclusive<u8> = 1..=31;
+
+/// Error returned when creating an [`AnalogOutput`] with an ADC resolution
+/// outside of [`RESOLUTION_BITS`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidResolution(pub u8);
+
+#[cfg(feature = "display")]
+impl Display for InvalidResolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid ADC resolution of {} bits (expected {} to {} bits)",
+            self.0,
+            RESOLUTION_BITS.start(),
+            RESOLUTION_BITS.end()
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidResolution {}
+
+/// Analog output of the sensor connected to an ADC channel.
+#[derive(Debug)]
+pub struct AnalogOutput<ADC, PIN> {
+    pin: PIN,
+    reference_millivolts: u32,
+    max_reading: u32,
+    range_ppm: u16,
+    adc: PhantomData<ADC>,
+}
+
+impl<ADC, PIN> AnalogOutput<ADC, PIN>
+where
+    PIN: Channel<ADC>,
+{
+    /// Create a new instance.
+    ///
+    /// * `pin`: ADC channel connected to the analog output of the sensor.
+    /// * `reference_millivolts`: Reference voltage of the ADC in millivolts
+    ///   (corresponding to the maximum reading).
+    /// * `resolution_bits`: Resolution of the ADC in bits (e.g. 12).
+    /// * `range_ppm`: Detection range (upper limit) of the sensor in ppm.
+    ///
+    /// Returns [`InvalidResolution`] if `resolution_bits` is not within
+    /// [`RESOLUTION_BITS`].
+    pub fn new(
+        pin: PIN,
+        reference_millivolts: u32,
+        resolution_bits: u8,
+        range_ppm: u16,
+    ) -> Result<Self, InvalidResolution> {
+        let max_reading = Some(resolution_bits)
+            .filter(|bits| RESOLUTION_BITS.contains(bits))
+            .and_then(|bits| 1u32.checked_shl(bits.into()))
+            .map(|full_scale| full_scale - 1)
+            .ok_or(InvalidResolution(resolution_bits))?;
+        Ok(Self {
+            pin,
+            reference_millivolts,
+            max_reading,
+            range_ppm,
+            adc: PhantomData,
+        })
+    }
+
+    /// Sets the detection range (upper limit) of the sensor in ppm.
+    ///
+    /// This has to match the detection range configured in the sensor.
+    pub fn set_range_ppm(&mut self, range_ppm: u16) {
+        self.range_ppm = range_ppm;
+    }
+
+    /// Returns the owned ADC channel.
+    pub fn into_inner(self) -> PIN {
+        self.pin
+    }
+
+    /// Converts a raw ADC reading to the output voltage in millivolts.
+    ///
+    /// Voltages exceeding `u32::MAX` millivolts (only possible for readings
+    /// above the maximum reading of the ADC) saturate.
+    pub fn reading_to_millivolts(&self, reading: u32) -> u32 {
+        (u64::from(reading) * u64::from(self.reference_millivolts))
+            .checked_div(u64::from(self.max_reading))
+            .map_or(0, |millivolts| {
+                u32::try_from(millivolts).unwrap_or(u32::MAX)
+            })
+    }
+
+    /// Reads the output voltage in millivolts.
+    pub fn read_millivolts<A, W>(&mut self, adc: &mut A) -> nb::Result<u32, A::Error>
+    where
+        A: OneShot<ADC, W, PIN>,
+        W: Into<u32>,
+    {
+        let reading = adc.read(&mut self.pin)?;
+        Ok(self.reading_to_millivolts(reading.into()))
+    }
+
+    /// Reads the CO₂ concentration in ppm.
+    pub fn read_co2_ppm<A, W>(&mut self, adc: &mut A) -> nb::Result<u16, A::Error>
+    where
+        A: OneShot<ADC, W, PIN>,
+        W: Into<u32>,
+    {
+        let millivolts = self.read_millivolts(adc)?;
+        Ok(millivolts_to_ppm(millivolts, self.range_ppm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nb::block;
+
+    #[derive(Debug)]
+    struct MockAdc(u16);
+    #[derive(Debug)]
+    struct MockPin;
+
+    impl Channel<MockAdc> for MockPin {
+        type ID = u8;
+
+        fn channel() -> u8 {
+            0
+        }
+    }
+
+    impl OneShot<MockAdc, u16, MockPin> for MockAdc {
+        type Error = ();
+
+        fn read(&mut self, _pin: &mut MockPin) -> nb::Result<u16, ()> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn test_millivolts_to_ppm() {
+        assert_eq!(millivolts_to_ppm(400, 5000), 0);
+        assert_eq!(millivolts_to_ppm(1200, 5000), 2500);
+        assert_eq!(millivolts_to_ppm(2000, 5000), 5000);
+        assert_eq!(millivolts_to_ppm(2000, 10000), 10000);
+    }
+
+    #[test]
+    fn test_millivolts_to_ppm_clamps() {
+        assert_eq!(millivolts_to_ppm(0, 5000), 0);
+        assert_eq!(millivolts_to_ppm(3300, 5000), 5000);
+    }
+
+    #[test]
+    fn test_read_co2_ppm() {
+        // 12 bit ADC with 3.3 V reference: 1489 ≙ 1200 mV
+        let mut adc = MockAdc(1489);
+        let mut output = AnalogOutput::new(MockPin, 3300, 12, 5000).unwrap();
+        assert_eq!(block!(output.read_millivolts(&mut adc)), Ok(1199));
+        assert_eq!(block!(output.read_co2_ppm(&mut adc)), Ok(2496));
+    }
+
+    #[test]
+    fn test_invalid_resolution() {
+        assert_eq!(
+            AnalogOutput::<MockAdc, _>::new(MockPin, 3300, 0, 5000).unwrap_err(),
+            InvalidResolution(0)
+        );
+        assert_eq!(
+            AnalogOutput::<MockAdc, _>::new(MockPin, 3300, 32, 5000).unwrap_err(),
+            InvalidResolution(32)
+        );
+        let output = AnalogOutput::<MockAdc, _>::new(MockPin, 3300, 31, 5000).unwrap();
+        assert_eq!(output.reading_to_millivolts(0x7fff_ffff), 3300);
+        let output = AnalogOutput::<MockAdc, _>::new(MockPin, u32::MAX, 1, 5000).unwrap();
+        assert_eq!(output.reading_to_millivolts(2), u32::MAX);
+    }
+}
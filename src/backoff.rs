@@ -0,0 +1,195 @@
+//! Pluggable strategies to wait between polls of non-blocking operations.
+//!
+//! [`nb::block!`] polls an operation in a busy loop until it completes. With
+//! [`block`], a [`Backoff`] strategy is invoked between polls instead, so
+//! that latency can be traded for power draw without rewriting the poll
+//! loops:
+//!
+//! * [`Spin`]: Busy waits with a spin loop hint (lowest latency).
+//! * [`Delay`]: Waits with an [`embedded_hal::blocking::delay::DelayUs`]
+//!   implementation, optionally with exponentially growing delays.
+//! * [`Yield`]: Yields to the scheduler of the operating system (requires the
+//!   `std` feature).
+//!
+//! ```
+//! # use mh_z19c::backoff::{block, Spin};
+//! # use mh_z19c::sim::Simulator;
+//! # use mh_z19c::MhZ19C;
+//! let mut co2sensor = MhZ19C::new(Simulator::new());
+//! let co2_ppm = block(&mut Spin, || co2sensor.read_co2_ppm())?;
+//! # assert_eq!(co2_ppm, 400);
+//! # Ok::<(), mh_z19c::Error<core::convert::Infallible>>(())
+//! ```
+
+use embedded_hal::blocking::delay::DelayUs;
+
+/// Strategy to wait between polls of a non-blocking operation that returned
+/// [`nb::Error::WouldBlock`].
+pub trait Backoff {
+    /// Waits before the next poll.
+    fn backoff(&mut self);
+
+    /// Resets the strategy at the start of a new operation.
+    fn reset(&mut self) {}
+}
+
+impl<B: Backoff + ?Sized> Backoff for &mut B {
+    fn backoff(&mut self) {
+        (**self).backoff();
+    }
+
+    fn reset(&mut self) {
+        (**self).reset();
+    }
+}
+
+/// Polls `f` until it completes, invoking `backoff` between polls.
+///
+/// The `backoff` is reset before the first poll.
+pub fn block<T, E, B, F>(backoff: &mut B, mut f: F) -> Result<T, E>
+where
+    B: Backoff + ?Sized,
+    F: FnMut() -> nb::Result<T, E>,
+{
+    backoff.reset();
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(nb::Error::Other(err)) => return Err(err),
+            Err(nb::Error::WouldBlock) => backoff.backoff(),
+        }
+    }
+}
+
+/// Busy waiting backoff issuing a spin loop hint to the processor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Spin;
+
+impl Backoff for Spin {
+    fn backoff(&mut self) {
+        core::hint::spin_loop();
+    }
+}
+
+/// Backoff waiting with a delay in microseconds.
+///
+/// The delay starts at the initial delay and is doubled after each poll up to
+/// the maximum delay, which equals the initial delay unless set with
+/// [`Delay::with_max_us`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delay<D> {
+    delay: D,
+    initial_us: u32,
+    max_us: u32,
+    current_us: u32,
+}
+
+impl<D: DelayUs<u32>> Delay<D> {
+    /// Creates a backoff waiting `interval_us` between polls using `delay`.
+    pub fn new(delay: D, interval_us: u32) -> Self {
+        Self {
+            delay,
+            initial_us: interval_us,
+            max_us: interval_us,
+            current_us: interval_us,
+        }
+    }
+
+    /// Sets the maximum delay in microseconds the delay grows to
+    /// exponentially.
+    pub fn with_max_us(mut self, max_us: u32) -> Self {
+        self.max_us = max_us.max(self.initial_us);
+        self
+    }
+
+    /// Returns the delay in microseconds before the next poll.
+    pub fn current_us(&self) -> u32 {
+        self.current_us
+    }
+
+    /// Returns the wrapped delay.
+    pub fn into_inner(self) -> D {
+        self.delay
+    }
+}
+
+impl<D: DelayUs<u32>> Backoff for Delay<D> {
+    fn backoff(&mut self) {
+        self.delay.delay_us(self.current_us);
+        self.current_us = self.current_us.saturating_mul(2).min(self.max_us);
+    }
+
+    fn reset(&mut self) {
+        self.current_us = self.initial_us;
+    }
+}
+
+/// Backoff yielding the time slice of the current thread to the scheduler
+/// (requires the `std` feature).
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Yield;
+
+#[cfg(feature = "std")]
+impl Backoff for Yield {
+    fn backoff(&mut self) {
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::delay_mock::DelayMock;
+
+    fn pending_polls(mut count: usize) -> impl FnMut() -> nb::Result<u8, ()> {
+        move || {
+            if count == 0 {
+                Ok(42)
+            } else {
+                count -= 1;
+                Err(nb::Error::WouldBlock)
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_backs_off_between_polls() {
+        let mut backoff = Delay::new(DelayMock::default(), 100);
+        assert_eq!(block(&mut backoff, pending_polls(3)), Ok(42));
+        assert_eq!(backoff.into_inner().delays_us, [100, 100, 100]);
+    }
+
+    #[test]
+    fn test_block_returns_error() {
+        let mut backoff = Delay::new(DelayMock::default(), 100);
+        assert_eq!(
+            block(&mut backoff, || Err::<(), _>(nb::Error::Other(1))),
+            Err(1)
+        );
+        assert!(backoff.into_inner().delays_us.is_empty());
+    }
+
+    #[test]
+    fn test_exponential_delay() {
+        let mut backoff = Delay::new(DelayMock::default(), 100).with_max_us(500);
+        assert_eq!(block(&mut backoff, pending_polls(5)), Ok(42));
+        assert_eq!(backoff.current_us(), 500);
+        assert_eq!(block(&mut backoff, pending_polls(1)), Ok(42));
+        assert_eq!(
+            backoff.into_inner().delays_us,
+            [100, 200, 400, 500, 500, 100]
+        );
+    }
+
+    #[test]
+    fn test_spin() {
+        assert_eq!(block(&mut Spin, pending_polls(2)), Ok(42));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_yield() {
+        assert_eq!(block(&mut Yield, pending_polls(2)), Ok(42));
+    }
+}
@@ -0,0 +1,126 @@
+//! Detection of the baud rate of a sensor (requires the `std` feature).
+//!
+//! The sensor communicates with 9600 baud by default, but previous firmware
+//! or tools might have changed the baud rate. [`detect_baud_rate`] tries the
+//! plausible baud rates and returns the first one at which the sensor
+//! responded with a valid frame. Configuring the baud rate of the serial
+//! interface is platform specific and left to the caller.
+//!
+//! ```no_run
+//! # use mh_z19c::baud::{detect_baud_rate, BAUD_RATES};
+//! # use mh_z19c::io::IoUart;
+//! # fn configure(path: &str, baud_rate: u32) -> std::io::Result<()> { Ok(()) }
+//! let detected = detect_baud_rate(BAUD_RATES, |baud_rate| {
+//!     configure("/dev/ttyUSB0", baud_rate)?;
+//!     let file = std::fs::OpenOptions::new()
+//!         .read(true)
+//!         .write(true)
+//!         .open("/dev/ttyUSB0")?;
+//!     Ok::<_, std::io::Error>(IoUart::new(file))
+//! })?;
+//! match detected {
+//!     Some((baud_rate, _co2sensor)) => println!("sensor found at {baud_rate} baud"),
+//!     None => println!("no sensor found"),
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::backoff::Delay;
+use crate::MhZ19C;
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use embedded_hal::serial::{Read, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Baud rates tried by default, starting with the default baud rate of the
+/// sensor.
+pub const BAUD_RATES: &[u32] = &[9600, 2400, 4800, 14400, 19200, 38400, 57600, 115200];
+
+/// Delay blocking the current thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThreadSleep;
+
+impl DelayMs<u16> for ThreadSleep {
+    fn delay_ms(&mut self, ms: u16) {
+        thread::sleep(Duration::from_millis(u64::from(ms)));
+    }
+}
+
+impl DelayUs<u32> for ThreadSleep {
+    fn delay_us(&mut self, us: u32) {
+        thread::sleep(Duration::from_micros(u64::from(us)));
+    }
+}
+
+/// Tries the `baud_rates` in order and returns the first one at which the
+/// sensor responded to a firmware version query with a valid frame, together
+/// with the driver for the UART interface opened at that baud rate.
+///
+/// The UART interface is opened with `open` for each baud rate and probed
+/// with [`MhZ19C::probe`]. Errors opening the interface are returned
+/// immediately. If the sensor did not respond at any baud rate, `None` is
+/// returned.
+#[allow(clippy::type_complexity)]
+pub fn detect_baud_rate<'a, U, E, F, OpenError>(
+    baud_rates: &[u32],
+    mut open: F,
+) -> Result<Option<(u32, MhZ19C<'a, U, E>)>, OpenError>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    F: FnMut(u32) -> Result<U, OpenError>,
+{
+    for &baud_rate in baud_rates {
+        let uart = open(baud_rate)?;
+        if let Ok(co2sensor) = MhZ19C::probe(uart, &mut Delay::new(ThreadSleep, 1_000)) {
+            return Ok(Some((baud_rate, co2sensor)));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firmware::FirmwareVersion;
+    use std::vec::Vec;
+    use test_support::serial_mock::SerialMock;
+    use test_support::FIRMWARE_0515_RESPONSE;
+
+    fn open_at(actual_baud_rate: u32) -> impl FnMut(u32) -> Result<SerialMock, ()> {
+        move |baud_rate| {
+            let reads = if baud_rate == actual_baud_rate {
+                FIRMWARE_0515_RESPONSE.iter().copied().map(Ok).collect()
+            } else {
+                // Garbled bytes as received with a mismatching baud rate.
+                Vec::from([Ok(0x00), Ok(0xfe), Ok(0xff), Ok(0x80)])
+            };
+            Ok(SerialMock::new(reads, vec![Ok(()); 9]))
+        }
+    }
+
+    #[test]
+    fn test_detect_baud_rate() {
+        let (baud_rate, co2sensor) = detect_baud_rate(&[9600, 19200], open_at(19200))
+            .unwrap()
+            .unwrap();
+        assert_eq!(baud_rate, 19200);
+        assert_eq!(
+            co2sensor.firmware_version(),
+            Some(FirmwareVersion::new(*b"0515"))
+        );
+    }
+
+    #[test]
+    fn test_detect_baud_rate_without_response() {
+        assert!(detect_baud_rate(&[9600], open_at(4800)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_detect_baud_rate_returns_open_error() {
+        assert_eq!(
+            detect_baud_rate(BAUD_RATES, |_| Err::<SerialMock, _>("busy"))
+                .map(|detected| detected.is_some()),
+            Err("busy")
+        );
+    }
+}
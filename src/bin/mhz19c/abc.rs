@@ -0,0 +1,32 @@
+//! The `abc` subcommand.
+
+use crate::args::{ArgError, Args};
+use crate::device::{self, wait, Device};
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let enable = match args.positional().as_deref() {
+        None | Some("status") => None,
+        Some("on") => Some(true),
+        Some("off") => Some(false),
+        Some(action) => return Err(ArgError::UnknownCommand(format!("abc {action}")).into()),
+    };
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    if let Some(enable) = enable {
+        wait(&mut sensor, |sensor| sensor.set_self_calibrate(enable))?;
+    }
+    let enabled = wait(&mut sensor, |sensor| sensor.get_self_calibrate())?;
+    println!(
+        "Automatic baseline correction: {}",
+        if enabled { "on" } else { "off" }
+    );
+
+    match enable {
+        Some(enable) if enable != enabled => {
+            Err("failed to change automatic baseline correction".into())
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,212 @@
+//! Minimal command line argument parsing.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Errors in the command line arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgError {
+    /// An option was given without a value.
+    MissingValue(String),
+    /// A required option or argument was not given.
+    Missing(String),
+    /// The value of an option or argument could not be parsed.
+    InvalidValue { arg: String, value: String },
+    /// An unknown (sub)command was given.
+    UnknownCommand(String),
+    /// An argument was not consumed by the invoked command.
+    Unexpected(String),
+}
+
+impl Display for ArgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingValue(arg) => write!(f, "missing value for {arg}"),
+            Self::Missing(arg) => write!(f, "missing required argument {arg}"),
+            Self::InvalidValue { arg, value } => write!(f, "invalid value '{value}' for {arg}"),
+            Self::UnknownCommand(command) => write!(f, "unknown command '{command}'"),
+            Self::Unexpected(arg) => write!(f, "unexpected argument '{arg}'"),
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+/// Command line arguments that have not been consumed yet.
+///
+/// Options (`--name value` or `--name=value`) and flags (`--name`) may appear
+/// anywhere and should be consumed first. The remaining positional arguments
+/// are consumed in order afterwards.
+#[derive(Clone, Debug)]
+pub struct Args(VecDeque<String>);
+
+impl Args {
+    pub fn new<I: IntoIterator<Item = String>>(args: I) -> Self {
+        Self(args.into_iter().collect())
+    }
+
+    /// Arguments of the current process without the program name.
+    pub fn from_env() -> Self {
+        Self::new(std::env::args().skip(1))
+    }
+
+    /// Consumes the flag `name` and returns whether it was given.
+    pub fn flag(&mut self, name: &str) -> bool {
+        match self.0.iter().position(|arg| arg == name) {
+            Some(index) => {
+                self.0.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Consumes the option `name` and returns its value if it was given.
+    pub fn option(&mut self, name: &str) -> Result<Option<String>, ArgError> {
+        let prefix = format!("{name}=");
+        for index in 0..self.0.len() {
+            if self.0[index] == name {
+                self.0.remove(index);
+                return self
+                    .0
+                    .remove(index)
+                    .map(Some)
+                    .ok_or_else(|| ArgError::MissingValue(name.into()));
+            }
+            if let Some(value) = self.0[index].strip_prefix(&prefix) {
+                let value = value.into();
+                self.0.remove(index);
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Consumes the option `name` and parses its value if it was given.
+    pub fn parsed_option<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, ArgError> {
+        self.option(name)?
+            .map(|value| parse(name, value))
+            .transpose()
+    }
+
+    /// Consumes the option `name` and parses its value as duration (see
+    /// [`parse_duration`]) if it was given.
+    pub fn duration_option(&mut self, name: &str) -> Result<Option<Duration>, ArgError> {
+        self.option(name)?
+            .map(|value| {
+                parse_duration(&value).ok_or(ArgError::InvalidValue {
+                    arg: name.into(),
+                    value,
+                })
+            })
+            .transpose()
+    }
+
+    /// Consumes the option `name` and returns an error if it was not given.
+    pub fn required_option(&mut self, name: &str) -> Result<String, ArgError> {
+        self.option(name)?
+            .ok_or_else(|| ArgError::Missing(name.into()))
+    }
+
+    /// Consumes the next positional argument.
+    pub fn positional(&mut self) -> Option<String> {
+        self.0.pop_front()
+    }
+
+    /// Ensures that all arguments have been consumed.
+    pub fn finish(self) -> Result<(), ArgError> {
+        match self.0.into_iter().next() {
+            Some(arg) => Err(ArgError::Unexpected(arg)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses the `value` given for `arg`.
+pub fn parse<T: FromStr>(arg: &str, value: String) -> Result<T, ArgError> {
+    value.parse().map_err(|_| ArgError::InvalidValue {
+        arg: arg.into(),
+        value,
+    })
+}
+
+/// Parses a duration given as integer with one of the units `ms`, `s`, `m`,
+/// or `h` (e.g. `500ms` or `5s`). Without unit, seconds are assumed.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    match unit {
+        "ms" => Some(Duration::from_millis(amount)),
+        "" | "s" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_secs(amount.checked_mul(60)?)),
+        "h" => Some(Duration::from_secs(amount.checked_mul(3600)?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Args {
+        Args::new(args.iter().map(|&arg| arg.into()))
+    }
+
+    #[test]
+    fn test_options_and_positionals() {
+        let mut args = args(&["read", "--device", "/dev/ttyUSB0", "--yes", "--n=3"]);
+        assert!(args.flag("--yes"));
+        assert!(!args.flag("--no"));
+        assert_eq!(args.option("--device"), Ok(Some("/dev/ttyUSB0".into())));
+        assert_eq!(args.parsed_option::<u32>("--n"), Ok(Some(3)));
+        assert_eq!(args.option("--device"), Ok(None));
+        assert_eq!(args.positional(), Some("read".into()));
+        assert_eq!(args.finish(), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_value() {
+        let mut args = args(&["--device"]);
+        assert_eq!(
+            args.option("--device"),
+            Err(ArgError::MissingValue("--device".into()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let mut args = args(&["--n", "x"]);
+        assert_eq!(
+            args.parsed_option::<u32>("--n"),
+            Err(ArgError::InvalidValue {
+                arg: "--n".into(),
+                value: "x".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("5s"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("5"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("s"), None);
+        assert_eq!(parse_duration("5d"), None);
+        assert_eq!(parse_duration("-5s"), None);
+    }
+
+    #[test]
+    fn test_unexpected_argument() {
+        assert_eq!(
+            args(&["--foo"]).finish(),
+            Err(ArgError::Unexpected("--foo".into()))
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! The `calibrate` subcommand.
+
+use crate::args::{ArgError, Args};
+use crate::device::{self, wait, Device, Sensor};
+use crate::prompt::{ask, confirm};
+use crate::read::read;
+use crate::signal;
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const ZERO_CALIBRATION_WARNING: &str = "\
+Zero point calibration will set the current reading as 400 ppm baseline.
+Before calibrating, the sensor must have been operating in a stable 400 ppm
+environment (e.g. fresh outdoor air) for at least 20 minutes. Calibrating under
+other conditions will make all subsequent readings inaccurate.";
+
+const STABILITY_READ_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_STABILITY_TOLERANCE_PPM: u16 = 20;
+/// Number of consecutive readings that need to be within the tolerance to be
+/// considered stable.
+const STABILITY_WINDOW: usize = 12;
+/// Maximum deviation from 400 ppm of stable readings in fresh air before a
+/// warning is shown.
+const FRESH_AIR_DEVIATION_PPM: u16 = 100;
+const DEFAULT_SETTLE_TIME: Duration = Duration::from_secs(20 * 60);
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    match args.positional().as_deref() {
+        Some("zero") => calibrate_zero(args),
+        Some("wizard") => wizard(args),
+        Some(kind) => Err(ArgError::UnknownCommand(format!("calibrate {kind}")).into()),
+        None => Err(ArgError::Missing("<calibration>".into()).into()),
+    }
+}
+
+fn calibrate_zero(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let yes = args.flag("--yes");
+    let verify = args.duration_option("--verify")?;
+    let tolerance = args
+        .parsed_option("--tolerance")?
+        .unwrap_or(DEFAULT_STABILITY_TOLERANCE_PPM);
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+
+    eprintln!("{ZERO_CALIBRATION_WARNING}\n");
+
+    if let Some(duration) = verify {
+        let (min, max) = observe_range(&mut sensor, duration)?;
+        eprintln!("Readings ranged from {min} ppm to {max} ppm.");
+        if max - min > tolerance {
+            return Err(format!(
+                "readings are not stable (spread of {} ppm exceeds tolerance of {tolerance} ppm)",
+                max - min
+            )
+            .into());
+        }
+    }
+
+    if !yes && !confirm("Send zero point calibration command?")? {
+        return Err("calibration aborted".into());
+    }
+
+    wait(&mut sensor, |sensor| sensor.calibrate_zero())?;
+    eprintln!("Zero point calibration command sent.");
+    Ok(())
+}
+
+/// Reads the sensor repeatedly for `duration` and returns the minimum and
+/// maximum CO₂ concentration observed.
+fn observe_range(
+    sensor: &mut Sensor,
+    duration: Duration,
+) -> Result<(u16, u16), Box<dyn std::error::Error>> {
+    eprintln!("Verifying stability of readings for {duration:?} …");
+    let end = Instant::now() + duration;
+    let mut range = (u16::MAX, u16::MIN);
+    loop {
+        let co2_ppm = read(sensor)?.co2_ppm;
+        range = (range.0.min(co2_ppm), range.1.max(co2_ppm));
+        if Instant::now() + STABILITY_READ_INTERVAL > end {
+            return Ok(range);
+        }
+        thread::sleep(STABILITY_READ_INTERVAL);
+    }
+}
+
+fn wizard(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let tolerance = args
+        .parsed_option("--tolerance")?
+        .unwrap_or(DEFAULT_STABILITY_TOLERANCE_PPM);
+    let settle_time = args
+        .duration_option("--settle-time")?
+        .unwrap_or(DEFAULT_SETTLE_TIME);
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    signal::install_handler();
+
+    eprintln!(
+        "Step 1: Ventilate the room thoroughly (e.g. open all windows) or move the\n\
+         sensor outdoors, so that it is exposed to fresh air of about 400 ppm CO₂.\n\
+         Keep people away from the sensor during the calibration."
+    );
+    ask("Press Enter once the sensor is exposed to fresh air.")?;
+
+    eprintln!("\nStep 2: Waiting for the readings to stabilize …");
+    let co2_ppm = wait_until_stable(&mut sensor, tolerance)?;
+    if co2_ppm.abs_diff(400) > FRESH_AIR_DEVIATION_PPM {
+        eprintln!(
+            "warning: the readings stabilized at {co2_ppm} ppm. This is expected if the zero\n\
+             point of the sensor has drifted, but make sure that the sensor is exposed\n\
+             to fresh air."
+        );
+    }
+
+    eprintln!("\nStep 3: Letting the sensor settle in fresh air …");
+    count_down(&mut sensor, settle_time)?;
+
+    eprintln!("\n{ZERO_CALIBRATION_WARNING}\n");
+    if !confirm("Send zero point calibration command?")? {
+        return Err("calibration aborted".into());
+    }
+    wait(&mut sensor, |sensor| sensor.calibrate_zero())?;
+    eprintln!("Zero point calibration command sent.");
+
+    if !confirm("\nContinue with span point calibration (requires a reference gas of known\nconcentration)?")? {
+        return Ok(());
+    }
+    let span_ppm: u16 = ask("Concentration of the reference gas in ppm:")?
+        .parse()
+        .map_err(|_| "invalid concentration")?;
+    ask("Press Enter once the sensor is exposed to the reference gas.")?;
+    eprintln!("\nWaiting for the readings to stabilize …");
+    wait_until_stable(&mut sensor, tolerance)?;
+    eprintln!("\nLetting the sensor settle in the reference gas …");
+    count_down(&mut sensor, settle_time)?;
+    if !confirm(&format!(
+        "Send span point calibration command for {span_ppm} ppm?"
+    ))? {
+        return Err("calibration aborted".into());
+    }
+    wait(&mut sensor, |sensor| sensor.calibrate_span(span_ppm))?;
+    eprintln!("Span point calibration command sent.");
+    Ok(())
+}
+
+/// Reads the sensor until the last [`STABILITY_WINDOW`] readings vary by at
+/// most `tolerance` and returns their mean.
+fn wait_until_stable(
+    sensor: &mut Sensor,
+    tolerance: u16,
+) -> Result<u16, Box<dyn std::error::Error>> {
+    let mut window = VecDeque::with_capacity(STABILITY_WINDOW);
+    loop {
+        let next_read = Instant::now() + STABILITY_READ_INTERVAL;
+        let co2_ppm = read(sensor)?.co2_ppm;
+        if window.len() == STABILITY_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(co2_ppm);
+
+        let spread = window.iter().max().unwrap() - window.iter().min().unwrap();
+        eprintln!(
+            "  {co2_ppm} ppm (spread of last {} readings: {spread} ppm)",
+            window.len()
+        );
+        if window.len() == STABILITY_WINDOW && spread <= tolerance {
+            let sum: u32 = window.iter().copied().map(u32::from).sum();
+            return Ok((sum / STABILITY_WINDOW as u32) as u16);
+        }
+
+        signal::sleep_until(next_read);
+        if signal::interrupted() {
+            return Err("calibration aborted".into());
+        }
+    }
+}
+
+/// Shows a countdown of `duration` together with the current readings.
+fn count_down(sensor: &mut Sensor, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
+    let end = Instant::now() + duration;
+    loop {
+        let remaining = end.saturating_duration_since(Instant::now()).as_secs();
+        let co2_ppm = read(sensor)?.co2_ppm;
+        eprint!(
+            "\r  {:02}:{:02} remaining, current reading {co2_ppm} ppm ",
+            remaining / 60,
+            remaining % 60
+        );
+        if remaining == 0 {
+            eprintln!();
+            return Ok(());
+        }
+
+        signal::sleep_until((Instant::now() + STABILITY_READ_INTERVAL).min(end));
+        if signal::interrupted() {
+            eprintln!();
+            return Err("calibration aborted".into());
+        }
+    }
+}
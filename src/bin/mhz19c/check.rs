@@ -0,0 +1,117 @@
+//! The `check` subcommand following the Nagios plugin conventions.
+
+use crate::args::{ArgError, Args};
+use crate::device::{self, Device};
+use crate::read::{read, Reading};
+use std::process::ExitCode;
+
+/// Service status as defined by the Nagios plugin API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Ok = 0,
+    Warning = 1,
+    Critical = 2,
+    Unknown = 3,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Warning => "WARNING",
+            Self::Critical => "CRITICAL",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Thresholds {
+    warn_ppm: u16,
+    crit_ppm: u16,
+}
+
+impl Thresholds {
+    fn status(self, co2_ppm: u16) -> Status {
+        if co2_ppm > self.crit_ppm {
+            Status::Critical
+        } else if co2_ppm > self.warn_ppm {
+            Status::Warning
+        } else {
+            Status::Ok
+        }
+    }
+}
+
+/// Reads the sensor once, prints a one-line status with performance data,
+/// and returns the status as exit code. Any error (including invalid
+/// arguments) results in the unknown status.
+pub fn run(args: Args) -> ExitCode {
+    let (status, message) = match check(args) {
+        Ok((status, reading, thresholds)) => (status, format_message(&reading, thresholds)),
+        Err(err) => (Status::Unknown, err.to_string()),
+    };
+    println!("CO2 {} - {message}", status.label());
+    ExitCode::from(status as u8)
+}
+
+fn check(mut args: Args) -> Result<(Status, Reading, Thresholds), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let thresholds = Thresholds {
+        warn_ppm: args
+            .parsed_option("--warn")?
+            .ok_or_else(|| ArgError::Missing("--warn".into()))?,
+        crit_ppm: args
+            .parsed_option("--crit")?
+            .ok_or_else(|| ArgError::Missing("--crit".into()))?,
+    };
+    args.finish()?;
+    if thresholds.warn_ppm > thresholds.crit_ppm {
+        return Err("warning threshold must not exceed critical threshold".into());
+    }
+
+    let mut sensor = device::open(&device)?;
+    let reading = read(&mut sensor)?;
+    Ok((thresholds.status(reading.co2_ppm), reading, thresholds))
+}
+
+fn format_message(reading: &Reading, thresholds: Thresholds) -> String {
+    let mut message = format!(
+        "{} ppm | co2_ppm={};{};{};0",
+        reading.co2_ppm, reading.co2_ppm, thresholds.warn_ppm, thresholds.crit_ppm
+    );
+    if let Some(temp_celsius) = reading.temp_celsius {
+        message.push_str(&format!(" temperature={temp_celsius:.1}"));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: Thresholds = Thresholds {
+        warn_ppm: 1000,
+        crit_ppm: 2000,
+    };
+
+    #[test]
+    fn test_status() {
+        assert_eq!(THRESHOLDS.status(800), Status::Ok);
+        assert_eq!(THRESHOLDS.status(1000), Status::Ok);
+        assert_eq!(THRESHOLDS.status(1001), Status::Warning);
+        assert_eq!(THRESHOLDS.status(2001), Status::Critical);
+    }
+
+    #[test]
+    fn test_format_message() {
+        let reading = Reading {
+            co2_ppm: 800,
+            temp_celsius: Some(24.0),
+        };
+        assert_eq!(
+            format_message(&reading, THRESHOLDS),
+            "800 ppm | co2_ppm=800;1000;2000;0 temperature=24.0"
+        );
+    }
+}
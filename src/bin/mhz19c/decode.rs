@@ -0,0 +1,211 @@
+//! Decoding of byte streams into annotated frames and the `decode` subcommand.
+
+use crate::args::{ArgError, Args};
+use mh_z19c::decoder::{Decoder, Event};
+use mh_z19c::frame::START_BYTE;
+use std::io::{self, BufRead};
+
+/// Event of the [`Decoder`] together with the raw bytes it was decoded from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoded {
+    /// Bytes skipped before the start byte of the frame.
+    pub skipped: Vec<u8>,
+    /// Bytes of the frame, or of an invalid frame up to the next start byte.
+    pub bytes: Vec<u8>,
+    /// Decoded event.
+    pub event: Event,
+}
+
+/// Decodes a stream of bytes with a [`Decoder`], keeping the raw bytes of
+/// each event for display.
+#[derive(Clone, Debug, Default)]
+pub struct RawDecoder {
+    decoder: Decoder,
+    pending: Vec<u8>,
+}
+
+impl RawDecoder {
+    /// Adds a received `byte` and returns the decoded event if it completes a
+    /// frame.
+    pub fn push(&mut self, byte: u8) -> Option<Decoded> {
+        self.pending.push(byte);
+        let event = self.decoder.push(byte)?;
+        let consumed = self.pending.len() - self.decoder.buffered();
+        let mut bytes: Vec<u8> = self.pending.drain(..consumed).collect();
+        let start = bytes
+            .iter()
+            .position(|&byte| byte == START_BYTE)
+            .unwrap_or(0);
+        let skipped = bytes.drain(..start).collect();
+        Some(Decoded {
+            skipped,
+            bytes,
+            event,
+        })
+    }
+
+    /// Returns and clears the bytes not decoded so far, i.e. skipped bytes
+    /// and the buffered bytes of an incomplete frame.
+    pub fn take_remaining(&mut self) -> Vec<u8> {
+        self.decoder.reset();
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Annotates a decoded `event` (see [`mh_z19c::frame::Frame::annotate`]).
+pub fn annotate(event: &Event) -> String {
+    match event {
+        Event::Response(frame) | Event::Command(frame) => frame.annotate().to_string(),
+        Event::Invalid(err) => format!("invalid frame ({err})"),
+    }
+}
+
+/// Runs the `decode` subcommand.
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let mut inputs: Vec<String> = std::iter::from_fn(|| args.positional()).collect();
+    if inputs.is_empty() {
+        inputs = io::stdin().lock().lines().collect::<Result<_, _>>()?;
+    }
+
+    let mut bytes = vec![];
+    for input in inputs {
+        if input.trim_start().starts_with('#') {
+            continue;
+        }
+        match parse_hex(&input) {
+            Some(parsed) => bytes.extend(parsed),
+            None => {
+                return Err(ArgError::InvalidValue {
+                    arg: "<hex>".into(),
+                    value: input,
+                }
+                .into())
+            }
+        }
+    }
+
+    for line in decode(&bytes) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Parses hexadecimal bytes separated by whitespace, commas, or colons, with
+/// an optional `0x` prefix. Separators between bytes are optional (e.g.
+/// `ff8601`).
+fn parse_hex(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    for token in input.split(|c: char| c.is_whitespace() || c == ',' || c == ':') {
+        let token = token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"))
+            .unwrap_or(token);
+        if !token.is_ascii() || token.len() % 2 != 0 {
+            return None;
+        }
+        for i in (0..token.len()).step_by(2) {
+            bytes.push(u8::from_str_radix(&token[i..i + 2], 16).ok()?);
+        }
+    }
+    Some(bytes)
+}
+
+/// Splits `bytes` into frames and annotates each of them, as well as bytes
+/// not forming a valid frame.
+fn decode(bytes: &[u8]) -> Vec<String> {
+    let mut decoder = RawDecoder::default();
+    let mut lines = vec![];
+    for &byte in bytes {
+        if let Some(decoded) = decoder.push(byte) {
+            lines.extend(describe_skipped(&decoded.skipped));
+            lines.push(format!(
+                "{}  {}",
+                hex(&decoded.bytes),
+                annotate(&decoded.event)
+            ));
+        }
+    }
+    lines.extend(describe_skipped(&decoder.take_remaining()));
+    lines
+}
+
+fn describe_skipped(skipped: &[u8]) -> Option<String> {
+    if skipped.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}  {} bytes not forming a valid frame",
+        hex(skipped),
+        skipped.len()
+    ))
+}
+
+/// Formats `bytes` as space-separated hexadecimal values.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mh_z19c::command::Command;
+    use mh_z19c::frame::Frame;
+
+    #[test]
+    fn test_raw_decoder() {
+        let read_co2 = Frame::from(Command::ReadCo2);
+        let mut decoder = RawDecoder::default();
+        let mut decoded = vec![];
+        for &byte in [0x12, 0xff, 0x34]
+            .iter()
+            .chain(read_co2.as_ref())
+            .chain(read_co2.as_ref())
+        {
+            decoded.extend(decoder.push(byte));
+        }
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[0].skipped, vec![0x12]);
+        assert_eq!(decoded[0].bytes, vec![0xff, 0x34]);
+        assert!(matches!(decoded[0].event, Event::Invalid(_)));
+        assert_eq!(decoded[1].skipped, vec![]);
+        assert_eq!(decoded[1].bytes, read_co2.as_ref());
+        assert_eq!(decoded[2].event, Event::Command(read_co2));
+        assert_eq!(decoder.take_remaining(), vec![]);
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("ff8601"), Some(vec![0xff, 0x86, 0x01]));
+        assert_eq!(
+            parse_hex(" 0xff, 0x86:01 02"),
+            Some(vec![0xff, 0x86, 0x01, 0x02])
+        );
+        assert_eq!(parse_hex(""), Some(vec![]));
+        assert_eq!(parse_hex("ff8"), None);
+        assert_eq!(parse_hex("zz"), None);
+        assert_eq!(parse_hex("ä1"), None);
+    }
+
+    #[test]
+    fn test_decode() {
+        let mut bytes = vec![0x12];
+        bytes.extend_from_slice(Frame::from(Command::ReadCo2).as_ref());
+        bytes.extend_from_slice(&[0xff, 0x86, 0x03, 0x20, 0, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&[0xff, 0x86, 0x03, 0x20, 0, 0, 0, 0, 0x57]);
+        bytes.extend_from_slice(&[0xff, 0x86]);
+        assert_eq!(
+            decode(&bytes),
+            vec![
+                "12  1 bytes not forming a valid frame",
+                "ff 01 86 00 00 00 00 00 79  ReadCo2",
+                "ff 86 03 20 00 00 00 00 00  invalid frame (invalid checksum (got 0 instead of 87))",
+                "ff 86 03 20 00 00 00 00 57  ReadCo2 → 800 ppm",
+                "ff 86  2 bytes not forming a valid frame",
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Access to the sensor connected to a serial device or simulated.
+
+use crate::args::{ArgError, Args};
+use embedded_hal::serial::{Read, Write};
+use mh_z19c::io::IoUart;
+use mh_z19c::profile::DeviceProfile;
+use mh_z19c::sim::Simulator;
+use mh_z19c::MhZ19C;
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Driver for a sensor connected to a serial device or simulated.
+pub type Sensor = MhZ19C<'static, Uart, io::Error>;
+
+/// Time to wait for the response of the sensor to a command.
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delay of reads from the simulator without available data to avoid busy
+/// waiting, similar to the read timeout of the serial device.
+const SIMULATOR_READ_DELAY: Duration = Duration::from_millis(10);
+
+/// The sensor to communicate with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Device {
+    /// Sensor connected to the serial device at the given path.
+    Serial(String),
+    /// The simulator provided by the library.
+    Simulated,
+}
+
+impl Device {
+    /// Consumes the `--simulate` flag or the `--device <path>` option.
+    pub fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        if args.flag("--simulate") {
+            Ok(Self::Simulated)
+        } else {
+            args.required_option("--device").map(Self::Serial)
+        }
+    }
+}
+
+impl Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serial(path) => f.write_str(path),
+            Self::Simulated => f.write_str("simulator"),
+        }
+    }
+}
+
+/// UART interface to a serial device or the simulator.
+#[derive(Debug)]
+pub enum Uart {
+    Serial(IoUart<File>),
+    Simulated(Simulator),
+}
+
+impl Read<u8> for Uart {
+    type Error = io::Error;
+
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        match self {
+            Self::Serial(uart) => uart.read(),
+            Self::Simulated(simulator) => simulator.read().map_err(|err| match err {
+                nb::Error::WouldBlock => {
+                    thread::sleep(SIMULATOR_READ_DELAY);
+                    nb::Error::WouldBlock
+                }
+                nb::Error::Other(never) => match never {},
+            }),
+        }
+    }
+}
+
+impl Write<u8> for Uart {
+    type Error = io::Error;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        match self {
+            Self::Serial(uart) => uart.write(word),
+            Self::Simulated(simulator) => simulator.write(word).map_err(|err| match err {
+                nb::Error::WouldBlock => nb::Error::WouldBlock,
+                nb::Error::Other(never) => match never {},
+            }),
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        match self {
+            Self::Serial(uart) => uart.flush(),
+            Self::Simulated(_) => Ok(()),
+        }
+    }
+}
+
+/// Opens the `device` and, for a serial device, configures it for
+/// communication with the sensor (9600 baud, 8 data bits, no parity, 1 stop
+/// bit).
+///
+/// The configuration is done with the `stty` utility. Reads from the device
+/// time out after a second to allow for [`RESPONSE_TIMEOUT`] to be checked.
+pub fn open(device: &Device) -> io::Result<Sensor> {
+    Ok(MhZ19C::new(open_uart(device)?))
+}
+
+/// Opens and configures the `device` like [`open`], but without creating a
+/// driver.
+pub fn open_uart(device: &Device) -> io::Result<Uart> {
+    match device {
+        Device::Serial(path) => {
+            configure(path)?;
+            let file = OpenOptions::new().read(true).write(true).open(path)?;
+            Ok(Uart::Serial(IoUart::new(file)))
+        }
+        Device::Simulated => Ok(Uart::Simulated(Simulator::new())),
+    }
+}
+
+fn configure(path: &str) -> io::Result<()> {
+    let device_flag = if cfg!(target_os = "macos") {
+        "-f"
+    } else {
+        "-F"
+    };
+    let status = Command::new("stty")
+        .args([device_flag, path])
+        .args([
+            "9600", "raw", "-echo", "cs8", "-cstopb", "-parenb", "clocal",
+        ])
+        .args(["min", "0", "time", "10"])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "failed to configure serial device {path}"
+        )))
+    }
+}
+
+/// Polls `f` with the `sensor` until it completes or the
+/// [`RESPONSE_TIMEOUT`] expires.
+///
+/// On timeout, the command in progress is aborted (see [`MhZ19C::abort`]), so
+/// that the next command is sent anew instead of waiting for a lost response.
+pub fn wait<'a, U, E, P, T, F>(sensor: &mut MhZ19C<'a, U, E, P>, f: F) -> Result<T, Box<dyn Error>>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    E: Debug + Display + 'static,
+    P: DeviceProfile,
+    F: FnMut(&mut MhZ19C<'a, U, E, P>) -> nb::Result<T, mh_z19c::Error<E>>,
+{
+    wait_until(sensor, Instant::now() + RESPONSE_TIMEOUT, f)
+}
+
+/// Polls `f` with the `sensor` like [`wait`], but until the `deadline`.
+pub fn wait_until<'a, U, E, P, T, F>(
+    sensor: &mut MhZ19C<'a, U, E, P>,
+    deadline: Instant,
+    mut f: F,
+) -> Result<T, Box<dyn Error>>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    E: Debug + Display + 'static,
+    P: DeviceProfile,
+    F: FnMut(&mut MhZ19C<'a, U, E, P>) -> nb::Result<T, mh_z19c::Error<E>>,
+{
+    loop {
+        match f(sensor) {
+            Ok(value) => return Ok(value),
+            Err(nb::Error::Other(err)) => return Err(err.into()),
+            Err(nb::Error::WouldBlock) => abort_after_deadline(sensor, deadline)?,
+        }
+    }
+}
+
+/// Aborts the command in progress and returns a timeout error if the
+/// `deadline` has passed.
+pub fn abort_after_deadline<U, E, P>(
+    sensor: &mut MhZ19C<'_, U, E, P>,
+    deadline: Instant,
+) -> io::Result<()>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    check_deadline(deadline).inspect_err(|_| {
+        // A UART error will be reported again by the next command.
+        let _ = sensor.abort();
+    })
+}
+
+/// Returns a timeout error if the `deadline` has passed.
+pub fn check_deadline(deadline: Instant) -> io::Result<()> {
+    if Instant::now() < deadline {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            ErrorKind::TimedOut,
+            "the sensor did not respond in time",
+        ))
+    }
+}
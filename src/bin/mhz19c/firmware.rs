@@ -0,0 +1,40 @@
+//! The `firmware` subcommand.
+
+use crate::args::Args;
+use crate::device::{self, wait, Device};
+use mh_z19c::capabilities::Capabilities;
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    let detected = wait(&mut sensor, |sensor| sensor.detect())?;
+    let version = detected.firmware_version();
+    println!("Model: {}", detected.model());
+    match version.as_str() {
+        Some(_) => println!("Firmware version: {version}"),
+        None => println!("Firmware version: {version} (not a valid version string)"),
+    }
+    let capabilities = detected.capabilities();
+    for (name, capability) in [
+        ("CO₂ readings", Capabilities::READ_CO2),
+        (
+            "Combined CO₂ and temperature readings",
+            Capabilities::READ_CO2_AND_TEMPERATURE,
+        ),
+        ("Self-calibration", Capabilities::SELF_CALIBRATE),
+        ("Zero and span calibration", Capabilities::CALIBRATION),
+        ("Detection range", Capabilities::DETECTION_RANGE),
+    ] {
+        println!(
+            "{name}: {}",
+            if capabilities.contains(capability) {
+                "supported"
+            } else {
+                "not supported"
+            }
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//! The `log` subcommand writing readings to a CSV file.
+
+use crate::args::Args;
+use crate::device::{self, Device};
+use crate::logging::{self, Priority};
+use crate::output::Format;
+use crate::read::{read, Reading};
+use crate::signal;
+use crate::time::format_date;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// When to start a new log file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// Always append to the same file.
+    #[default]
+    Never,
+    /// Start a new file for each day (UTC). The file of the previous day is
+    /// renamed to include its date.
+    Daily,
+}
+
+impl FromStr for Rotation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(Self::Never),
+            "daily" => Ok(Self::Daily),
+            _ => Err(()),
+        }
+    }
+}
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let out = args.required_option("--out")?;
+    let interval = args
+        .duration_option("--interval")?
+        .unwrap_or(DEFAULT_INTERVAL);
+    let rotation = args.parsed_option("--rotate")?.unwrap_or_default();
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    let started = format!("logging readings of {device} every {interval:?} to {out}");
+    let mut log = CsvLog::open(out.into(), rotation, SystemTime::now())?;
+    signal::install_handler();
+    logging::log(Priority::Info, &started);
+
+    let start = Instant::now();
+    let mut n: u64 = 0;
+    while !signal::interrupted() {
+        let timestamp = SystemTime::now();
+        match read(&mut sensor) {
+            Ok(reading) => log.append(timestamp, &reading)?,
+            Err(err) => logging::log(Priority::Error, &err.to_string()),
+        }
+        n += 1;
+        signal::sleep_until(signal::nth_tick(start, interval, n));
+    }
+    logging::log(Priority::Info, "stopped logging readings");
+    Ok(())
+}
+
+/// CSV log file with optional rotation.
+#[derive(Debug)]
+pub struct CsvLog {
+    path: PathBuf,
+    rotation: Rotation,
+    file: File,
+    date: String,
+}
+
+impl CsvLog {
+    /// Opens the log file at `path` for appending. If the file was last
+    /// modified on an earlier day than `now` and daily rotation is enabled,
+    /// it will be rotated first.
+    pub fn open(path: PathBuf, rotation: Rotation, now: SystemTime) -> io::Result<Self> {
+        let date = format_date(now);
+        if rotation == Rotation::Daily {
+            if let Ok(modified) = fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                let modified_date = format_date(modified);
+                if modified_date != date {
+                    rotate(&path, &modified_date)?;
+                }
+            }
+        }
+        let file = open_with_header(&path)?;
+        Ok(Self {
+            path,
+            rotation,
+            file,
+            date,
+        })
+    }
+
+    /// Appends a `reading` taken at `timestamp` to the log, rotating the log
+    /// file first if necessary.
+    pub fn append(&mut self, timestamp: SystemTime, reading: &Reading) -> io::Result<()> {
+        if self.rotation == Rotation::Daily {
+            let date = format_date(timestamp);
+            if date != self.date {
+                rotate(&self.path, &self.date)?;
+                self.file = open_with_header(&self.path)?;
+                self.date = date;
+            }
+        }
+        writeln!(
+            self.file,
+            "{}",
+            Format::Csv.format_reading(timestamp, reading)
+        )?;
+        self.file.flush()
+    }
+}
+
+/// Opens the file at `path` for appending and writes the CSV header if the
+/// file is empty.
+fn open_with_header(path: &Path) -> io::Result<File> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        if let Some(header) = Format::Csv.header() {
+            writeln!(file, "{header}")?;
+        }
+    }
+    Ok(file)
+}
+
+/// Renames the log file at `path` to its rotated path for `date`.
+///
+/// Existing rotated files are never overwritten. If a file for `date` has
+/// already been rotated (e.g. after restarting several times on the same
+/// day), a counter is appended to the date (e.g. `co2.2023-02-17.1.csv`).
+fn rotate(path: &Path, date: &str) -> io::Result<()> {
+    let mut target = rotated_path(path, date);
+    let mut n: u64 = 0;
+    while target.try_exists()? {
+        n += 1;
+        target = rotated_path(path, &format!("{date}.{n}"));
+    }
+    fs::rename(path, target)
+}
+
+/// Returns the path of a log file rotated on `date` (e.g. `co2.2023-02-17.csv`
+/// for `co2.csv`).
+fn rotated_path(path: &Path, date: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{stem}.{date}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{date}"),
+    };
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    const READING: Reading = Reading {
+        co2_ppm: 800,
+        temp_celsius: None,
+    };
+
+    #[test]
+    fn test_rotated_path() {
+        assert_eq!(
+            rotated_path(Path::new("/var/log/co2.csv"), "2023-02-17"),
+            Path::new("/var/log/co2.2023-02-17.csv")
+        );
+        assert_eq!(
+            rotated_path(Path::new("co2"), "2023-02-17"),
+            Path::new("co2.2023-02-17")
+        );
+    }
+
+    #[test]
+    fn test_daily_rotation() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("mhz19c-log-test-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("co2.csv");
+        let day1 = UNIX_EPOCH + Duration::from_secs(1_676_637_296);
+        let day2 = day1 + Duration::from_secs(86400);
+
+        let mut log = CsvLog::open(path.clone(), Rotation::Daily, day1)?;
+        log.append(day1, &READING)?;
+        log.append(day2, &READING)?;
+
+        assert_eq!(
+            fs::read_to_string(dir.join("co2.2023-02-17.csv"))?,
+            "timestamp,co2_ppm,temp_celsius\n2023-02-17T12:34:56Z,800,\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "timestamp,co2_ppm,temp_celsius\n2023-02-18T12:34:56Z,800,\n"
+        );
+        fs::remove_dir_all(&dir)
+    }
+
+    #[test]
+    fn test_rotation_keeps_existing_rotated_files() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("mhz19c-log-test-keep-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("co2.csv");
+        fs::write(dir.join("co2.2023-02-17.csv"), "first\n")?;
+        fs::write(dir.join("co2.2023-02-17.1.csv"), "second\n")?;
+        fs::write(&path, "third\n")?;
+
+        rotate(&path, "2023-02-17")?;
+
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("co2.2023-02-17.csv"))?,
+            "first\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("co2.2023-02-17.1.csv"))?,
+            "second\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("co2.2023-02-17.2.csv"))?,
+            "third\n"
+        );
+        fs::remove_dir_all(&dir)
+    }
+}
@@ -0,0 +1,162 @@
+//! Logging of diagnostic messages to stderr, syslog, or the systemd journal.
+
+use crate::time::format_rfc3339;
+use std::fmt::{self, Display};
+use std::io;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+/// Identifier of the messages in syslog and the journal.
+const IDENTIFIER: &str = "mhz19c";
+
+/// Socket of the local syslog daemon.
+const SYSLOG_SOCKET: &str = "/dev/log";
+
+/// Socket of the native protocol of the systemd journal.
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Facility of the messages in syslog (user-level messages).
+const SYSLOG_FACILITY_USER: u8 = 1;
+
+static TARGET: OnceLock<Target> = OnceLock::new();
+
+/// Destination of the log messages selected with the `--log-target` option.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Target {
+    /// Standard error with a timestamp prefix.
+    #[default]
+    Stderr,
+    /// The local syslog daemon.
+    Syslog,
+    /// The systemd journal.
+    Journald,
+}
+
+impl FromStr for Target {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stderr" => Ok(Self::Stderr),
+            "syslog" => Ok(Self::Syslog),
+            "journald" => Ok(Self::Journald),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Priority (severity) of a log message as defined by syslog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Error = 3,
+    Info = 6,
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Info => "info",
+        })
+    }
+}
+
+/// Sets the target of the log messages. Can only be set once, later calls
+/// are ignored.
+pub fn set_target(target: Target) {
+    let _ = TARGET.set(target);
+}
+
+/// Returns the target of the log messages.
+pub fn target() -> Target {
+    TARGET.get().copied().unwrap_or_default()
+}
+
+/// Logs the `message` with the `priority` to the configured target.
+///
+/// If sending the message to syslog or the journal fails, it is written to
+/// stderr instead.
+pub fn log(priority: Priority, message: &str) {
+    let sent = match target() {
+        Target::Stderr => Ok(()),
+        Target::Syslog => send(SYSLOG_SOCKET, &syslog_message(priority, message)),
+        Target::Journald => send(JOURNALD_SOCKET, &journald_message(priority, message)),
+    };
+    if target() == Target::Stderr || sent.is_err() {
+        eprintln!(
+            "{}  {priority}: {message}",
+            format_rfc3339(SystemTime::now())
+        );
+    }
+}
+
+#[cfg(unix)]
+fn send(socket: &str, message: &[u8]) -> io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+    UnixDatagram::unbound()?.send_to(message, socket).map(drop)
+}
+
+#[cfg(not(unix))]
+fn send(_socket: &str, _message: &[u8]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Formats a message for the local syslog socket. The syslog daemon adds the
+/// timestamp and host name.
+fn syslog_message(priority: Priority, message: &str) -> Vec<u8> {
+    let pri = SYSLOG_FACILITY_USER * 8 + priority as u8;
+    format!("<{pri}>{IDENTIFIER}[{}]: {message}", std::process::id()).into_bytes()
+}
+
+/// Formats a message in the native journal protocol.
+fn journald_message(priority: Priority, message: &str) -> Vec<u8> {
+    let mut datagram = format!(
+        "PRIORITY={}\nSYSLOG_IDENTIFIER={IDENTIFIER}\n",
+        priority as u8
+    )
+    .into_bytes();
+    if message.contains('\n') {
+        // Values containing newlines are length-prefixed.
+        datagram.extend_from_slice(b"MESSAGE\n");
+        datagram.extend_from_slice(&(message.len() as u64).to_le_bytes());
+        datagram.extend_from_slice(message.as_bytes());
+        datagram.push(b'\n');
+    } else {
+        datagram.extend_from_slice(format!("MESSAGE={message}\n").as_bytes());
+    }
+    datagram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!("stderr".parse(), Ok(Target::Stderr));
+        assert_eq!("syslog".parse(), Ok(Target::Syslog));
+        assert_eq!("journald".parse(), Ok(Target::Journald));
+        assert_eq!("file".parse::<Target>(), Err(()));
+    }
+
+    #[test]
+    fn test_syslog_message() {
+        assert_eq!(
+            syslog_message(Priority::Error, "failed"),
+            format!("<11>mhz19c[{}]: failed", std::process::id()).into_bytes()
+        );
+    }
+
+    #[test]
+    fn test_journald_message() {
+        assert_eq!(
+            journald_message(Priority::Error, "failed"),
+            b"PRIORITY=3\nSYSLOG_IDENTIFIER=mhz19c\nMESSAGE=failed\n"
+        );
+        assert_eq!(
+            journald_message(Priority::Info, "a\nb"),
+            b"PRIORITY=6\nSYSLOG_IDENTIFIER=mhz19c\nMESSAGE\n\x03\0\0\0\0\0\0\0a\nb\n"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Command line tool to interact with the Winsen MH-Z19C CO₂ sensor.
+
+mod abc;
+mod args;
+mod calibrate;
+mod check;
+mod decode;
+mod device;
+mod firmware;
+mod log;
+mod logging;
+mod monitor;
+mod output;
+mod prom;
+mod prompt;
+mod range;
+mod raw;
+mod read;
+mod selftest;
+mod signal;
+mod sniff;
+mod time;
+mod tui;
+
+use args::{ArgError, Args};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: mhz19c <command> [options]
+
+Commands:
+  read --device <path> [--format <format>]
+                          Read the current CO₂ concentration (and temperature
+                          if supported by the sensor firmware).
+  monitor --device <path> [--interval <duration>] [--count <n>]
+          [--format <format>]
+                          Continuously print timestamped readings every
+                          interval (default 5s) until Ctrl-C is pressed or
+                          <n> readings have been taken. Durations are given
+                          with a unit of ms, s, m, or h (e.g. 500ms, 5s).
+  calibrate zero --device <path> [--verify <duration>] [--tolerance <ppm>]
+                 [--yes]
+                          Calibrate the zero point (400 ppm) after asking for
+                          confirmation (skipped with --yes). With --verify,
+                          readings are taken for the given duration first and
+                          the calibration is aborted if they vary by more than
+                          the tolerance (default 20 ppm).
+  calibrate wizard --device <path> [--tolerance <ppm>]
+                   [--settle-time <duration>]
+                          Interactively guide through the zero (and optionally
+                          span) point calibration: waits for readings to
+                          stabilize within the tolerance, lets the sensor
+                          settle (default 20m), and asks before sending the
+                          calibration commands.
+  range [get] --device <path>
+                          Print the detection range of the sensor.
+  range set <2000|5000|10000> --device <path>
+                          Set the detection range of the sensor and print the
+                          value read back for verification.
+  firmware --device <path>
+                          Print the firmware version of the sensor and the
+                          features supported by it.
+  abc [on|off|status] --device <path>
+                          Turn the automatic baseline correction (ABC) on or
+                          off and print its status.
+  raw --device <path> --op <op code> [--args <bytes>]
+                          Send a command frame with the given op code (e.g.
+                          0x84) and up to five comma-separated hexadecimal
+                          argument bytes (e.g. 00,00,00,00,00) and print the
+                          decoded response.
+  sniff --device <path>   Passively listen on a (tapped) serial line and print
+                          the decoded frames until Ctrl-C is pressed.
+  decode [<hex>...]       Decode frames given as hexadecimal bytes (e.g. from
+                          a logic analyzer capture) as arguments or, without
+                          arguments, on stdin, and print their validity and
+                          meaning.
+  selftest --device <path> [--reads <n>]
+                          Probe the sensor, check the firmware, perform <n>
+                          readings (default 10), and print a diagnostic report
+                          including response latencies and error rates.
+  check --device <path> --warn <ppm> --crit <ppm>
+                          Read the sensor once and print a status line
+                          following the Nagios plugin conventions. Exits with
+                          0 (OK), 1 (WARNING), 2 (CRITICAL), or 3 (UNKNOWN).
+  tui --device <path> [--interval <duration>]
+                          Show a live view of the readings with a sparkline
+                          of recent values, refreshed every interval (default
+                          2s).
+  log --device <path> --out <file> [--interval <duration>]
+      [--rotate never|daily]
+                          Append timestamped readings every interval (default
+                          10s) to a CSV file until Ctrl-C is pressed. With
+                          daily rotation, the file of the previous day (UTC)
+                          is renamed to include its date (e.g.
+                          co2.2023-02-17.csv, or co2.2023-02-17.1.csv if that
+                          file already exists).
+  export-prom --device <path> [--timestamps] [--prefix <prefix>]
+              [--label <name>=<value>]...
+                          Read the sensor once and print the metrics in the
+                          Prometheus text exposition format (e.g. for the
+                          textfile collector of the node exporter). With
+                          --timestamps, the samples carry the time of the
+                          reading (not supported by the textfile collector).
+                          The metric names start with the prefix (default
+                          mhz19c) and the given constant labels are added to
+                          all samples.
+
+Options:
+  --simulate              Use the built-in sensor simulator instead of the
+                          sensor at --device <path>.
+  --format <format>       Output format of readings: plain (default), json
+                          (one object per line), or csv.
+  --log-target <target>   Destination of error and status messages: stderr
+                          (default), syslog, or journald.
+  --help                  Print this help.";
+
+fn main() -> ExitCode {
+    let mut args = Args::from_env();
+    if args.flag("--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    match args.parsed_option("--log-target") {
+        Ok(target) => logging::set_target(target.unwrap_or_default()),
+        Err(err) => return report(err.into()),
+    }
+
+    let result = match args.positional().as_deref() {
+        Some("read") => read::run(args),
+        Some("monitor") => monitor::run(args),
+        Some("calibrate") => calibrate::run(args),
+        Some("range") => range::run(args),
+        Some("firmware") => firmware::run(args),
+        Some("abc") => abc::run(args),
+        Some("raw") => raw::run(args),
+        Some("sniff") => sniff::run(args),
+        Some("decode") => decode::run(args),
+        Some("selftest") => selftest::run(args),
+        Some("check") => return check::run(args),
+        Some("tui") => tui::run(args),
+        Some("log") => log::run(args),
+        Some("export-prom") => prom::run(args),
+        Some(command) => Err(ArgError::UnknownCommand(command.into()).into()),
+        None => Err(ArgError::Missing("<command>".into()).into()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report(err),
+    }
+}
+
+fn report(err: Box<dyn std::error::Error>) -> ExitCode {
+    if logging::target() == logging::Target::Stderr {
+        eprintln!("error: {err}");
+        if err.is::<ArgError>() {
+            eprintln!("Run 'mhz19c --help' for usage information.");
+        }
+    } else {
+        logging::log(logging::Priority::Error, &err.to_string());
+    }
+    ExitCode::FAILURE
+}
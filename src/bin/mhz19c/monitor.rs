@@ -0,0 +1,51 @@
+//! The `monitor` subcommand.
+
+use crate::args::Args;
+use crate::device::{self, Device};
+use crate::logging::{self, Priority};
+use crate::output::Format;
+use crate::read::read;
+use crate::signal;
+use std::io::{self, Write};
+use std::time::{Duration, Instant, SystemTime};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let interval = args
+        .duration_option("--interval")?
+        .unwrap_or(DEFAULT_INTERVAL);
+    let count: Option<u64> = args.parsed_option("--count")?;
+    let format: Format = args.parsed_option("--format")?.unwrap_or_default();
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    signal::install_handler();
+
+    if let Some(header) = format.header() {
+        println!("{header}");
+    }
+
+    let start = Instant::now();
+    let mut n: u64 = 0;
+    while count.is_none_or(|count| n < count) && !signal::interrupted() {
+        if n > 0 {
+            signal::sleep_until(signal::nth_tick(start, interval, n));
+            if signal::interrupted() {
+                break;
+            }
+        }
+
+        let timestamp = SystemTime::now();
+        match read(&mut sensor) {
+            Ok(reading) => {
+                println!("{}", format.format_reading(timestamp, &reading));
+                io::stdout().flush()?;
+            }
+            Err(err) => logging::log(Priority::Error, &err.to_string()),
+        }
+        n += 1;
+    }
+    Ok(())
+}
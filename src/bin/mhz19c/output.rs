@@ -0,0 +1,124 @@
+//! Output formats for readings.
+
+use crate::read::Reading;
+use crate::time::format_rfc3339;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// Output format selected with the `--format` option.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Human-readable text.
+    #[default]
+    Plain,
+    /// One JSON object per line.
+    Json,
+    /// Comma-separated values with a header line.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Format {
+    /// Returns the header line to print before the first reading, if any.
+    pub fn header(self) -> Option<&'static str> {
+        match self {
+            Self::Csv => Some("timestamp,co2_ppm,temp_celsius"),
+            Self::Plain | Self::Json => None,
+        }
+    }
+
+    /// Formats a `reading` taken at `timestamp` as a single line.
+    pub fn format_reading(self, timestamp: SystemTime, reading: &Reading) -> String {
+        let timestamp = format_rfc3339(timestamp);
+        match self {
+            Self::Plain => {
+                let mut line = format!("{timestamp}  CO₂: {} ppm", reading.co2_ppm);
+                if let Some(temp_celsius) = reading.temp_celsius {
+                    line.push_str(&format!("  Temperature: {temp_celsius:.1} °C"));
+                }
+                line
+            }
+            Self::Json => format!(
+                r#"{{"timestamp":"{timestamp}","co2_ppm":{},"temp_celsius":{}}}"#,
+                reading.co2_ppm,
+                reading
+                    .temp_celsius
+                    .map_or_else(|| "null".into(), |temp| temp.to_string())
+            ),
+            Self::Csv => format!(
+                "{timestamp},{},{}",
+                reading.co2_ppm,
+                reading
+                    .temp_celsius
+                    .map_or_else(String::new, |temp| temp.to_string())
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const READING: Reading = Reading {
+        co2_ppm: 800,
+        temp_celsius: Some(24.5),
+    };
+    const READING_WITHOUT_TEMP: Reading = Reading {
+        co2_ppm: 800,
+        temp_celsius: None,
+    };
+
+    fn timestamp() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_676_637_296)
+    }
+
+    #[test]
+    fn test_format_plain() {
+        assert_eq!(
+            Format::Plain.format_reading(timestamp(), &READING),
+            "2023-02-17T12:34:56Z  CO₂: 800 ppm  Temperature: 24.5 °C"
+        );
+        assert_eq!(
+            Format::Plain.format_reading(timestamp(), &READING_WITHOUT_TEMP),
+            "2023-02-17T12:34:56Z  CO₂: 800 ppm"
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            Format::Json.format_reading(timestamp(), &READING),
+            r#"{"timestamp":"2023-02-17T12:34:56Z","co2_ppm":800,"temp_celsius":24.5}"#
+        );
+        assert_eq!(
+            Format::Json.format_reading(timestamp(), &READING_WITHOUT_TEMP),
+            r#"{"timestamp":"2023-02-17T12:34:56Z","co2_ppm":800,"temp_celsius":null}"#
+        );
+    }
+
+    #[test]
+    fn test_format_csv() {
+        assert_eq!(
+            Format::Csv.format_reading(timestamp(), &READING),
+            "2023-02-17T12:34:56Z,800,24.5"
+        );
+        assert_eq!(
+            Format::Csv.format_reading(timestamp(), &READING_WITHOUT_TEMP),
+            "2023-02-17T12:34:56Z,800,"
+        );
+    }
+}
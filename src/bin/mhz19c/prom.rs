@@ -0,0 +1,266 @@
+//! The `export-prom` subcommand printing the Prometheus exposition format.
+
+use crate::args::{ArgError, Args};
+use crate::device::{self, Device};
+use crate::read::{read, Reading};
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_PREFIX: &str = "mhz19c";
+
+/// Options of the exposition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Options {
+    /// Prefix of the metric names.
+    prefix: String,
+    /// Constant labels added to all samples after the `device` label.
+    labels: Vec<(String, String)>,
+    /// Whether the samples of the reading carry the time of the reading, so
+    /// that Prometheus records the time of the measurement instead of the
+    /// scrape. Note that the textfile collector of the node exporter rejects
+    /// samples with timestamps.
+    sample_timestamps: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            prefix: DEFAULT_PREFIX.into(),
+            labels: vec![],
+            sample_timestamps: false,
+        }
+    }
+}
+
+impl Options {
+    /// Consumes the `--prefix`, `--label` (repeatable), and `--timestamps`
+    /// options.
+    fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        let mut options = Self::default();
+        if let Some(prefix) = args.option("--prefix")? {
+            if !is_valid_name(&prefix) {
+                return Err(ArgError::InvalidValue {
+                    arg: "--prefix".into(),
+                    value: prefix,
+                });
+            }
+            options.prefix = prefix;
+        }
+        while let Some(label) = args.option("--label")? {
+            match parse_label(&label) {
+                Some(label) if !options.labels.iter().any(|(name, _)| *name == label.0) => {
+                    options.labels.push(label)
+                }
+                _ => {
+                    return Err(ArgError::InvalidValue {
+                        arg: "--label".into(),
+                        value: label,
+                    })
+                }
+            }
+        }
+        options.sample_timestamps = args.flag("--timestamps");
+        Ok(options)
+    }
+}
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let options = Options::from_args(&mut args)?;
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    let reading = read(&mut sensor)?;
+    let timestamp = SystemTime::now();
+    print!(
+        "{}",
+        format_metrics(&options, &device.to_string(), timestamp, &reading)
+    );
+    Ok(())
+}
+
+/// Formats the `reading` taken at `timestamp` in the Prometheus text
+/// exposition format with the `device` as label.
+fn format_metrics(
+    options: &Options,
+    device: &str,
+    timestamp: SystemTime,
+    reading: &Reading,
+) -> String {
+    let prefix = &options.prefix;
+    let mut labels = format!("device=\"{}\"", escape_label_value(device));
+    for (name, value) in &options.labels {
+        write!(labels, ",{name}=\"{}\"", escape_label_value(value)).unwrap();
+    }
+    let labels = format!("{{{labels}}}");
+    let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let sample_timestamp = if options.sample_timestamps {
+        format!(" {}", since_epoch.as_millis())
+    } else {
+        String::new()
+    };
+    let mut metrics = format!(
+        "# HELP {prefix}_co2_ppm CO2 concentration in parts per million.\n\
+         # TYPE {prefix}_co2_ppm gauge\n\
+         {prefix}_co2_ppm{labels} {}{sample_timestamp}\n",
+        reading.co2_ppm
+    );
+    if let Some(temp_celsius) = reading.temp_celsius {
+        write!(
+            metrics,
+            "# HELP {prefix}_temperature_celsius Temperature measured by the sensor in degrees Celsius.\n\
+             # TYPE {prefix}_temperature_celsius gauge\n\
+             {prefix}_temperature_celsius{labels} {temp_celsius}{sample_timestamp}\n"
+        )
+        .unwrap();
+    }
+    write!(
+        metrics,
+        "# HELP {prefix}_last_read_timestamp_seconds Time of the reading as Unix timestamp in seconds.\n\
+         # TYPE {prefix}_last_read_timestamp_seconds gauge\n\
+         {prefix}_last_read_timestamp_seconds{labels} {}.{:03}\n",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis()
+    )
+    .unwrap();
+    metrics
+}
+
+/// Returns whether `name` is a valid metric or label name (without colons).
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses a constant label given as `<name>=<value>`. The `device` label and
+/// names reserved by Prometheus (starting with `__`) are rejected.
+fn parse_label(label: &str) -> Option<(String, String)> {
+    let (name, value) = label.split_once('=')?;
+    if !is_valid_name(name) || name == "device" || name.starts_with("__") {
+        return None;
+    }
+    Some((name.into(), value.into()))
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn timestamp() -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(1_676_592_000_250)
+    }
+
+    #[test]
+    fn test_format_metrics() {
+        let reading = Reading {
+            co2_ppm: 800,
+            temp_celsius: Some(25.5),
+        };
+        assert_eq!(
+            format_metrics(&Options::default(), "/dev/ttyUSB0", timestamp(), &reading),
+            "# HELP mhz19c_co2_ppm CO2 concentration in parts per million.\n\
+             # TYPE mhz19c_co2_ppm gauge\n\
+             mhz19c_co2_ppm{device=\"/dev/ttyUSB0\"} 800\n\
+             # HELP mhz19c_temperature_celsius Temperature measured by the sensor in degrees Celsius.\n\
+             # TYPE mhz19c_temperature_celsius gauge\n\
+             mhz19c_temperature_celsius{device=\"/dev/ttyUSB0\"} 25.5\n\
+             # HELP mhz19c_last_read_timestamp_seconds Time of the reading as Unix timestamp in seconds.\n\
+             # TYPE mhz19c_last_read_timestamp_seconds gauge\n\
+             mhz19c_last_read_timestamp_seconds{device=\"/dev/ttyUSB0\"} 1676592000.250\n"
+        );
+    }
+
+    #[test]
+    fn test_format_metrics_with_sample_timestamps() {
+        let reading = Reading {
+            co2_ppm: 800,
+            temp_celsius: Some(25.5),
+        };
+        let options = Options {
+            sample_timestamps: true,
+            ..Options::default()
+        };
+        let metrics = format_metrics(&options, "/dev/ttyUSB0", timestamp(), &reading);
+        assert!(metrics.contains("mhz19c_co2_ppm{device=\"/dev/ttyUSB0\"} 800 1676592000250\n"));
+        assert!(metrics
+            .contains("mhz19c_temperature_celsius{device=\"/dev/ttyUSB0\"} 25.5 1676592000250\n"));
+        assert!(metrics.contains(
+            "mhz19c_last_read_timestamp_seconds{device=\"/dev/ttyUSB0\"} 1676592000.250\n"
+        ));
+    }
+
+    #[test]
+    fn test_format_metrics_without_temperature() {
+        let reading = Reading {
+            co2_ppm: 800,
+            temp_celsius: None,
+        };
+        assert!(
+            !format_metrics(&Options::default(), "/dev/ttyUSB0", timestamp(), &reading)
+                .contains("temperature")
+        );
+    }
+
+    #[test]
+    fn test_format_metrics_with_prefix_and_labels() {
+        let reading = Reading {
+            co2_ppm: 800,
+            temp_celsius: None,
+        };
+        let options = Options {
+            prefix: "office".into(),
+            labels: vec![("room".into(), "2.14".into()), ("floor".into(), "2".into())],
+            sample_timestamps: false,
+        };
+        let metrics = format_metrics(&options, "/dev/ttyUSB0", timestamp(), &reading);
+        assert!(metrics.contains("# TYPE office_co2_ppm gauge\n"));
+        assert!(metrics
+            .contains("office_co2_ppm{device=\"/dev/ttyUSB0\",room=\"2.14\",floor=\"2\"} 800\n"));
+        assert!(!metrics.contains("mhz19c"));
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let mut args = Args::new(
+            ["--label", "room=kitchen", "--prefix=home", "--label=id=a=b"].map(String::from),
+        );
+        let options = Options::from_args(&mut args).unwrap();
+        assert_eq!(options.prefix, "home");
+        assert_eq!(
+            options.labels,
+            [
+                ("room".into(), "kitchen".into()),
+                ("id".into(), "a=b".into())
+            ]
+        );
+        assert!(!options.sample_timestamps);
+        args.finish().unwrap();
+
+        for args in [
+            &["--prefix", "1st"][..],
+            &["--label", "room"],
+            &["--label", "device=x"],
+            &["--label", "__name__=x"],
+            &["--label", "a=1", "--label", "a=2"],
+        ] {
+            let mut args = Args::new(args.iter().map(|&arg| arg.to_string()));
+            assert!(Options::from_args(&mut args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -0,0 +1,20 @@
+//! Interactive confirmation prompts.
+
+use std::io::{self, BufRead, Write};
+
+/// Asks the user to confirm `question` on the terminal.
+///
+/// Only an explicit "y" or "yes" answer counts as confirmation.
+pub fn confirm(question: &str) -> io::Result<bool> {
+    let answer = ask(&format!("{question} [y/N]"))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Asks the user `question` on the terminal and returns the trimmed answer.
+pub fn ask(question: &str) -> io::Result<String> {
+    eprint!("{question} ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().into())
+}
@@ -0,0 +1,44 @@
+//! The `range` subcommand.
+
+use crate::args::{parse, ArgError, Args};
+use crate::device::{self, wait, Device};
+use mh_z19c::profile::{DeviceProfile, MhZ19CProfile};
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let action = args.positional();
+    let range_ppm: Option<u16> = match action.as_deref() {
+        None | Some("get") => None,
+        Some("set") => {
+            let value = args
+                .positional()
+                .ok_or_else(|| ArgError::Missing("<range>".into()))?;
+            let range_ppm = parse("<range>", value.clone())?;
+            if !MhZ19CProfile::DETECTION_RANGES_PPM.contains(&range_ppm) {
+                return Err(ArgError::InvalidValue {
+                    arg: "<range>".into(),
+                    value,
+                }
+                .into());
+            }
+            Some(range_ppm)
+        }
+        Some(action) => return Err(ArgError::UnknownCommand(format!("range {action}")).into()),
+    };
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    if let Some(range_ppm) = range_ppm {
+        wait(&mut sensor, |sensor| sensor.set_detection_range(range_ppm))?;
+    }
+    let actual_range_ppm = wait(&mut sensor, |sensor| sensor.get_detection_range())?;
+    println!("Detection range: {actual_range_ppm} ppm");
+
+    match range_ppm {
+        Some(range_ppm) if range_ppm != actual_range_ppm => Err(format!(
+            "failed to set detection range (requested {range_ppm} ppm, but sensor reports {actual_range_ppm} ppm)"
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
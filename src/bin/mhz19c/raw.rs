@@ -0,0 +1,103 @@
+//! The `raw` subcommand.
+
+use crate::args::{ArgError, Args};
+use crate::decode::hex;
+use crate::device::{self, check_deadline, Device, RESPONSE_TIMEOUT};
+use embedded_hal::serial::{Read, Write};
+use mh_z19c::frame::Frame;
+use std::time::Instant;
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let op_code = args.required_option("--op")?;
+    let op_code = parse_byte(&op_code).ok_or(ArgError::InvalidValue {
+        arg: "--op".into(),
+        value: op_code,
+    })?;
+    let command_args = args.option("--args")?.unwrap_or_default();
+    let command_args = parse_args(&command_args).ok_or(ArgError::InvalidValue {
+        arg: "--args".into(),
+        value: command_args,
+    })?;
+    args.finish()?;
+
+    let mut command = [op_code, 0, 0, 0, 0, 0];
+    command[1..].copy_from_slice(&command_args);
+    let frame = Frame::from_serialized_command(command);
+
+    let mut uart = device::open_uart(&device)?;
+    let deadline = Instant::now() + RESPONSE_TIMEOUT;
+    for &byte in frame.as_ref() {
+        nb::block!(uart.write(byte))?;
+    }
+    nb::block!(uart.flush())?;
+    println!("Sent:     {}", hex(frame.as_ref()));
+    println!("          {}", frame.annotate());
+
+    let mut response = [0u8; 9];
+    let mut received = 0;
+    while received < response.len() {
+        match uart.read() {
+            Ok(byte) => {
+                response[received] = byte;
+                received += 1;
+            }
+            Err(nb::Error::WouldBlock) => {
+                if check_deadline(deadline).is_err() {
+                    break;
+                }
+            }
+            Err(nb::Error::Other(err)) => return Err(err.into()),
+        }
+    }
+
+    if received < response.len() {
+        println!("Received: {}", hex(&response[..received]));
+        println!("          incomplete response (timed out after {received} bytes)");
+    } else {
+        println!("Received: {}", hex(&response));
+        println!("          {}", Frame::new(response).annotate());
+    }
+    Ok(())
+}
+
+/// Parses a byte given in decimal or hexadecimal notation with `0x` prefix.
+fn parse_byte(value: &str) -> Option<u8> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// Parses up to five comma-separated hexadecimal bytes. Missing bytes are
+/// filled up with zeros.
+fn parse_args(value: &str) -> Option<[u8; 5]> {
+    let mut args = [0u8; 5];
+    if value.is_empty() {
+        return Some(args);
+    }
+    for (i, byte) in value.split(',').enumerate() {
+        *args.get_mut(i)? = u8::from_str_radix(byte.trim_start_matches("0x"), 16).ok()?;
+    }
+    Some(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_byte() {
+        assert_eq!(parse_byte("0x84"), Some(0x84));
+        assert_eq!(parse_byte("132"), Some(0x84));
+        assert_eq!(parse_byte("0x100"), None);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(""), Some([0; 5]));
+        assert_eq!(parse_args("a0,0x01"), Some([0xa0, 0x01, 0, 0, 0]));
+        assert_eq!(parse_args("00,00,00,00,00,00"), None);
+        assert_eq!(parse_args("zz"), None);
+    }
+}
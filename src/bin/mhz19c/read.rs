@@ -0,0 +1,122 @@
+//! The `read` subcommand.
+
+use crate::args::Args;
+use crate::device::{self, abort_after_deadline, wait_until, Device, RESPONSE_TIMEOUT};
+use crate::output::Format;
+use embedded_hal::serial::{Read, Write};
+use mh_z19c::profile::DeviceProfile;
+use mh_z19c::{Error, MhZ19C};
+use std::fmt::{Debug, Display};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A single measurement of the sensor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reading {
+    pub co2_ppm: u16,
+    /// Only available with firmware 5 or higher.
+    pub temp_celsius: Option<f32>,
+}
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let format: Format = args.parsed_option("--format")?.unwrap_or_default();
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    let reading = read(&mut sensor)?;
+    if format == Format::Plain {
+        println!("CO₂: {} ppm", reading.co2_ppm);
+        if let Some(temp_celsius) = reading.temp_celsius {
+            println!("Temperature: {temp_celsius:.1} °C");
+        }
+    } else {
+        if let Some(header) = format.header() {
+            println!("{header}");
+        }
+        println!("{}", format.format_reading(SystemTime::now(), &reading));
+    }
+    Ok(())
+}
+
+/// Reads the CO₂ concentration and, if supported by the firmware, the
+/// temperature.
+///
+/// If the sensor does not respond within the [`RESPONSE_TIMEOUT`], the
+/// command is aborted and a timeout error returned.
+pub fn read<U, E, P>(
+    sensor: &mut MhZ19C<'_, U, E, P>,
+) -> Result<Reading, Box<dyn std::error::Error>>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    E: Debug + Display + 'static,
+    P: DeviceProfile,
+{
+    read_with_timeout(sensor, RESPONSE_TIMEOUT)
+}
+
+fn read_with_timeout<U, E, P>(
+    sensor: &mut MhZ19C<'_, U, E, P>,
+    timeout: Duration,
+) -> Result<Reading, Box<dyn std::error::Error>>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    E: Debug + Display + 'static,
+    P: DeviceProfile,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        match sensor.read_co2_and_temp() {
+            Ok(reading) => {
+                return Ok(Reading {
+                    co2_ppm: reading.co2_ppm,
+                    temp_celsius: Some(reading.temp_celsius),
+                });
+            }
+            Err(nb::Error::WouldBlock) => abort_after_deadline(sensor, deadline)?,
+            Err(nb::Error::Other(Error::NotSupportedByFirmware(_))) => break,
+            Err(nb::Error::Other(err)) => return Err(err.into()),
+        }
+    }
+    Ok(Reading {
+        co2_ppm: wait_until(sensor, deadline, MhZ19C::read_co2_ppm)?,
+        temp_celsius: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mh_z19c::command::Command;
+    use mh_z19c::firmware::FirmwareVersion;
+    use mh_z19c::frame::Frame;
+    use test_support::serial_mock::SerialMock;
+    use test_support::READ_CO2_AND_TEMPERATURE_RESPONSE;
+
+    #[test]
+    fn test_read_sends_new_command_after_timeout() {
+        let reads = vec![Err(nb::Error::WouldBlock), Err(nb::Error::WouldBlock)]
+            .into_iter()
+            .chain(READ_CO2_AND_TEMPERATURE_RESPONSE.iter().copied().map(Ok))
+            .collect();
+        let mut sensor = MhZ19C::builder()
+            .firmware_version(FirmwareVersion::new(*b"0515"))
+            .build(SerialMock::new(reads, vec![Ok(()); 2 * 9]));
+
+        // The response to the first command is lost.
+        let err = read_with_timeout(&mut sensor, Duration::ZERO).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>()
+                .map(std::io::Error::kind),
+            Some(std::io::ErrorKind::TimedOut)
+        );
+        assert!(!sensor.is_busy());
+
+        let reading = read(&mut sensor).unwrap();
+        let command = Frame::from(Command::ReadCo2AndTemperature);
+        assert_eq!(reading.co2_ppm, 800);
+        assert_eq!(
+            sensor.into_inner().write_buf,
+            [command.as_ref(), command.as_ref()].concat()
+        );
+    }
+}
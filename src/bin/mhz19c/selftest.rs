@@ -0,0 +1,153 @@
+//! The `selftest` subcommand.
+
+use crate::args::Args;
+use crate::device::{self, wait, Device, Sensor};
+use mh_z19c::frame::ValidateFrameError;
+use std::error::Error;
+use std::io::{self, ErrorKind};
+use std::time::{Duration, Instant};
+
+const DEFAULT_READS: u32 = 10;
+const PLAUSIBLE_CO2_PPM: std::ops::RangeInclusive<u16> = 1..=10000;
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let device = Device::from_args(&mut args)?;
+    let reads = args.parsed_option("--reads")?.unwrap_or(DEFAULT_READS);
+    args.finish()?;
+
+    println!("Self-test of the sensor at {device}");
+    let mut report = Report::default();
+    match device::open(&device) {
+        Ok(mut sensor) => {
+            report.check("open serial device", Ok(()));
+            check_firmware(&mut sensor, &mut report);
+            check_reads(&mut sensor, reads, &mut report);
+        }
+        Err(err) => report.check("open serial device", Err(err.to_string())),
+    }
+
+    if report.passed {
+        println!("Result: PASS");
+        Ok(())
+    } else {
+        println!("Result: FAIL");
+        Err("self-test failed".into())
+    }
+}
+
+#[derive(Debug)]
+struct Report {
+    passed: bool,
+}
+
+impl Default for Report {
+    fn default() -> Self {
+        Self { passed: true }
+    }
+}
+
+impl Report {
+    /// Prints the outcome of a single check of the self-test.
+    fn check(&mut self, description: &str, result: Result<(), String>) {
+        match result {
+            Ok(()) => println!("[PASS] {description}"),
+            Err(reason) => {
+                self.passed = false;
+                println!("[FAIL] {description}: {reason}");
+            }
+        }
+    }
+}
+
+fn check_firmware(sensor: &mut Sensor, report: &mut Report) {
+    let start = Instant::now();
+    match wait(sensor, |sensor| sensor.get_firmware_version()) {
+        Ok(version) => report.check(
+            &format!(
+                "firmware version {version} ({} ms)",
+                start.elapsed().as_millis()
+            ),
+            Ok(()),
+        ),
+        Err(err) => report.check("firmware version", Err(err.to_string())),
+    }
+}
+
+fn check_reads(sensor: &mut Sensor, reads: u32, report: &mut Report) {
+    let mut latencies: Vec<Duration> = vec![];
+    let mut readings = vec![];
+    let (mut checksum_errors, mut timeouts, mut other_errors) = (0, 0, 0);
+    for _ in 0..reads {
+        let start = Instant::now();
+        match wait(sensor, |sensor| sensor.read_co2_ppm()) {
+            Ok(co2_ppm) => {
+                latencies.push(start.elapsed());
+                readings.push(co2_ppm);
+            }
+            Err(err) if is_checksum_error(&*err) => checksum_errors += 1,
+            Err(err) if is_timeout(&*err) => timeouts += 1,
+            Err(_) => other_errors += 1,
+        }
+    }
+
+    let failed = reads - readings.len() as u32;
+    report.check(
+        &format!(
+            "{}/{reads} CO₂ readings successful (checksum errors: {checksum_errors}, timeouts: {timeouts}, other errors: {other_errors})",
+            readings.len()
+        ),
+        if failed == 0 {
+            Ok(())
+        } else {
+            Err(format!(
+                "error rate {:.0} %",
+                f64::from(failed) / f64::from(reads) * 100.
+            ))
+        },
+    );
+
+    if sensor.likely_baud_mismatch() {
+        println!(
+            "       hint: all recently received frames were invalid, the baud rate likely does not match (the sensor uses 9600 baud by default)"
+        );
+    }
+
+    if let (Some(min), Some(max)) = (latencies.iter().min(), latencies.iter().max()) {
+        let avg = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+        println!(
+            "       response latency: min {} ms, avg {} ms, max {} ms",
+            min.as_millis(),
+            avg.as_millis(),
+            max.as_millis()
+        );
+    }
+
+    if let (Some(&min), Some(&max)) = (readings.iter().min(), readings.iter().max()) {
+        let description = format!("readings plausible ({min} to {max} ppm)");
+        if PLAUSIBLE_CO2_PPM.contains(&min) && PLAUSIBLE_CO2_PPM.contains(&max) {
+            report.check(&description, Ok(()));
+        } else {
+            report.check(
+                &description,
+                Err(format!(
+                    "expected values from {} to {} ppm",
+                    PLAUSIBLE_CO2_PPM.start(),
+                    PLAUSIBLE_CO2_PPM.end()
+                )),
+            );
+        }
+    }
+}
+
+fn is_checksum_error(err: &(dyn Error + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<mh_z19c::Error<io::Error>>(),
+        Some(mh_z19c::Error::ValidateFrameError(
+            ValidateFrameError::InvalidChecksum { .. }
+        ))
+    )
+}
+
+fn is_timeout(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref::<io::Error>(), Some(err) if err.kind() == ErrorKind::TimedOut)
+}
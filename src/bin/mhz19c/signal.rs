@@ -0,0 +1,66 @@
+//! Graceful handling of interrupt (Ctrl-C) and termination signals.
+
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Returns `true` once an interrupt or termination signal has been received
+/// after calling [`install_handler`].
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Installs a handler for the `SIGINT` and `SIGTERM` signals that sets the
+/// [`interrupted`] flag instead of terminating the process.
+#[cfg(unix)]
+pub fn install_handler() {
+    use std::os::raw::c_int;
+
+    const SIGINT: c_int = 2;
+    const SIGTERM: c_int = 15;
+
+    extern "C" {
+        fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+    }
+
+    extern "C" fn handle(_signum: c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    // SAFETY: The handler only stores to an atomic which is async-signal-safe.
+    unsafe {
+        signal(SIGINT, handle);
+        signal(SIGTERM, handle);
+    }
+}
+
+/// Signals are not handled on this platform and terminate the process.
+#[cfg(not(unix))]
+pub fn install_handler() {}
+
+/// Sleeps until `deadline` or an interrupt signal is received.
+pub fn sleep_until(deadline: Instant) {
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+    while !interrupted() {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Returns the instant of the `n`-th tick of a schedule starting at `start`
+/// with the given `interval`.
+///
+/// Falls back to one `interval` from now if the tick is not representable.
+pub fn nth_tick(start: Instant, interval: Duration, n: u64) -> Instant {
+    u32::try_from(n)
+        .ok()
+        .and_then(|n| interval.checked_mul(n))
+        .and_then(|offset| start.checked_add(offset))
+        .unwrap_or_else(|| Instant::now() + interval)
+}
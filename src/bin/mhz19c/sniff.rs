@@ -0,0 +1,53 @@
+//! The `sniff` subcommand.
+
+use crate::args::Args;
+use crate::decode::{annotate, hex, RawDecoder};
+use crate::device::{self, Device};
+use crate::signal;
+use crate::time::format_rfc3339;
+use embedded_hal::serial::Read;
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    args.finish()?;
+
+    let mut uart = device::open_uart(&device)?;
+    signal::install_handler();
+
+    let mut decoder = RawDecoder::default();
+    while !signal::interrupted() {
+        let byte = match uart.read() {
+            Ok(byte) => byte,
+            Err(nb::Error::WouldBlock) => continue,
+            Err(nb::Error::Other(err)) => return Err(err.into()),
+        };
+        if let Some(decoded) = decoder.push(byte) {
+            let timestamp = format_rfc3339(SystemTime::now());
+            print_discarded(&timestamp, &decoded.skipped);
+            println!(
+                "{timestamp}  {}  {}",
+                hex(&decoded.bytes),
+                annotate(&decoded.event)
+            );
+            io::stdout().flush()?;
+        }
+    }
+
+    print_discarded(
+        &format_rfc3339(SystemTime::now()),
+        &decoder.take_remaining(),
+    );
+    Ok(())
+}
+
+fn print_discarded(timestamp: &str, discarded: &[u8]) {
+    if !discarded.is_empty() {
+        println!(
+            "{timestamp}  {}  discarded {} bytes not forming a valid frame",
+            hex(discarded),
+            discarded.len()
+        );
+    }
+}
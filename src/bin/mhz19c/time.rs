@@ -0,0 +1,25 @@
+//! Formatting of timestamps.
+
+pub use mh_z19c::timestamp::format_rfc3339;
+use std::time::SystemTime;
+
+/// Formats the UTC date of `time` (e.g. `2023-02-17`).
+pub fn format_date(time: SystemTime) -> String {
+    let mut timestamp = format_rfc3339(time);
+    timestamp.truncate(10);
+    timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(
+            format_date(UNIX_EPOCH + Duration::from_secs(1_676_637_296)),
+            "2023-02-17"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! The `tui` subcommand rendering a live view with ANSI escape sequences.
+
+use crate::args::Args;
+use crate::device::{self, Device};
+use crate::read::{read, Reading};
+use crate::signal;
+use crate::time::format_rfc3339;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::{Duration, Instant, SystemTime};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_WIDTH: usize = 60;
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h\x1b[?25l";
+const LEAVE_ALTERNATE_SCREEN: &str = "\x1b[?25h\x1b[?1049l";
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let device = Device::from_args(&mut args)?;
+    let interval = args
+        .duration_option("--interval")?
+        .unwrap_or(DEFAULT_INTERVAL);
+    args.finish()?;
+
+    let mut sensor = device::open(&device)?;
+    signal::install_handler();
+
+    let width = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse::<usize>().ok())
+        .map_or(DEFAULT_WIDTH, |columns| columns.saturating_sub(4).max(10));
+    let mut view = View::new(&device.to_string(), width);
+
+    let mut stdout = io::stdout();
+    write!(stdout, "{ENTER_ALTERNATE_SCREEN}")?;
+    while !signal::interrupted() {
+        let next_update = Instant::now() + interval;
+        let result = read(&mut sensor).map_err(|err| err.to_string());
+        view.update(SystemTime::now(), result);
+        write!(stdout, "{CLEAR_SCREEN}{}", view.render())?;
+        stdout.flush()?;
+        signal::sleep_until(next_update);
+    }
+    write!(stdout, "{LEAVE_ALTERNATE_SCREEN}")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// State of the live view.
+#[derive(Debug)]
+struct View {
+    device: String,
+    history: VecDeque<u16>,
+    width: usize,
+    latest: Option<Reading>,
+    min_ppm: Option<u16>,
+    max_ppm: Option<u16>,
+    status: String,
+}
+
+impl View {
+    fn new(device: &str, width: usize) -> Self {
+        Self {
+            device: device.into(),
+            history: VecDeque::with_capacity(width),
+            width,
+            latest: None,
+            min_ppm: None,
+            max_ppm: None,
+            status: "waiting for first reading".into(),
+        }
+    }
+
+    fn update(&mut self, timestamp: SystemTime, result: Result<Reading, String>) {
+        let timestamp = format_rfc3339(timestamp);
+        match result {
+            Ok(reading) => {
+                if self.history.len() == self.width {
+                    self.history.pop_front();
+                }
+                self.history.push_back(reading.co2_ppm);
+                self.min_ppm = Some(
+                    self.min_ppm
+                        .map_or(reading.co2_ppm, |min| min.min(reading.co2_ppm)),
+                );
+                self.max_ppm = Some(
+                    self.max_ppm
+                        .map_or(reading.co2_ppm, |max| max.max(reading.co2_ppm)),
+                );
+                self.latest = Some(reading);
+                self.status = format!("OK (last update {timestamp})");
+            }
+            Err(err) => self.status = format!("error at {timestamp}: {err}"),
+        }
+    }
+
+    fn render(&self) -> String {
+        let value = |value: Option<String>| value.unwrap_or_else(|| "–".into());
+        format!(
+            "MH-Z19C live view of {}    (Ctrl-C to quit)\n\n  \
+             CO₂           {}\n  \
+             Temperature   {}\n  \
+             Min / Max     {} / {}\n\n  \
+             {}\n\n  \
+             Status: {}\n",
+            self.device,
+            value(
+                self.latest
+                    .map(|reading| format!("{} ppm", reading.co2_ppm))
+            ),
+            value(
+                self.latest
+                    .and_then(|reading| reading.temp_celsius)
+                    .map(|temp| format!("{temp:.1} °C"))
+            ),
+            value(self.min_ppm.map(|min| format!("{min} ppm"))),
+            value(self.max_ppm.map(|max| format!("{max} ppm"))),
+            sparkline(&self.history),
+            self.status
+        )
+    }
+}
+
+/// Renders `values` as a line of block characters scaled between the minimum
+/// and maximum value.
+fn sparkline(values: &VecDeque<u16>) -> String {
+    let (min, max) = match (values.iter().min(), values.iter().max()) {
+        (Some(&min), Some(&max)) => (min, max),
+        _ => return String::new(),
+    };
+    let span = u32::from(max - min).max(1);
+    values
+        .iter()
+        .map(|&value| {
+            let level = u32::from(value - min) * (SPARK_CHARS.len() as u32 - 1) / span;
+            SPARK_CHARS[level as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&VecDeque::new()), "");
+        assert_eq!(sparkline(&VecDeque::from(vec![800, 800])), "▁▁");
+        assert_eq!(sparkline(&VecDeque::from(vec![400, 750, 1100])), "▁▄█");
+    }
+
+    #[test]
+    fn test_view_keeps_history_within_width() {
+        let mut view = View::new("/dev/null", 2);
+        for co2_ppm in [500, 600, 700] {
+            let reading = Reading {
+                co2_ppm,
+                temp_celsius: None,
+            };
+            view.update(SystemTime::now(), Ok(reading));
+        }
+        assert_eq!(view.history, VecDeque::from(vec![600, 700]));
+        assert_eq!((view.min_ppm, view.max_ppm), (Some(500), Some(700)));
+    }
+}
@@ -0,0 +1,239 @@
+//! Builder to configure the driver before constructing it.
+
+use crate::correction::Co2Correction;
+use crate::firmware::FirmwareVersion;
+use crate::frame::Validation;
+use crate::profile::{DeviceProfile, MhZ19CProfile};
+use crate::{MhZ19C, MhZ19CState};
+use core::marker::PhantomData;
+use embedded_hal::serial::{Read, Write};
+
+/// Builder for the [`MhZ19C`] driver.
+///
+/// Obtained with [`MhZ19C::builder`] for the MH-Z19C or
+/// [`MhZ19CBuilder::new`] for other device profiles.
+///
+/// ```
+/// # use mh_z19c::MhZ19C;
+/// # use nb::block;
+/// # use test_support::{create_serial_mock_returning, READ_CO2_RESPONSE};
+/// # fn main() -> Result<(), mh_z19c::Error<String>> {
+/// # let uart = create_serial_mock_returning(&READ_CO2_RESPONSE);
+/// let mut co2sensor = MhZ19C::builder()
+///     .read_ahead(true)
+///     .detection_range_ppm(5000)
+///     .build(uart);
+/// assert_eq!(co2sensor.detection_range_ppm(), Some(5000));
+/// let co2 = block!(co2sensor.read_co2_ppm())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MhZ19CBuilder<'a, U, E, P = MhZ19CProfile> {
+    firmware_version: Option<FirmwareVersion>,
+    detection_range_ppm: Option<u16>,
+    read_ahead: bool,
+    echo_suppression: bool,
+    co2_correction: Co2Correction,
+    temp_offset_celsius: f32,
+    validation: Validation,
+    retries: u8,
+    marker: PhantomData<(&'a (), U, E, P)>,
+}
+
+impl<'a, U, E, P> MhZ19CBuilder<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Create a new builder for a driver of the sensor described by the
+    /// profile `P`.
+    pub fn new() -> Self {
+        Self {
+            firmware_version: None,
+            detection_range_ppm: None,
+            read_ahead: false,
+            echo_suppression: false,
+            co2_correction: Co2Correction::IDENTITY,
+            temp_offset_celsius: 0.0,
+            validation: Validation::STRICT,
+            retries: 0,
+            marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn from_driver(driver: &MhZ19C<'a, U, E, P>) -> Self {
+        Self {
+            firmware_version: driver.firmware_version,
+            detection_range_ppm: driver.detection_range_ppm,
+            read_ahead: driver.read_ahead,
+            echo_suppression: driver.echo_suppression,
+            co2_correction: driver.co2_correction,
+            temp_offset_celsius: driver.temp_offset_celsius,
+            validation: driver.validation,
+            retries: driver.retries,
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets the firmware version of the sensor if known in advance.
+    ///
+    /// The firmware version will not be queried from the sensor to determine
+    /// the supported capabilities (see [`MhZ19C::firmware_version`]).
+    pub fn firmware_version(mut self, firmware_version: FirmwareVersion) -> Self {
+        self.firmware_version = Some(firmware_version);
+        self
+    }
+
+    /// Sets the expected detection range in ppm.
+    ///
+    /// Readings at or above the detection range will be rejected with
+    /// [`crate::Error::OutOfRange`] without querying the range from the
+    /// sensor first (see [`MhZ19C::detection_range_ppm`]).
+    pub fn detection_range_ppm(mut self, range_ppm: u16) -> Self {
+        self.detection_range_ppm = Some(range_ppm);
+        self
+    }
+
+    /// Enables or disables the read-ahead mode (see
+    /// [`MhZ19C::with_read_ahead`]).
+    pub fn read_ahead(mut self, enabled: bool) -> Self {
+        self.read_ahead = enabled;
+        self
+    }
+
+    /// Enables or disables the echo suppression (see
+    /// [`MhZ19C::with_echo_suppression`]).
+    pub fn echo_suppression(mut self, enabled: bool) -> Self {
+        self.echo_suppression = enabled;
+        self
+    }
+
+    /// Sets the software correction applied to the CO₂ readings (see
+    /// [`MhZ19C::with_co2_correction`]).
+    pub fn co2_correction(mut self, correction: Co2Correction) -> Self {
+        self.co2_correction = correction;
+        self
+    }
+
+    /// Sets the offset in °C added to the temperature readings (see
+    /// [`MhZ19C::with_temp_offset_celsius`]).
+    pub fn temp_offset_celsius(mut self, offset_celsius: f32) -> Self {
+        self.temp_offset_celsius = offset_celsius;
+        self
+    }
+
+    /// Sets the strictness of the validation of the received frames (see
+    /// [`MhZ19C::with_validation`]).
+    ///
+    /// Stale bytes left in the receive buffer (e.g. of a cancelled command)
+    /// are drained before each command if
+    /// [`Validation::discard_trailing_bytes`] is set.
+    pub fn validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Sets how often a command is re-issued if its response is corrupted
+    /// (disabled by default).
+    ///
+    /// A command failing with [`crate::Error::ValidateFrameError`] or
+    /// [`crate::Error::EchoMismatch`] is sent again up to `retries` times
+    /// before the error is returned. Other errors are returned immediately.
+    /// Combine with [`Validation::discard_trailing_bytes`] to drain the
+    /// remainder of a garbled response before the command is re-issued.
+    pub fn retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Builds the driver.
+    ///
+    /// * `uart`: Serial (UART) interface for communication with the sensor.
+    pub fn build(self, uart: U) -> MhZ19C<'a, U, E, P> {
+        MhZ19C {
+            state: MhZ19CState::default(),
+            uart,
+            firmware_version: self.firmware_version,
+            detection_range_ppm: self.detection_range_ppm,
+            read_ahead: self.read_ahead,
+            echo_suppression: self.echo_suppression,
+            co2_correction: self.co2_correction,
+            temp_offset_celsius: self.temp_offset_celsius,
+            validation: self.validation,
+            retries: self.retries,
+            retries_used: 0,
+            consecutive_invalid_frames: 0,
+            self_test_firmware_version: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, U, E, P> Default for MhZ19CBuilder<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use nb::block;
+    use test_support::serial_mock::SerialMock;
+    use test_support::{create_serial_mock_returning, READ_CO2_5000_RESPONSE};
+
+    #[test]
+    fn test_build_with_expected_detection_range() {
+        let uart = create_serial_mock_returning(&READ_CO2_5000_RESPONSE);
+        let mut co2sensor = MhZ19C::builder().detection_range_ppm(5000).build(uart);
+        assert_eq!(co2sensor.detection_range_ppm(), Some(5000));
+        assert_eq!(
+            block!(co2sensor.read_co2_ppm()),
+            Err(Error::OutOfRange {
+                co2_ppm: 5000,
+                range_ppm: 5000
+            })
+        );
+    }
+
+    #[test]
+    fn test_rebuild_preserves_configuration() {
+        let uart = create_serial_mock_returning(&READ_CO2_5000_RESPONSE);
+        let co2sensor = MhZ19C::builder()
+            .detection_range_ppm(5000)
+            .firmware_version(FirmwareVersion::new(*b"0515"))
+            .read_ahead(true)
+            .build(uart);
+        let (_, builder) = co2sensor.into_builder();
+        let uart = create_serial_mock_returning(&READ_CO2_5000_RESPONSE);
+        let mut co2sensor = builder.build(uart);
+        assert_eq!(
+            co2sensor.firmware_version(),
+            Some(FirmwareVersion::new(*b"0515"))
+        );
+        assert!(matches!(
+            block!(co2sensor.read_co2_ppm()),
+            Err(Error::OutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_build_with_firmware_version() {
+        let uart = SerialMock::new(vec![], vec![]);
+        let mut co2sensor = MhZ19C::builder()
+            .firmware_version(FirmwareVersion::new(*b"0515"))
+            .build(uart);
+        assert_eq!(co2sensor.init(), Ok(()));
+        assert_eq!(
+            co2sensor.firmware_version(),
+            Some(FirmwareVersion::new(*b"0515"))
+        );
+        assert!(co2sensor.into_inner().write_buf.is_empty());
+    }
+}
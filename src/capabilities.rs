@@ -0,0 +1,164 @@
+//! Firmware dependent capabilities of the sensor.
+
+use crate::command::Command;
+use crate::firmware::FirmwareVersion;
+use core::ops::{BitOr, BitOrAssign};
+
+/// Encoding of the temperature in the responses of a sensor firmware.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TemperatureEncoding {
+    /// Fixed-point temperature in 0.01 °C in the response to
+    /// [`Command::ReadCo2AndTemperature`] (firmware 5 and higher).
+    FixedPoint,
+    /// Temperature in °C offset by 40 °C in a single byte of the response to
+    /// [`Command::ReadCo2`] (firmware below 5).
+    OffsetByte,
+}
+
+impl TemperatureEncoding {
+    /// Returns the encoding used by the sensor firmware with the given
+    /// version.
+    pub fn from_firmware_version(firmware_version: FirmwareVersion) -> Self {
+        if Capabilities::from_firmware_version(firmware_version)
+            .supports(Command::ReadCo2AndTemperature)
+        {
+            Self::FixedPoint
+        } else {
+            Self::OffsetByte
+        }
+    }
+}
+
+/// Set of capabilities supported by a sensor firmware.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+    /// Reading the CO₂ concentration ([`Command::ReadCo2`]).
+    pub const READ_CO2: Self = Self(1 << 0);
+    /// Combined reading of CO₂ concentration and temperature
+    /// ([`Command::ReadCo2AndTemperature`]).
+    pub const READ_CO2_AND_TEMPERATURE: Self = Self(1 << 1);
+    /// Querying and setting the self-calibration mode
+    /// ([`Command::GetSelfCalibrate`], [`Command::SetSelfCalibrate`]).
+    pub const SELF_CALIBRATE: Self = Self(1 << 2);
+    /// Calibration of the zero and span point ([`Command::CalibrateZero`],
+    /// [`Command::CalibrateSpan`]).
+    pub const CALIBRATION: Self = Self(1 << 3);
+    /// Querying and setting the detection range
+    /// ([`Command::GetDetectionRange`], [`Command::SetDetectionRange`]).
+    pub const DETECTION_RANGE: Self = Self(1 << 4);
+
+    /// Capabilities supported by all firmware versions.
+    ///
+    /// Querying the firmware version does not require any capability (see
+    /// [`Capabilities::required_for`]).
+    pub const BASE: Self = Self(
+        Self::READ_CO2.0 | Self::SELF_CALIBRATE.0 | Self::CALIBRATION.0 | Self::DETECTION_RANGE.0,
+    );
+
+    /// Returns an empty set of capabilities.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns the capabilities of a sensor with the given firmware version.
+    ///
+    /// The only documented difference between firmware versions is the
+    /// combined reading of CO₂ concentration and temperature added in
+    /// firmware 5. All other commands are supported by all versions.
+    pub fn from_firmware_version(firmware_version: FirmwareVersion) -> Self {
+        if firmware_version >= "0500" {
+            Self::BASE | Self::READ_CO2_AND_TEMPERATURE
+        } else {
+            Self::BASE
+        }
+    }
+
+    /// Returns the capabilities required to execute `command`.
+    ///
+    /// The driver checks these against the capabilities of the sensor
+    /// firmware before executing a command and returns
+    /// [`Error::NotSupportedByFirmware`](crate::Error::NotSupportedByFirmware)
+    /// if any is missing.
+    pub fn required_for(command: Command) -> Self {
+        match command {
+            Command::GetFirmwareVersion => Self::empty(),
+            Command::ReadCo2 => Self::READ_CO2,
+            Command::ReadCo2AndTemperature => Self::READ_CO2_AND_TEMPERATURE,
+            Command::SetSelfCalibrate(_) | Command::GetSelfCalibrate => Self::SELF_CALIBRATE,
+            Command::CalibrateZero | Command::CalibrateSpan(_) => Self::CALIBRATION,
+            Command::SetDetectionRange(_) | Command::GetDetectionRange => Self::DETECTION_RANGE,
+        }
+    }
+
+    /// Returns the raw bits of the set.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if all capabilities in `other` are contained in this
+    /// set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if the capabilities required by `command` are
+    /// contained in this set.
+    pub fn supports(self, command: Command) -> bool {
+        self.contains(Self::required_for(command))
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Capabilities {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_firmware_version() {
+        let capabilities = Capabilities::from_firmware_version(FirmwareVersion::new(*b"0400"));
+        assert!(capabilities.contains(Capabilities::BASE));
+        assert!(!capabilities.supports(Command::ReadCo2AndTemperature));
+
+        let capabilities = Capabilities::from_firmware_version(FirmwareVersion::new(*b"0515"));
+        assert!(capabilities.contains(Capabilities::BASE));
+        assert!(capabilities.supports(Command::ReadCo2AndTemperature));
+    }
+
+    #[test]
+    fn test_temperature_encoding() {
+        assert_eq!(
+            TemperatureEncoding::from_firmware_version(FirmwareVersion::new(*b"0430")),
+            TemperatureEncoding::OffsetByte
+        );
+        assert_eq!(
+            TemperatureEncoding::from_firmware_version(FirmwareVersion::new(*b"0500")),
+            TemperatureEncoding::FixedPoint
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let capabilities = Capabilities::READ_CO2 | Capabilities::CALIBRATION;
+        assert!(capabilities.contains(Capabilities::READ_CO2));
+        assert!(capabilities.contains(Capabilities::empty()));
+        assert!(!capabilities.contains(Capabilities::READ_CO2 | Capabilities::DETECTION_RANGE));
+        assert!(capabilities.supports(Command::CalibrateSpan(2000)));
+        assert!(!capabilities.supports(Command::GetDetectionRange));
+        assert!(Capabilities::empty().supports(Command::GetFirmwareVersion));
+    }
+}
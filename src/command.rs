@@ -1,5 +1,22 @@
 //! MH-Z19C command definitions.
 
+use crate::frame::Frame;
+#[cfg(feature = "display")]
+use core::fmt::{self, Display};
+
+/// One command per op code to look up commands by op code.
+const COMMANDS: [Command; 9] = [
+    Command::ReadCo2AndTemperature,
+    Command::ReadCo2,
+    Command::GetFirmwareVersion,
+    Command::SetSelfCalibrate(false),
+    Command::GetSelfCalibrate,
+    Command::CalibrateZero,
+    Command::CalibrateSpan(0),
+    Command::SetDetectionRange(0),
+    Command::GetDetectionRange,
+];
+
 /// Commands understood by the MH-Z19C sensor.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Command {
@@ -12,6 +29,16 @@ pub enum Command {
     GetFirmwareVersion,
     /// Set self calibration enabled status.
     SetSelfCalibrate(bool),
+    /// Read out self calibration enabled status.
+    GetSelfCalibrate,
+    /// Calibrate the zero point (400 ppm) to the current reading.
+    CalibrateZero,
+    /// Calibrate the span point to the given concentration in ppm.
+    CalibrateSpan(u16),
+    /// Set the detection range (upper limit) in ppm.
+    SetDetectionRange(u16),
+    /// Read out the detection range (upper limit) in ppm.
+    GetDetectionRange,
 }
 
 impl Command {
@@ -22,6 +49,70 @@ impl Command {
             Self::ReadCo2 => 0x86,
             Self::GetFirmwareVersion => 0xA0,
             Self::SetSelfCalibrate(_) => 0x79,
+            Self::GetSelfCalibrate => 0x7d,
+            Self::CalibrateZero => 0x87,
+            Self::CalibrateSpan(_) => 0x88,
+            Self::SetDetectionRange(_) => 0x99,
+            Self::GetDetectionRange => 0x9b,
+        }
+    }
+
+    /// Returns the name of the command (e.g. `"ReadCo2"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ReadCo2AndTemperature => "ReadCo2AndTemperature",
+            Self::ReadCo2 => "ReadCo2",
+            Self::GetFirmwareVersion => "GetFirmwareVersion",
+            Self::SetSelfCalibrate(_) => "SetSelfCalibrate",
+            Self::GetSelfCalibrate => "GetSelfCalibrate",
+            Self::CalibrateZero => "CalibrateZero",
+            Self::CalibrateSpan(_) => "CalibrateSpan",
+            Self::SetDetectionRange(_) => "SetDetectionRange",
+            Self::GetDetectionRange => "GetDetectionRange",
+        }
+    }
+
+    /// Returns the name of the command with the given `op_code`, or `None`
+    /// if the op code is unknown.
+    pub fn name_of_op_code(op_code: u8) -> Option<&'static str> {
+        COMMANDS
+            .iter()
+            .find(|command| command.op_code() == op_code)
+            .map(Command::name)
+    }
+
+    /// Returns the command sent with a command `frame`, or `None` if the
+    /// frame is a response or its op code is unknown.
+    ///
+    /// The validity of the frame is not checked.
+    pub fn from_frame(frame: &Frame) -> Option<Self> {
+        if frame.is_response() {
+            return None;
+        }
+        let [_, _, op_code, a0, a1, _, a3, a4, _] = frame.clone().into_inner();
+        let command = COMMANDS
+            .iter()
+            .find(|command| command.op_code() == op_code)?;
+        Some(match command {
+            Self::SetSelfCalibrate(_) => Self::SetSelfCalibrate(a0 == 0xa0),
+            Self::CalibrateSpan(_) => Self::CalibrateSpan(u16::from_be_bytes([a0, a1])),
+            Self::SetDetectionRange(_) => Self::SetDetectionRange(u16::from_be_bytes([a3, a4])),
+            command => *command,
+        })
+    }
+
+    /// Returns `true` if the sensor sends a response to the command.
+    pub fn has_response(&self) -> bool {
+        match self {
+            Self::ReadCo2AndTemperature
+            | Self::ReadCo2
+            | Self::GetFirmwareVersion
+            | Self::GetSelfCalibrate
+            | Self::GetDetectionRange => true,
+            Self::SetSelfCalibrate(_)
+            | Self::CalibrateZero
+            | Self::CalibrateSpan(_)
+            | Self::SetDetectionRange(_) => false,
         }
     }
 
@@ -33,6 +124,82 @@ impl Command {
             Self::GetFirmwareVersion => [self.op_code(), 0, 0, 0, 0, 0],
             Self::SetSelfCalibrate(true) => [self.op_code(), 0xa0, 0, 0, 0, 0],
             Self::SetSelfCalibrate(false) => [self.op_code(), 0, 0, 0, 0, 0],
+            Self::GetSelfCalibrate => [self.op_code(), 0, 0, 0, 0, 0],
+            Self::CalibrateZero => [self.op_code(), 0, 0, 0, 0, 0],
+            Self::CalibrateSpan(span_ppm) => {
+                let [high, low] = span_ppm.to_be_bytes();
+                [self.op_code(), high, low, 0, 0, 0]
+            }
+            Self::SetDetectionRange(range_ppm) => {
+                let [high, low] = range_ppm.to_be_bytes();
+                [self.op_code(), 0, 0, 0, high, low]
+            }
+            Self::GetDetectionRange => [self.op_code(), 0, 0, 0, 0, 0],
         }
     }
 }
+
+/// Formats the command name followed by its argument, e.g.
+/// `SetDetectionRange(5000 ppm)`.
+#[cfg(feature = "display")]
+impl Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SetSelfCalibrate(enabled) => {
+                write!(
+                    f,
+                    "{}({})",
+                    self.name(),
+                    if *enabled { "on" } else { "off" }
+                )
+            }
+            Self::CalibrateSpan(ppm) | Self::SetDetectionRange(ppm) => {
+                write!(f, "{}({ppm} ppm)", self.name())
+            }
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_frame_round_trip() {
+        for command in [
+            Command::ReadCo2,
+            Command::SetSelfCalibrate(true),
+            Command::SetSelfCalibrate(false),
+            Command::CalibrateSpan(2000),
+            Command::SetDetectionRange(5000),
+        ] {
+            assert_eq!(Command::from_frame(&command.into()), Some(command));
+        }
+        assert_eq!(
+            Command::from_frame(&Frame::from_serialized_command([0x84, 0, 0, 0, 0, 0])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_name_of_op_code() {
+        assert_eq!(Command::name_of_op_code(0x86), Some("ReadCo2"));
+        assert_eq!(Command::name_of_op_code(0x84), None);
+    }
+
+    #[cfg(feature = "display")]
+    #[test]
+    fn test_display() {
+        use std::string::ToString;
+        assert_eq!(Command::ReadCo2.to_string(), "ReadCo2");
+        assert_eq!(
+            Command::SetSelfCalibrate(true).to_string(),
+            "SetSelfCalibrate(on)"
+        );
+        assert_eq!(
+            Command::SetDetectionRange(5000).to_string(),
+            "SetDetectionRange(5000 ppm)"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Software correction of the readings.
+//!
+//! Co-locating the sensor with a reference instrument allows to determine a
+//! linear correction of the readings. Configured in the driver (see
+//! [`crate::MhZ19C::with_co2_correction`]), the correction is applied to all
+//! returned CO₂ readings without changing the calibration of the sensor
+//! itself.
+//!
+//! ```
+//! # use mh_z19c::correction::Co2Correction;
+//! let correction = Co2Correction::new(-20, 1.05);
+//! assert_eq!(correction.apply(800), 820);
+//! ```
+
+/// Linear correction of CO₂ readings.
+///
+/// The corrected reading is given by `raw * gain + offset_ppm`, rounded to
+/// the nearest integer and saturated to the range of `u16`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Co2Correction {
+    /// Offset in ppm added after applying the gain.
+    pub offset_ppm: i16,
+    /// Factor applied to the raw reading.
+    pub gain: f32,
+}
+
+impl Co2Correction {
+    /// Correction returning the raw readings unchanged.
+    pub const IDENTITY: Self = Self::new(0, 1.0);
+
+    /// Returns a correction with the given `offset_ppm` and `gain`.
+    pub const fn new(offset_ppm: i16, gain: f32) -> Self {
+        Self { offset_ppm, gain }
+    }
+
+    /// Returns the corrected reading for the `raw_ppm` reading.
+    pub fn apply(&self, raw_ppm: u16) -> u16 {
+        // Float to integer casts saturate, the offset of 0.5 rounds to the
+        // nearest integer for the non-negative values that are not saturated.
+        (f32::from(raw_ppm) * self.gain + f32::from(self.offset_ppm) + 0.5) as u16
+    }
+}
+
+impl Default for Co2Correction {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity() {
+        for raw_ppm in [0, 400, 5000, u16::MAX] {
+            assert_eq!(Co2Correction::IDENTITY.apply(raw_ppm), raw_ppm);
+        }
+    }
+
+    #[test]
+    fn test_apply_rounds_and_saturates() {
+        assert_eq!(Co2Correction::new(10, 0.9).apply(445), 411);
+        assert_eq!(Co2Correction::new(-500, 1.0).apply(400), 0);
+        assert_eq!(Co2Correction::new(100, 2.0).apply(60_000), u16::MAX);
+    }
+}
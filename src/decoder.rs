@@ -0,0 +1,175 @@
+//! Push-based decoding of received bytes.
+//!
+//! The [`Decoder`] is fed with the received bytes one at a time, e.g. from a
+//! UART RX interrupt handler, and returns an [`Event`] whenever a frame is
+//! completed. It does not allocate, block, or access the UART interface, so
+//! that it is safe to call from interrupt context. The events can then be
+//! handed to the thread context for further processing.
+//!
+//! ```
+//! # use mh_z19c::command::Command;
+//! # use mh_z19c::decoder::{Decoder, Event};
+//! # use mh_z19c::profile::{DeviceProfile, MhZ19CProfile};
+//! # use test_support::READ_CO2_RESPONSE;
+//! let mut decoder = Decoder::new();
+//! for &byte in READ_CO2_RESPONSE.iter() {
+//!     if let Some(Event::Response(frame)) = decoder.push(byte) {
+//!         if frame.op_code() == Command::ReadCo2.op_code() {
+//!             let co2_ppm = MhZ19CProfile::decode_co2_ppm(&frame.response_data());
+//!             assert_eq!(co2_ppm, 800);
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::frame::{Frame, ValidateFrameError, FRAME_LEN, START_BYTE};
+
+/// Event returned by the [`Decoder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A valid response frame has been received.
+    Response(Frame),
+    /// A valid command frame has been received (e.g. the echo of a
+    /// transmitted command on half-duplex wiring).
+    Command(Frame),
+    /// A frame with an invalid checksum has been received. The decoder
+    /// resynchronizes on the next start byte within the invalid frame.
+    Invalid(ValidateFrameError),
+}
+
+/// Decoder assembling frames from bytes pushed one at a time.
+///
+/// Bytes before a start byte are skipped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Decoder {
+    buf: [u8; FRAME_LEN],
+    len: usize,
+}
+
+impl Decoder {
+    /// Returns a new decoder without any buffered bytes.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; FRAME_LEN],
+            len: 0,
+        }
+    }
+
+    /// Adds a received `byte` and returns an event if it completes a frame.
+    pub fn push(&mut self, byte: u8) -> Option<Event> {
+        if self.len == 0 && byte != START_BYTE {
+            return None;
+        }
+        if let Some(slot) = self.buf.get_mut(self.len) {
+            *slot = byte;
+            self.len += 1;
+        }
+        if self.len < FRAME_LEN {
+            return None;
+        }
+
+        let frame = Frame::new(self.buf);
+        match frame.validate() {
+            Ok(()) => {
+                self.len = 0;
+                if frame.is_response() {
+                    Some(Event::Response(frame))
+                } else {
+                    Some(Event::Command(frame))
+                }
+            }
+            Err(err) => {
+                self.resync();
+                Some(Event::Invalid(err))
+            }
+        }
+    }
+
+    /// Returns the number of buffered bytes of an incomplete frame.
+    pub fn buffered(&self) -> usize {
+        self.len
+    }
+
+    /// Discards the buffered bytes of an incomplete frame.
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    fn resync(&mut self) {
+        let next_start = self
+            .buf
+            .iter()
+            .skip(1)
+            .position(|&byte| byte == START_BYTE)
+            .map_or(FRAME_LEN, |pos| pos + 1);
+        self.buf.copy_within(next_start.., 0);
+        self.len = FRAME_LEN - next_start;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Command;
+    use std::vec::Vec;
+    use test_support::{READ_CO2_AND_TEMPERATURE_RESPONSE, READ_CO2_RESPONSE};
+
+    fn push_all(decoder: &mut Decoder, bytes: &[u8]) -> Vec<Event> {
+        bytes
+            .iter()
+            .filter_map(|&byte| decoder.push(byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_decodes_frames() {
+        let mut decoder = Decoder::new();
+        let command = Frame::from(Command::ReadCo2);
+        assert_eq!(
+            push_all(&mut decoder, command.as_ref()),
+            [Event::Command(command)]
+        );
+        assert_eq!(
+            push_all(&mut decoder, &READ_CO2_RESPONSE),
+            [Event::Response(Frame::new(READ_CO2_RESPONSE))]
+        );
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn test_skips_bytes_before_start_byte() {
+        let mut decoder = Decoder::new();
+        assert_eq!(push_all(&mut decoder, &[0x00, 0x12]), []);
+        assert_eq!(decoder.buffered(), 0);
+        assert_eq!(
+            push_all(&mut decoder, &READ_CO2_RESPONSE),
+            [Event::Response(Frame::new(READ_CO2_RESPONSE))]
+        );
+    }
+
+    #[test]
+    fn test_resyncs_after_invalid_frame() {
+        let mut decoder = Decoder::new();
+        let mut bytes = std::vec![START_BYTE, 0x86];
+        bytes.extend_from_slice(&READ_CO2_AND_TEMPERATURE_RESPONSE);
+        let events = push_all(&mut decoder, &bytes);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0],
+            Event::Invalid(ValidateFrameError::InvalidChecksum { .. })
+        ));
+        assert_eq!(
+            events[1],
+            Event::Response(Frame::new(READ_CO2_AND_TEMPERATURE_RESPONSE))
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut decoder = Decoder::new();
+        push_all(&mut decoder, &READ_CO2_RESPONSE[..4]);
+        assert_eq!(decoder.buffered(), 4);
+        decoder.reset();
+        assert_eq!(decoder.buffered(), 0);
+    }
+}
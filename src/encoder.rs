@@ -0,0 +1,59 @@
+//! Encoding of commands into the bytes sent to the sensor.
+//!
+//! The functions in this module return the complete frame of a [`Command`]
+//! including start byte and checksum without requiring a driver instance.
+//! This allows to reuse the protocol implementation of this crate with a
+//! custom transmit path, e.g. using DMA.
+//!
+//! ```
+//! # use mh_z19c::command::Command;
+//! # use mh_z19c::encoder;
+//! # use mh_z19c::frame::FRAME_LEN;
+//! let mut tx_buf = [0u8; FRAME_LEN];
+//! encoder::encode_into(Command::ReadCo2, &mut tx_buf);
+//! assert_eq!(tx_buf, [0xff, 0x01, 0x86, 0x00, 0x00, 0x00, 0x00, 0x00, 0x79]);
+//! ```
+
+use crate::command::Command;
+use crate::frame::{Frame, FRAME_LEN};
+
+/// Returns the bytes to send for `command`.
+pub fn encode(command: Command) -> [u8; FRAME_LEN] {
+    Frame::from(command).into_inner()
+}
+
+/// Writes the bytes to send for `command` into `buf`.
+pub fn encode_into(command: Command, buf: &mut [u8; FRAME_LEN]) {
+    *buf = encode(command);
+}
+
+/// Returns an iterator over the bytes to send for `command`.
+pub fn bytes(command: Command) -> core::array::IntoIter<u8, FRAME_LEN> {
+    IntoIterator::into_iter(encode(command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+    use test_support::{CALIBRATE_SPAN_2000_COMMAND, SET_DETECTION_RANGE_5000_COMMAND};
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            encode(Command::CalibrateSpan(2000)),
+            CALIBRATE_SPAN_2000_COMMAND
+        );
+        let mut buf = [0u8; FRAME_LEN];
+        encode_into(Command::SetDetectionRange(5000), &mut buf);
+        assert_eq!(buf, SET_DETECTION_RANGE_5000_COMMAND);
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(
+            bytes(Command::CalibrateSpan(2000)).collect::<Vec<_>>(),
+            CALIBRATE_SPAN_2000_COMMAND
+        );
+    }
+}
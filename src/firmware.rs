@@ -0,0 +1,157 @@
+//! Firmware version of the sensor.
+
+use core::cmp::Ordering;
+use core::convert::TryInto;
+#[cfg(feature = "display")]
+use core::fmt::{self, Display};
+
+/// Firmware version reported by the sensor.
+///
+/// The sensor reports the version as four ASCII digits, the first two giving
+/// the major and the last two the minor version (e.g. `"0515"` for version
+/// 5.15). Versions are ordered by their raw bytes, which coincides with the
+/// numeric order for valid version strings. They can be compared against
+/// version literals:
+///
+/// ```
+/// # use mh_z19c::firmware::FirmwareVersion;
+/// let version = FirmwareVersion::new(*b"0515");
+/// assert!(version >= "0500");
+/// assert_eq!(version, "0515");
+/// assert_eq!(version.major(), Some(5));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FirmwareVersion([u8; 4]);
+
+impl FirmwareVersion {
+    /// Return a new firmware version from the raw bytes reported by the
+    /// sensor.
+    pub const fn new(raw: [u8; 4]) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw bytes reported by the sensor.
+    pub const fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    /// Unwraps the raw bytes reported by the sensor.
+    pub const fn into_inner(self) -> [u8; 4] {
+        self.0
+    }
+
+    /// Returns the version string or `None` if the raw bytes are not valid
+    /// UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        core::str::from_utf8(&self.0).ok()
+    }
+
+    /// Returns the major version or `None` if it is not given by two ASCII
+    /// digits.
+    pub fn major(&self) -> Option<u8> {
+        parse_digits(self.0[0], self.0[1])
+    }
+
+    /// Returns the minor version or `None` if it is not given by two ASCII
+    /// digits.
+    pub fn minor(&self) -> Option<u8> {
+        parse_digits(self.0[2], self.0[3])
+    }
+}
+
+fn parse_digits(tens: u8, ones: u8) -> Option<u8> {
+    if tens.is_ascii_digit() && ones.is_ascii_digit() {
+        Some((tens - b'0') * 10 + (ones - b'0'))
+    } else {
+        None
+    }
+}
+
+impl From<[u8; 4]> for FirmwareVersion {
+    fn from(raw: [u8; 4]) -> Self {
+        Self(raw)
+    }
+}
+
+impl From<FirmwareVersion> for [u8; 4] {
+    fn from(version: FirmwareVersion) -> Self {
+        version.0
+    }
+}
+
+impl AsRef<[u8]> for FirmwareVersion {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "display")]
+impl Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_str() {
+            Some(version) => f.write_str(version),
+            None => write!(f, "{:02x?}", self.0),
+        }
+    }
+}
+
+impl PartialEq<str> for FirmwareVersion {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for FirmwareVersion {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialOrd<str> for FirmwareVersion {
+    fn partial_cmp(&self, other: &str) -> Option<Ordering> {
+        let other: [u8; 4] = other.as_bytes().try_into().ok()?;
+        self.0.partial_cmp(&other)
+    }
+}
+
+impl PartialOrd<&str> for FirmwareVersion {
+    fn partial_cmp(&self, other: &&str) -> Option<Ordering> {
+        self.partial_cmp(*other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "display")]
+    use std::string::ToString;
+
+    #[test]
+    fn test_major_minor() {
+        let version = FirmwareVersion::new(*b"0515");
+        assert_eq!(version.major(), Some(5));
+        assert_eq!(version.minor(), Some(15));
+        assert_eq!(FirmwareVersion::new([0xff, 0, 0, 0]).major(), None);
+    }
+
+    #[test]
+    fn test_compare() {
+        let version = FirmwareVersion::new(*b"0430");
+        assert!(version < FirmwareVersion::new(*b"0500"));
+        assert!(version < "0500");
+        assert!(version >= "0400");
+        assert_eq!(version, "0430");
+        assert_ne!(version, "430");
+        assert_eq!(version.partial_cmp("430"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "display")]
+    fn test_display() {
+        assert_eq!(FirmwareVersion::new(*b"0515").to_string(), "0515");
+        assert_eq!(
+            FirmwareVersion::new([0xff, 0, 0x30, 0]).to_string(),
+            "[ff, 00, 30, 00]"
+        );
+    }
+}
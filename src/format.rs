@@ -0,0 +1,107 @@
+//! Formatting of readings into byte buffers without `core::fmt`.
+//!
+//! The functions write the formatted reading into a caller-provided buffer
+//! and return the written part as string slice, or `None` if the buffer is
+//! too small. This avoids the code size of the formatting machinery when
+//! driving character LCDs or small displays from tight firmware.
+//!
+//! ```
+//! # use mh_z19c::format;
+//! let mut buf = [0u8; format::MAX_LEN];
+//! assert_eq!(format::co2_ppm(1234, &mut buf), Some("1234 ppm"));
+//! assert_eq!(format::temp_celsius(23.44, &mut buf), Some("23.4°C"));
+//! ```
+
+/// Buffer length in bytes sufficient for any reading reported by the sensor.
+pub const MAX_LEN: usize = 10;
+
+/// Formats a CO₂ concentration as `"<ppm> ppm"`, e.g. `"1234 ppm"`.
+pub fn co2_ppm(co2_ppm: u16, buf: &mut [u8]) -> Option<&str> {
+    let mut cursor = Cursor { buf, len: 0 };
+    cursor.push_u32(u32::from(co2_ppm))?;
+    cursor.push_bytes(b" ppm")?;
+    cursor.finish()
+}
+
+/// Formats a temperature rounded to 0.1 °C as `"<temperature>°C"`, e.g.
+/// `"23.4°C"`.
+///
+/// Note that the degree sign is encoded as UTF-8 (two bytes).
+pub fn temp_celsius(temp_celsius: f32, buf: &mut [u8]) -> Option<&str> {
+    let deci_celsius = temp_celsius * 10.0;
+    let rounding = if deci_celsius < 0.0 { -0.5 } else { 0.5 };
+    let deci_celsius = (deci_celsius + rounding) as i32;
+
+    let mut cursor = Cursor { buf, len: 0 };
+    if deci_celsius < 0 {
+        cursor.push_bytes(b"-")?;
+    }
+    let deci_celsius = deci_celsius.unsigned_abs();
+    cursor.push_u32(deci_celsius / 10)?;
+    cursor.push_bytes(b".")?;
+    cursor.push_u32(deci_celsius % 10)?;
+    cursor.push_bytes("°C".as_bytes())?;
+    cursor.finish()
+}
+
+struct Cursor<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> Cursor<'b> {
+    fn push_bytes(&mut self, bytes: &[u8]) -> Option<()> {
+        for &byte in bytes {
+            *self.buf.get_mut(self.len)? = byte;
+            self.len += 1;
+        }
+        Some(())
+    }
+
+    fn push_u32(&mut self, value: u32) -> Option<()> {
+        let mut divisor = 1;
+        while value / divisor >= 10 {
+            divisor *= 10;
+        }
+        while divisor > 0 {
+            self.push_bytes(&[b'0' + (value / divisor % 10) as u8])?;
+            divisor /= 10;
+        }
+        Some(())
+    }
+
+    fn finish(self) -> Option<&'b str> {
+        let Self { buf, len } = self;
+        core::str::from_utf8(buf.get(..len)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_co2_ppm() {
+        let mut buf = [0u8; MAX_LEN];
+        assert_eq!(co2_ppm(0, &mut buf), Some("0 ppm"));
+        assert_eq!(co2_ppm(400, &mut buf), Some("400 ppm"));
+        assert_eq!(co2_ppm(u16::MAX, &mut buf), Some("65535 ppm"));
+    }
+
+    #[test]
+    fn test_temp_celsius() {
+        let mut buf = [0u8; MAX_LEN];
+        assert_eq!(temp_celsius(0.0, &mut buf), Some("0.0°C"));
+        assert_eq!(temp_celsius(23.45, &mut buf), Some("23.5°C"));
+        assert_eq!(temp_celsius(-5.04, &mut buf), Some("-5.0°C"));
+        assert_eq!(temp_celsius(-0.06, &mut buf), Some("-0.1°C"));
+        assert_eq!(temp_celsius(655.35, &mut buf), Some("655.4°C"));
+    }
+
+    #[test]
+    fn test_buffer_too_small() {
+        assert_eq!(co2_ppm(1234, &mut [0u8; 7]), None);
+        assert_eq!(co2_ppm(1234, &mut [0u8; 8]), Some("1234 ppm"));
+        assert_eq!(temp_celsius(23.4, &mut [0u8; 6]), None);
+    }
+}
@@ -1,7 +1,10 @@
 //! Serial communication frame handling for the MH-Z19C sensor.
 
 use crate::command::Command;
+#[cfg(feature = "display")]
+use crate::firmware::FirmwareVersion;
 use core::convert::From;
+#[cfg(feature = "display")]
 use core::fmt::{self, Display, Formatter};
 
 /// Calculates the checksum of `buf`.
@@ -10,19 +13,20 @@ pub fn checksum(buf: &[u8]) -> u8 {
         .fold(0x00, |acc: u8, &x: &u8| acc.overflowing_sub(x).0)
 }
 
-const START_BYTE: u8 = 0xff;
+/// Length of a frame in bytes.
+pub const FRAME_LEN: usize = 9;
+/// First byte of every frame.
+pub const START_BYTE: u8 = 0xff;
 const COMMAND_MAGIC_BYTE: u8 = 0x01;
 
 /// Represents a frame for the serial communication.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[repr(transparent)]
 pub struct Frame([u8; 9]);
 
 impl From<Command> for Frame {
     fn from(command: Command) -> Self {
-        let mut buf = [START_BYTE, COMMAND_MAGIC_BYTE, 0, 0, 0, 0, 0, 0, 0];
-        buf[2..8].copy_from_slice(&command.serialize());
-        buf[8] = checksum(&buf[1..8]);
-        Self(buf)
+        Self::from_serialized_command(command.serialize())
     }
 }
 
@@ -38,6 +42,24 @@ impl Frame {
         Self(data)
     }
 
+    /// Return a borrowed [`Frame`] view of `data` without copying it.
+    pub fn from_bytes(data: &[u8; 9]) -> &Self {
+        // SAFETY: `Frame` is a `repr(transparent)` wrapper of `[u8; 9]`.
+        unsafe { &*(data as *const [u8; 9] as *const Self) }
+    }
+
+    /// Return a command frame for a serialized command.
+    ///
+    /// * `command`: Op code followed by the command arguments as returned by
+    ///   [`Command::serialize`]. This also allows to send commands not
+    ///   provided by [`Command`].
+    pub fn from_serialized_command(command: [u8; 6]) -> Self {
+        let mut buf = [START_BYTE, COMMAND_MAGIC_BYTE, 0, 0, 0, 0, 0, 0, 0];
+        buf[2..8].copy_from_slice(&command);
+        buf[8] = checksum(&buf[1..8]);
+        Self(buf)
+    }
+
     /// Unwrap the frame data.
     pub fn into_inner(self) -> [u8; 9] {
         self.0
@@ -79,11 +101,46 @@ impl Frame {
         }
     }
 
+    /// Returns the response data (without op code) of a response frame.
+    pub fn response_data(&self) -> [u8; 6] {
+        let [_, _, d0, d1, d2, d3, d4, d5, _] = self.0;
+        [d0, d1, d2, d3, d4, d5]
+    }
+
     /// Returns `true` if the frame's checksum is valid.
     pub fn has_valid_checksum(&self) -> bool {
         checksum(&self.0[1..8]) == self.checksum()
     }
 
+    /// Returns a human-readable annotation of the frame for logging.
+    ///
+    /// Command frames are formatted like [`Command`] (e.g.
+    /// `SetDetectionRange(5000 ppm)`), response frames by the command name
+    /// followed by the interpreted data (e.g. `ReadCo2 → 812 ppm`) according
+    /// to the MH-Z19C protocol. Invalid frames are formatted with the
+    /// validation error.
+    ///
+    /// ```
+    /// # use mh_z19c::frame::Frame;
+    /// let frame = Frame::new([0xff, 0x86, 0x03, 0x2c, 0x00, 0x00, 0x00, 0x00, 0x4b]);
+    /// assert_eq!(frame.annotate().to_string(), "ReadCo2 → 812 ppm");
+    /// ```
+    #[cfg(feature = "display")]
+    pub fn annotate(&self) -> Annotation<'_> {
+        Annotation(self)
+    }
+
+    /// Validates the frame with the given strictness.
+    ///
+    /// The start byte is always checked, the checksum only if required by
+    /// the `validation`.
+    pub fn validate_with(&self, validation: Validation) -> Result<(), ValidateFrameError> {
+        match self.validate() {
+            Err(ValidateFrameError::InvalidChecksum { .. }) if !validation.check_checksum => Ok(()),
+            result => result,
+        }
+    }
+
     /// Validates the correctness of the frame.
     ///
     /// * Checks that the start byte is valid.
@@ -102,6 +159,91 @@ impl Frame {
     }
 }
 
+/// Human-readable annotation of a [`Frame`] returned by [`Frame::annotate`].
+#[cfg(feature = "display")]
+#[derive(Clone, Copy, Debug)]
+pub struct Annotation<'a>(&'a Frame);
+
+#[cfg(feature = "display")]
+impl Display for Annotation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let frame = self.0;
+        if let Err(err) = frame.validate() {
+            return write!(f, "invalid frame ({err})");
+        }
+        if !frame.is_response() {
+            return match Command::from_frame(frame) {
+                Some(command) => write!(f, "{command}"),
+                None => write!(f, "0x{:02x}({:02x?})", frame.op_code(), frame.data()),
+            };
+        }
+
+        let op_code = frame.op_code();
+        match Command::name_of_op_code(op_code) {
+            Some(name) => write!(f, "{name} → ")?,
+            None => write!(f, "0x{op_code:02x} → ")?,
+        }
+        let [d0, d1, d2, d3, d4, d5] = frame.response_data();
+        match op_code {
+            op if op == Command::ReadCo2AndTemperature.op_code() => write!(
+                f,
+                "{} ppm, {:.2} °C",
+                u16::from_be_bytes([d2, d3]),
+                f32::from(u16::from_be_bytes([d0, d1])) / 100.0
+            ),
+            op if op == Command::ReadCo2.op_code() => {
+                write!(f, "{} ppm", u16::from_be_bytes([d0, d1]))
+            }
+            op if op == Command::GetSelfCalibrate.op_code() => {
+                f.write_str(if d5 != 0 { "on" } else { "off" })
+            }
+            op if op == Command::GetDetectionRange.op_code() => {
+                write!(f, "{} ppm", u16::from_be_bytes([d2, d3]))
+            }
+            op if op == Command::GetFirmwareVersion.op_code() => {
+                write!(f, "{}", FirmwareVersion::new([d0, d1, d2, d3]))
+            }
+            _ => write!(f, "{:02x?}", [d0, d1, d2, d3, d4, d5]),
+        }
+    }
+}
+
+/// Strictness of the validation of received frames.
+///
+/// Some third-party modules (e.g. clones of the sensor) deviate from the
+/// protocol in ways that would otherwise make every command fail. A lenient
+/// validation tolerates these quirks at the cost of accepting corrupted
+/// frames that would have been detected otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Validation {
+    /// Whether frames with an invalid checksum are rejected.
+    pub check_checksum: bool,
+    /// Whether bytes received after a complete frame (e.g. trailing garbage
+    /// sent by the module) are discarded before the next command is sent.
+    /// Otherwise, such bytes would be read as part of the next response.
+    pub discard_trailing_bytes: bool,
+}
+
+impl Validation {
+    /// Validation according to the protocol of the MH-Z19C (the default).
+    pub const STRICT: Self = Self {
+        check_checksum: true,
+        discard_trailing_bytes: false,
+    };
+
+    /// Validation tolerating invalid checksums and trailing bytes.
+    pub const LENIENT: Self = Self {
+        check_checksum: false,
+        discard_trailing_bytes: true,
+    };
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Self::STRICT
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ValidateFrameError {
     /// Indicates that the start byte is invalid and provides that invalid byte.
@@ -110,6 +252,7 @@ pub enum ValidateFrameError {
     InvalidChecksum { expected: u8, actual: u8 },
 }
 
+#[cfg(feature = "display")]
 impl Display for ValidateFrameError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
         use ValidateFrameError::*;
@@ -124,8 +267,8 @@ impl Display for ValidateFrameError {
     }
 }
 
-#[cfg(std)]
-impl std::error::Error for Error {}
+#[cfg(feature = "std")]
+impl std::error::Error for ValidateFrameError {}
 
 #[cfg(test)]
 mod tests {
@@ -143,6 +286,42 @@ mod tests {
         assert!(frame.validate().is_ok());
     }
 
+    #[cfg(feature = "display")]
+    #[test]
+    fn test_annotate() {
+        use std::string::ToString;
+        assert_eq!(
+            Frame::from(Command::CalibrateSpan(2000))
+                .annotate()
+                .to_string(),
+            "CalibrateSpan(2000 ppm)"
+        );
+        assert_eq!(
+            Frame::new([0xff, 0x85, 0x09, 0x60, 0x03, 0x20, 0x56, 0x78, 0x21])
+                .annotate()
+                .to_string(),
+            "ReadCo2AndTemperature → 800 ppm, 24.00 °C"
+        );
+        assert_eq!(
+            Frame::new([0xff, 0xa0, b'0', b'5', b'1', b'5', 0, 0, 0x00])
+                .annotate()
+                .to_string(),
+            "invalid frame (invalid checksum (got 0 instead of 149))"
+        );
+    }
+
+    #[test]
+    fn test_validate_with() {
+        let frame = Frame::new([0xff, 0x86, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert!(frame.validate_with(Validation::STRICT).is_err());
+        assert_eq!(frame.validate_with(Validation::LENIENT), Ok(()));
+        let frame = Frame::new([0x00, 0x86, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x77]);
+        assert_eq!(
+            frame.validate_with(Validation::LENIENT),
+            Err(ValidateFrameError::InvalidStartByte(0x00))
+        );
+    }
+
     #[test]
     fn test_frame_read_co2_response() {
         let frame = Frame::new([0xff, 0x86, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x77]);
@@ -191,6 +370,23 @@ mod tests {
         assert!(frame.validate().is_ok());
     }
 
+    #[test]
+    fn test_frame_from_serialized_command() {
+        let frame = Frame::from_serialized_command([0x84, 0x01, 0x02, 0x03, 0x04, 0x05]);
+        assert!(!frame.is_response());
+        assert_eq!(frame.op_code(), 0x84);
+        assert_eq!(frame.data(), [0x01, 0x02, 0x03, 0x04, 0x05]);
+        assert!(frame.validate().is_ok());
+    }
+
+    #[test]
+    fn test_frame_from_bytes() {
+        let data = [0xff, 0x86, 0x03, 0x20, 0x12, 0x34, 0x56, 0x78, 0x43];
+        let frame = Frame::from_bytes(&data);
+        assert_eq!(frame, &Frame::new(data));
+        assert_eq!(frame.validate(), Ok(()));
+    }
+
     #[test]
     fn test_checksum() {
         assert_eq!(checksum(&[0x01, 0x86, 0x00, 0x00, 0x00, 0x00, 0x00]), 0x79);
@@ -0,0 +1,82 @@
+//! Zero point calibration via the HD pin of the sensor.
+//!
+//! As an alternative to [`crate::MhZ19C::calibrate_zero`], the zero point
+//! calibration can be triggered by pulling the HD pin of the sensor low for
+//! at least 7 seconds.
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Duration in milliseconds the HD pin is held low to trigger the zero point
+/// calibration. The data sheet requires at least 7 seconds.
+pub const CALIBRATION_PULSE_MS: u16 = 7500;
+
+/// Output pin connected to the HD pin of the sensor.
+///
+/// The HD pin is idle at a high level. The pin should be configured as
+/// open-drain output or otherwise ensure compatible voltage levels.
+#[derive(Debug)]
+pub struct HdPin<P> {
+    pin: P,
+}
+
+impl<P: OutputPin> HdPin<P> {
+    /// Create a new instance and set the `pin` to the idle high level.
+    pub fn new(mut pin: P) -> Result<Self, P::Error> {
+        pin.set_high()?;
+        Ok(Self { pin })
+    }
+
+    /// Calibrates the zero point (400 ppm) to the current reading by holding
+    /// the HD pin low for [`CALIBRATION_PULSE_MS`].
+    ///
+    /// The same preconditions as for [`crate::MhZ19C::calibrate_zero`]
+    /// apply: The sensor must have been operating in a stable 400 ppm
+    /// environment (e.g. fresh outdoor air) for at least 20 minutes before
+    /// calibrating. See the sensor's data sheet for more information.
+    ///
+    /// If setting the pin low fails, the pin is set back to the idle high
+    /// level before returning the error.
+    pub fn calibrate_zero<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), P::Error> {
+        if let Err(err) = self.pin.set_low() {
+            let _ = self.pin.set_high();
+            return Err(err);
+        }
+        delay.delay_ms(CALIBRATION_PULSE_MS);
+        self.pin.set_high()
+    }
+
+    /// Returns the owned output pin.
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::delay_mock::DelayMock;
+    use test_support::pin_mock::PinMock;
+
+    #[test]
+    fn test_calibrate_zero() {
+        let mut delay = DelayMock::default();
+        let mut hd = HdPin::new(PinMock::default()).unwrap();
+        hd.calibrate_zero(&mut delay).unwrap();
+        assert_eq!(hd.into_inner().levels, vec![true, false, true]);
+        assert!(delay.total_ms() >= 7000);
+    }
+
+    #[test]
+    fn test_calibrate_zero_releases_pin_on_error() {
+        let mut delay = DelayMock::default();
+        let mut hd = HdPin::new(PinMock {
+            levels: vec![],
+            fail_low: true,
+        })
+        .unwrap();
+        assert_eq!(hd.calibrate_zero(&mut delay), Err(()));
+        assert_eq!(hd.into_inner().levels, vec![true, true]);
+        assert_eq!(delay.total_ms(), 0);
+    }
+}
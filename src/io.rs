@@ -0,0 +1,129 @@
+//! Adapter to use [`std::io`] streams as UART interface.
+//!
+//! This module is only available with the `std` feature.
+
+use embedded_hal::serial::{Read, Write};
+use std::io::{self, ErrorKind};
+
+/// Wraps a [`std::io::Read`] + [`std::io::Write`] stream (e.g. an opened
+/// serial device file) to provide the [`embedded_hal::serial`] traits
+/// required by the [`crate::MhZ19C`] driver.
+///
+/// A read returning zero bytes (e.g. because a read timeout of the underlying
+/// device expired) as well as the [`ErrorKind::WouldBlock`],
+/// [`ErrorKind::TimedOut`], and [`ErrorKind::Interrupted`] errors will be
+/// reported as [`nb::Error::WouldBlock`].
+#[derive(Debug)]
+pub struct IoUart<T> {
+    inner: T,
+}
+
+impl<T> IoUart<T> {
+    /// Wraps the `inner` stream.
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the wrapped stream.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwraps the wrapped stream.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn map_io_error(err: io::Error) -> nb::Error<io::Error> {
+    match err.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted => {
+            nb::Error::WouldBlock
+        }
+        _ => nb::Error::Other(err),
+    }
+}
+
+impl<T: io::Read> Read<u8> for IoUart<T> {
+    type Error = io::Error;
+
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let mut buf = [0u8; 1];
+        match self.inner.read(&mut buf) {
+            Ok(0) => Err(nb::Error::WouldBlock),
+            Ok(_) => Ok(buf[0]),
+            Err(err) => Err(map_io_error(err)),
+        }
+    }
+}
+
+impl<T: io::Write> Write<u8> for IoUart<T> {
+    type Error = io::Error;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        match self.inner.write(&[word]) {
+            Ok(0) => Err(nb::Error::Other(ErrorKind::WriteZero.into())),
+            Ok(_) => Ok(()),
+            Err(err) => Err(map_io_error(err)),
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.inner.flush().map_err(map_io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MhZ19C;
+    use nb::block;
+    use std::io::Cursor;
+    use std::vec::Vec;
+    use test_support::READ_CO2_RESPONSE;
+
+    #[derive(Debug)]
+    struct Loopback {
+        rx: Cursor<Vec<u8>>,
+        tx: Vec<u8>,
+    }
+
+    impl io::Read for Loopback {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.rx.read(buf)
+        }
+    }
+
+    impl io::Write for Loopback {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.tx.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_read_co2_via_io_uart() {
+        let uart = IoUart::new(Loopback {
+            rx: Cursor::new(READ_CO2_RESPONSE.to_vec()),
+            tx: vec![],
+        });
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(block!(co2sensor.read_co2_ppm()).unwrap(), 800);
+        let uart = co2sensor.into_inner();
+        assert_eq!(uart.get_ref().tx, crate::READ_CO2.as_ref());
+    }
+
+    #[test]
+    fn test_exhausted_stream_would_block() {
+        let mut uart = IoUart::new(Cursor::new(vec![]));
+        assert!(matches!(uart.read(), Err(nb::Error::WouldBlock)));
+    }
+}
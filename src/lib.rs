@@ -33,6 +33,9 @@
 //! #     FIRMWARE_0515_RESPONSE,
 //! #     READ_CO2_AND_TEMPERATURE_RESPONSE
 //! # };
+//! # #[cfg(not(feature = "firmware5"))]
+//! # fn main() {}
+//! # #[cfg(feature = "firmware5")]
 //! # fn main() -> Result<(), mh_z19c::Error<String>> {
 //! # let mut responses: Vec<nb::Result<u8, String>> = FIRMWARE_0515_RESPONSE
 //! #     .iter()
@@ -49,12 +52,32 @@
 //! # }
 //! ```
 //!
+//! The [`sim::Simulator`] can be used in place of the UART interface to
+//! develop and test applications without the sensor hardware.
+//!
 //!
 //! # no_std
 //!
 //! This crate is `no_std` by default, unless the `std` feature is activated.
-//! Currently, the `std` feature will only add [`std::error::Error`] trait
-//! implementations to the error types.
+//! The `std` feature adds [`std::error::Error`] trait implementations to the
+//! error types and the `io` module to use [`std::io`] streams as UART
+//! interface. It is also required by the `mhz19c` command line tool.
+//!
+//! The `calibration` and `firmware5` features are enabled by default. They can
+//! be disabled to reduce the code size if the respective functionality is not
+//! needed. The `calibration` feature provides the methods to calibrate the
+//! zero and span point and to configure the self-calibration. The `firmware5`
+//! feature provides the [`Firmware5Api`] and the combined reading of CO₂
+//! concentration and temperature.
+//!
+//! The `display` feature (enabled by default and implied by the `std`
+//! feature) provides the [`core::fmt::Display`] implementations of the error
+//! and firmware version types. Disabling it avoids the code size of the
+//! formatting machinery, errors can still be identified by [`Error::code`].
+//!
+//! The `analog` feature adds the `analog` module to read the analog voltage
+//! output of the sensor with an ADC. It enables the `unproven` feature of
+//! `embedded-hal`.
 //!
 //!
 //! # Versioning
@@ -62,89 +85,350 @@
 //! This crate uses [Semantic Versioning](https://semver.org/).
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        clippy::panic,
+        clippy::unreachable,
+        clippy::unwrap_used
+    )
+)]
 
 #[cfg(doc)]
 extern crate std;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use crate::builder::MhZ19CBuilder;
+use crate::capabilities::Capabilities;
+#[cfg(feature = "firmware5")]
+use crate::capabilities::TemperatureEncoding;
 use crate::command::Command;
-use crate::frame::{Frame, ValidateFrameError};
-use crate::nb_comm::{NbFuture, WriteAll, WriteAndReadResponse};
-use core::convert::TryInto;
+use crate::correction::Co2Correction;
+use crate::firmware::FirmwareVersion;
+use crate::frame::{Frame, ValidateFrameError, Validation};
+use crate::nb_comm::{NbFuture, WriteAll, WriteAndReadInto, WriteAndReadResponse};
+use crate::profile::{DeviceProfile, MhZ19CProfile};
+#[cfg(feature = "display")]
 use core::fmt::{self, Display};
+use core::marker::PhantomData;
 use embedded_hal::serial::{Read, Write};
 
+#[cfg(feature = "analog")]
+pub mod analog;
+pub mod backoff;
+#[cfg(feature = "std")]
+pub mod baud;
+pub mod builder;
+pub mod capabilities;
 pub mod command;
+pub mod correction;
+pub mod decoder;
+pub mod encoder;
+pub mod firmware;
+pub mod format;
 pub mod frame;
-mod nb_comm;
+pub mod hd;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod nb_comm;
+pub mod payload;
+pub mod poll;
+pub mod power;
+pub mod probe;
+pub mod profile;
+pub mod pwm;
+pub mod sensor;
+pub mod shared;
+pub mod sim;
+#[cfg(feature = "std")]
+pub mod timestamp;
+pub mod typestate;
 
+#[cfg(test)]
 lazy_static! {
-    static ref READ_CO2_AND_TEMPERATURE: Frame = Command::ReadCo2AndTemperature.into();
     static ref READ_CO2: Frame = Command::ReadCo2.into();
     static ref GET_FIRMWARE_VERSION: Frame = Command::GetFirmwareVersion.into();
+    static ref GET_SELF_CALIBRATE: Frame = Command::GetSelfCalibrate.into();
+    static ref GET_DETECTION_RANGE: Frame = Command::GetDetectionRange.into();
 }
 
 /// Methods supported by all MH-Z19C sensors.
+///
+/// The trait is object safe, so that drivers for different sensors or
+/// firmware versions can be used as `&mut dyn BaseApi<E>`:
+///
+/// ```
+/// # use mh_z19c::{BaseApi, MhZ19C};
+/// # use nb::block;
+/// # use test_support::{create_serial_mock_returning, READ_CO2_RESPONSE};
+/// fn print_co2(sensor: &mut dyn BaseApi<String>) -> Result<(), mh_z19c::Error<String>> {
+///     println!("CO₂ concentration: {}ppm", block!(sensor.read_co2_ppm())?);
+///     Ok(())
+/// }
+///
+/// # fn main() -> Result<(), mh_z19c::Error<String>> {
+/// # let uart = create_serial_mock_returning(&READ_CO2_RESPONSE);
+/// let mut co2sensor = MhZ19C::new(uart);
+/// print_co2(&mut co2sensor)?;
+/// # Ok(())
+/// # }
+/// ```
 pub trait BaseApi<E> {
     /// Reads and returns the CO₂ concentration in parts-per-million (ppm).
     fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>>;
 
     /// Retrieves the firmware version of the sensor.
-    fn get_firmware_version(&mut self) -> nb::Result<[u8; 4], Error<E>>;
+    fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>>;
 
     /// Activates or deactivates the sensor's self-calibration mode.
     ///
     /// See the sensor's data sheet for more information on self-calibration
     /// and hand-operated mode.
+    #[cfg(feature = "calibration")]
     fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>>;
+
+    /// Retrieves whether the sensor's self-calibration mode is activated.
+    #[cfg(feature = "calibration")]
+    fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>>;
+
+    /// Calibrates the zero point (400 ppm) to the current reading.
+    ///
+    /// The sensor must have been operating in a stable 400 ppm environment
+    /// (e.g. fresh outdoor air) for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>>;
+
+    /// Calibrates the span point to the current reading.
+    ///
+    /// The zero point must be calibrated before the span point and the sensor
+    /// must have been operating in a stable environment of the given
+    /// `span_ppm` concentration for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>>;
+
+    /// Sets the detection range (upper limit of measurable CO₂ concentration)
+    /// in ppm.
+    ///
+    /// The MH-Z19C supports ranges of 2000, 5000, and 10000 ppm.
+    fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>>;
+
+    /// Retrieves the detection range (upper limit of measurable CO₂
+    /// concentration) in ppm.
+    fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>>;
 }
 
+/// Number of consecutive invalid frames after which
+/// [`MhZ19C::likely_baud_mismatch`] reports a likely baud rate mismatch.
+pub const BAUD_MISMATCH_THRESHOLD: u8 = 3;
+
 /// Data-transfer object for combined measurement of CO₂ and temperature.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Co2AndTemperature {
     /// CO₂ concentration in parts per million (ppm).
     pub co2_ppm: u16,
-    /// Temperature in degrees Celsius (°C).
+    /// Temperature in degrees Celsius (°C) with the configured offset
+    /// applied (see [`MhZ19C::with_temp_offset_celsius`]).
     pub temp_celsius: f32,
+    /// Temperature in degrees Celsius (°C) as reported by the sensor.
+    pub raw_temp_celsius: f32,
+}
+
+impl Co2AndTemperature {
+    /// Returns a measurement without temperature offset, i.e. the raw
+    /// temperature equals the temperature.
+    pub const fn new(co2_ppm: u16, temp_celsius: f32) -> Self {
+        Self {
+            co2_ppm,
+            temp_celsius,
+            raw_temp_celsius: temp_celsius,
+        }
+    }
+}
+
+/// Sensor variant determined by [`MhZ19C::detect`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DetectedSensor {
+    /// Sensor with firmware 5 or higher supporting the [`Firmware5Api`].
+    Firmware5 {
+        /// Sensor model as given by the [`DeviceProfile`].
+        model: &'static str,
+        /// Firmware version reported by the sensor.
+        firmware_version: FirmwareVersion,
+    },
+    /// Sensor with a firmware below version 5 supporting only the
+    /// [`BaseApi`].
+    Legacy {
+        /// Sensor model as given by the [`DeviceProfile`].
+        model: &'static str,
+        /// Firmware version reported by the sensor.
+        firmware_version: FirmwareVersion,
+    },
+}
+
+impl DetectedSensor {
+    /// Returns the sensor model as given by the [`DeviceProfile`].
+    pub fn model(&self) -> &'static str {
+        match self {
+            Self::Firmware5 { model, .. } | Self::Legacy { model, .. } => model,
+        }
+    }
+
+    /// Returns the firmware version reported by the sensor.
+    pub fn firmware_version(&self) -> FirmwareVersion {
+        match self {
+            Self::Firmware5 {
+                firmware_version, ..
+            }
+            | Self::Legacy {
+                firmware_version, ..
+            } => *firmware_version,
+        }
+    }
+
+    /// Returns `true` if the [`Firmware5Api`] is supported.
+    pub fn supports_firmware5_api(&self) -> bool {
+        matches!(self, Self::Firmware5 { .. })
+    }
+
+    /// Returns the capabilities supported by the sensor firmware.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::from_firmware_version(self.firmware_version())
+    }
+}
+
+/// Result of the communication check by [`MhZ19C::self_test`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Firmware version reported by the sensor.
+    pub firmware_version: FirmwareVersion,
+    /// CO₂ concentration in parts per million (ppm) reported by the sensor.
+    pub co2_ppm: u16,
+    /// Detection range in ppm if known (see [`MhZ19C::detection_range_ppm`]).
+    pub detection_range_ppm: Option<u16>,
+}
+
+impl SelfTestReport {
+    /// Returns `true` if the firmware version consists of ASCII digits.
+    pub fn firmware_version_plausible(&self) -> bool {
+        self.firmware_version.major().is_some() && self.firmware_version.minor().is_some()
+    }
+
+    /// Returns `true` if the CO₂ reading is non-zero and below the detection
+    /// range (if known).
+    pub fn co2_plausible(&self) -> bool {
+        self.co2_ppm > 0
+            && self
+                .detection_range_ppm
+                .is_none_or(|range_ppm| self.co2_ppm < range_ppm)
+    }
+
+    /// Returns `true` if all checks passed.
+    pub fn passed(&self) -> bool {
+        self.firmware_version_plausible() && self.co2_plausible()
+    }
 }
 
 /// Methods supported by all MH-Z19C sensors with firmware 5.
+///
+/// Like [`BaseApi`], the trait is object safe.
+#[cfg(feature = "firmware5")]
 pub trait Firmware5Api<E>: BaseApi<E> {
     /// Reads the CO₂ concentration and temperature.
     fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>>;
 }
 
-/// Driver for the MH-Z19C sensor.
-#[derive(Debug)]
-pub struct MhZ19C<'a, U, E>
+impl<E, T> BaseApi<E> for &mut T
 where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    T: BaseApi<E> + ?Sized,
 {
-    state: MhZ19CState<'a, U, E>,
-    uart: Option<U>,
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+        (**self).read_co2_ppm()
+    }
+
+    fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        (**self).get_firmware_version()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+        (**self).set_self_calibrate(enabled)
+    }
+
+    #[cfg(feature = "calibration")]
+    fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        (**self).get_self_calibrate()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        (**self).calibrate_zero()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        (**self).calibrate_span(span_ppm)
+    }
+
+    fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        (**self).set_detection_range(range_ppm)
+    }
+
+    fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        (**self).get_detection_range()
+    }
 }
 
-#[derive(Debug)]
-enum MhZ19CState<'a, U, E>
+#[cfg(feature = "firmware5")]
+impl<E, T> Firmware5Api<E> for &mut T
 where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    T: Firmware5Api<E> + ?Sized,
 {
-    Idle,
-    ReadCo2AndTemperature(WriteAndReadResponse<U, E, &'a [u8], [u8; 9]>),
-    ReadCo2(WriteAndReadResponse<U, E, &'a [u8], [u8; 9]>),
-    GetFirmwareVersion(WriteAndReadResponse<U, E, &'a [u8], [u8; 9]>),
-    SetSelfCalibrate(WriteAll<U, E, Frame>),
+    fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        (**self).read_co2_and_temp()
+    }
 }
 
-impl<'a, U, E> Default for MhZ19CState<'a, U, E>
+/// Driver for the MH-Z19C sensor.
+///
+/// Other Winsen NDIR sensors using the same serial protocol can be supported
+/// by providing a [`DeviceProfile`] as `P` type parameter.
+#[derive(Debug)]
+pub struct MhZ19C<'a, U, E, P = MhZ19CProfile>
 where
     U: Read<u8, Error = E> + Write<u8, Error = E>,
 {
-    fn default() -> Self {
-        Self::Idle
-    }
+    state: MhZ19CState,
+    uart: U,
+    firmware_version: Option<FirmwareVersion>,
+    detection_range_ppm: Option<u16>,
+    read_ahead: bool,
+    echo_suppression: bool,
+    co2_correction: Co2Correction,
+    temp_offset_celsius: f32,
+    validation: Validation,
+    retries: u8,
+    retries_used: u8,
+    consecutive_invalid_frames: u8,
+    /// Firmware version queried by a self-test in progress, which is reading
+    /// the CO₂ concentration.
+    self_test_firmware_version: Option<FirmwareVersion>,
+    marker: PhantomData<(&'a (), P)>,
+}
+
+#[derive(Debug, Default)]
+enum MhZ19CState {
+    #[default]
+    Idle,
+    Request(Command, WriteAndReadResponse<Frame, [u8; 9]>),
+    Send(Command, WriteAll<Frame>),
+    RequestInto(Command, WriteAndReadInto<Frame>),
 }
 
 impl<'a, U, E> MhZ19C<'a, U, E>
@@ -155,24 +439,118 @@ where
     ///
     /// * `uart`: Serial (UART) interface for communication with the sensor.
     pub fn new(uart: U) -> Self {
-        Self {
-            state: MhZ19CState::default(),
-            uart: Some(uart),
-        }
+        Self::with_profile(uart)
+    }
+
+    /// Returns a builder to configure the driver before constructing it.
+    pub fn builder() -> MhZ19CBuilder<'a, U, E> {
+        MhZ19CBuilder::new()
     }
 }
 
-impl<'a, U, E> MhZ19C<'a, U, E>
+impl<'a, U, E, P> MhZ19C<'a, U, E, P>
 where
     U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
 {
+    /// Create a new instance for the sensor described by the profile `P`.
+    ///
+    /// * `uart`: Serial (UART) interface for communication with the sensor.
+    pub fn with_profile(uart: U) -> Self {
+        MhZ19CBuilder::new().build(uart)
+    }
+
+    /// Enables or disables the read-ahead mode (disabled by default).
+    ///
+    /// In read-ahead mode the driver immediately sends the next read command
+    /// after completing a reading of the CO₂ concentration (and temperature).
+    /// The following call of the same read method will usually find the
+    /// response already received, hiding the latency of the sensor and UART
+    /// in periodic sampling loops. Note that the returned readings will have
+    /// been taken at the time of the previous call.
+    pub fn with_read_ahead(mut self, enabled: bool) -> Self {
+        self.read_ahead = enabled;
+        self
+    }
+
+    /// Enables or disables the suppression of the transmitted bytes being
+    /// echoed back (disabled by default).
+    ///
+    /// Some wiring setups (e.g. single-wire adapters or certain USB TTL
+    /// adapters) echo the transmitted command frame back on the receive line.
+    /// With echo suppression enabled, the echo is read and checked before
+    /// the response of the sensor. If it does not match the transmitted
+    /// frame, [`Error::EchoMismatch`] will be returned.
+    pub fn with_echo_suppression(mut self, enabled: bool) -> Self {
+        self.echo_suppression = enabled;
+        self
+    }
+
+    /// Sets the strictness of the validation of the received frames
+    /// ([`Validation::STRICT`] by default).
+    pub fn with_validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Sets the software correction applied to all returned CO₂ readings
+    /// (none by default).
+    ///
+    /// The detection range is checked against the uncorrected readings.
+    pub fn with_co2_correction(mut self, correction: Co2Correction) -> Self {
+        self.co2_correction = correction;
+        self
+    }
+
+    /// Returns the software correction applied to the CO₂ readings.
+    pub fn co2_correction(&self) -> Co2Correction {
+        self.co2_correction
+    }
+
+    /// Changes the software correction applied to the CO₂ readings, e.g.
+    /// after comparing the readings with a reference instrument.
+    pub fn set_co2_correction(&mut self, correction: Co2Correction) {
+        self.co2_correction = correction;
+    }
+
+    /// Sets the offset in °C added to the temperature readings (0 °C by
+    /// default).
+    ///
+    /// The temperature sensor is used for internal compensation and reads
+    /// high due to the self-heating of the sensor. A negative offset
+    /// determined by comparison with a thermometer corrects the readings. The
+    /// uncorrected temperature remains available as
+    /// [`Co2AndTemperature::raw_temp_celsius`].
+    pub fn with_temp_offset_celsius(mut self, offset_celsius: f32) -> Self {
+        self.temp_offset_celsius = offset_celsius;
+        self
+    }
+
+    /// Returns the offset in °C added to the temperature readings.
+    pub fn temp_offset_celsius(&self) -> f32 {
+        self.temp_offset_celsius
+    }
+
+    /// Changes the offset in °C added to the temperature readings.
+    pub fn set_temp_offset_celsius(&mut self, offset_celsius: f32) {
+        self.temp_offset_celsius = offset_celsius;
+    }
+
     /// Reads and returns the CO₂ concentration in parts-per-million (ppm).
+    ///
+    /// If the detection range is known (see [`MhZ19C::detection_range_ppm`]),
+    /// readings at or above it will be rejected with [`Error::OutOfRange`].
+    /// The configured software correction (see
+    /// [`MhZ19C::with_co2_correction`]) is applied to the reading.
     pub fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
         BaseApi::read_co2_ppm(self)
     }
 
     /// Retrieves the firmware version of the sensor.
-    pub fn get_firmware_version(&mut self) -> nb::Result<[u8; 4], Error<E>> {
+    ///
+    /// The firmware version will be cached (see
+    /// [`MhZ19C::firmware_version`]).
+    pub fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
         BaseApi::get_firmware_version(self)
     }
 
@@ -180,258 +558,940 @@ where
     ///
     /// See the sensor's data sheet for more information on self-calibration
     /// and hand-operated mode.
+    #[cfg(feature = "calibration")]
     pub fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
         BaseApi::set_self_calibrate(self, enabled)
     }
 
-    /// Returns the owned UART interface.vec!
+    /// Retrieves whether the sensor's self-calibration mode is activated.
+    #[cfg(feature = "calibration")]
+    pub fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        BaseApi::get_self_calibrate(self)
+    }
+
+    /// Calibrates the zero point (400 ppm) to the current reading.
     ///
-    /// Note that this might leave the interface with partially written or read
-    /// bytes on the UART interface if not all MH-Z19C commands have been polled
-    /// to completion (i.e. the last command call did not return
-    /// [`nb::Error::WouldBlock`]).
-    pub fn into_inner(mut self) -> U {
-        use MhZ19CState::*;
-        match self.state {
-            Idle => self.uart.take().unwrap(),
-            ReadCo2AndTemperature(future) => future.into_return_value().0,
-            ReadCo2(future) => future.into_return_value().0,
-            GetFirmwareVersion(future) => future.into_return_value().0,
-            SetSelfCalibrate(future) => future.into_return_value(),
-        }
+    /// The sensor must have been operating in a stable 400 ppm environment
+    /// (e.g. fresh outdoor air) for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        BaseApi::calibrate_zero(self)
     }
 
-    /// Will make the [`Firmware5Api`] capabilities available.
+    /// Calibrates the span point to the current reading.
     ///
-    /// If the sensor firmware is not at least of version 5, an error will be
-    /// returned.
-    pub fn upgrade_to_v5<'b>(&'b mut self) -> nb::Result<MhZ19CFw5<'a, 'b, U, E>, Error<E>> {
-        let fw_version = self.get_firmware_version()?;
+    /// The zero point must be calibrated before the span point and the sensor
+    /// must have been operating in a stable environment of the given
+    /// `span_ppm` concentration for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        BaseApi::calibrate_span(self, span_ppm)
+    }
 
-        if fw_version[1] >= b'5' {
-            Ok(MhZ19CFw5 { mh_z19c: self })
-        } else {
-            Err(nb::Error::Other(Error::NotSupportedByFirmware(fw_version)))
-        }
+    /// Sets the detection range (upper limit of measurable CO₂ concentration)
+    /// in ppm.
+    ///
+    /// The MH-Z19C supports ranges of 2000, 5000, and 10000 ppm. The
+    /// detection range will be tracked (see [`MhZ19C::detection_range_ppm`]).
+    pub fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        BaseApi::set_detection_range(self, range_ppm)
     }
 
-    fn poll(&mut self) -> nb::Result<(), Error<E>> {
-        use MhZ19CState::*;
-        match &mut self.state {
-            Idle => Ok(()),
-            ReadCo2AndTemperature(future) => future.poll(),
-            ReadCo2(future) => future.poll(),
-            GetFirmwareVersion(future) => future.poll(),
-            SetSelfCalibrate(future) => future.poll(),
-        }
-        .map_err(|err| err.map(Error::UartError))
+    /// Retrieves the detection range (upper limit of measurable CO₂
+    /// concentration) in ppm.
+    ///
+    /// The detection range will be tracked (see
+    /// [`MhZ19C::detection_range_ppm`]).
+    pub fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        BaseApi::get_detection_range(self)
     }
 
-    fn recover_uart(&mut self, state: MhZ19CState<U, E>) {
-        use MhZ19CState::*;
-        match state {
-            Idle => (),
-            ReadCo2AndTemperature(future) => self.uart = Some(future.into_return_value().0),
-            ReadCo2(future) => self.uart = Some(future.into_return_value().0),
-            GetFirmwareVersion(future) => self.uart = Some(future.into_return_value().0),
-            SetSelfCalibrate(future) => self.uart = Some(future.into_return_value()),
-        }
+    /// Returns the tracked detection range in ppm or `None` if it has neither
+    /// been set nor retrieved yet.
+    ///
+    /// While the detection range is known, CO₂ readings at or above it are
+    /// considered saturated and rejected with [`Error::OutOfRange`].
+    pub fn detection_range_ppm(&self) -> Option<u16> {
+        self.detection_range_ppm
     }
 
-    fn unpack_return_frame(command: Command, frame: &Frame) -> Result<&[u8], Error<E>> {
-        frame.validate().map_err(Error::ValidateFrameError)?;
-        if !frame.is_response() {
-            Err(Error::NotAResponse)
-        } else if frame.op_code() != command.op_code() {
-            Err(Error::OpCodeMismatch {
-                expected: command.op_code(),
-                got: frame.op_code(),
-            })
-        } else {
-            Ok(frame.data())
-        }
+    /// Queries and caches the firmware version unless it has been cached
+    /// already.
+    ///
+    /// Calling this method is optional as the firmware version will be
+    /// queried on first use by methods depending on it.
+    pub fn init(&mut self) -> nb::Result<(), Error<E>> {
+        self.cached_firmware_version().map(drop)
     }
-}
 
-impl<'a, U, E> BaseApi<E> for MhZ19C<'a, U, E>
-where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
-{
-    fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+    /// Returns the cached firmware version or `None` if it has not been
+    /// queried yet.
+    pub fn firmware_version(&self) -> Option<FirmwareVersion> {
+        self.firmware_version
+    }
+
+    /// Returns the capabilities according to the cached firmware version or
+    /// `None` if it has not been queried yet.
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        self.firmware_version
+            .map(Capabilities::from_firmware_version)
+    }
+
+    /// Reads the CO₂ concentration and temperature.
+    ///
+    /// The firmware version will be queried and cached on first use. If the
+    /// firmware does not support the combined reading,
+    /// [`Error::NotSupportedByFirmware`] will be returned.
+    #[cfg(feature = "firmware5")]
+    pub fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        self.execute_read_co2_and_temp()
+    }
+
+    /// Reads the CO₂ concentration and temperature with any firmware version.
+    ///
+    /// The firmware version will be queried and cached on first use to
+    /// select the [`TemperatureEncoding`], which is returned together with
+    /// the reading. Firmware 5 and higher provide the temperature with a
+    /// resolution of 0.01 °C (see [`MhZ19C::read_co2_and_temp`]), older
+    /// firmware only in whole degrees as part of the CO₂ reading.
+    #[cfg(feature = "firmware5")]
+    pub fn read_co2_and_temp_any_firmware(
+        &mut self,
+    ) -> nb::Result<(Co2AndTemperature, TemperatureEncoding), Error<E>> {
+        let encoding = TemperatureEncoding::from_firmware_version(self.cached_firmware_version()?);
+        let reading = match encoding {
+            TemperatureEncoding::FixedPoint => self.execute_read_co2_and_temp()?,
+            TemperatureEncoding::OffsetByte => self.execute(Command::ReadCo2, |this, data| {
+                let reading = P::decode_co2_and_offset_temperature(data);
+                this.check_range(reading.co2_ppm)?;
+                Ok(this.correct(reading))
+            })?,
+        };
+        Ok((reading, encoding))
+    }
+
+    /// Executes `command` and reads the response directly into the
+    /// caller-provided `response` buffer.
+    ///
+    /// Returns a borrowed view of the validated response frame in `response`
+    /// without copying it, or `None` for commands without response (see
+    /// [`Command::has_response`]). The same buffer has to be passed on each
+    /// call until the command completes. No decoding of the response takes
+    /// place and the cached firmware version and detection range will not be
+    /// updated.
+    pub fn execute_into<'r>(
+        &mut self,
+        command: Command,
+        response: &'r mut [u8; 9],
+    ) -> nb::Result<Option<&'r Frame>, Error<E>> {
+        if !command.has_response() {
+            return self.execute(command, |_, _| Ok(None));
+        }
+
+        Self::ensure_supported(command)?;
+        self.check_capabilities(command)?;
         loop {
             if let MhZ19CState::Idle = &mut self.state {
-                let uart = self.uart.take().unwrap();
-                self.state = MhZ19CState::ReadCo2(WriteAndReadResponse::new(
-                    uart,
-                    READ_CO2.as_ref(),
-                    [0u8; 9],
-                    9,
-                ));
+                self.discard_trailing_bytes();
+                self.state = MhZ19CState::RequestInto(
+                    command,
+                    WriteAndReadInto::new(command.into(), 9).with_echo(self.echo_suppression),
+                );
             }
 
-            self.poll()?;
+            match &mut self.state {
+                MhZ19CState::RequestInto(pending, future) if *pending == command => future
+                    .poll_into(&mut self.uart, response)
+                    .map_err(|err| err.map(Error::UartError))?,
+                _ => self.poll()?,
+            }
 
-            let state = core::mem::take(&mut self.state);
-            if let MhZ19CState::ReadCo2(future) = state {
-                let (uart, buf) = future.into_return_value();
-                self.uart = Some(uart);
-                let frame = Frame::new(buf);
-                let data = Self::unpack_return_frame(Command::ReadCo2, &frame)
-                    .map_err(nb::Error::Other)?;
-                return Ok(u16::from_be_bytes(data[..2].try_into().unwrap()));
-            } else {
-                self.recover_uart(state);
+            match core::mem::take(&mut self.state) {
+                MhZ19CState::RequestInto(pending, future) if pending == command => {
+                    if !future.echo_matches() {
+                        self.retry_or_fail(Error::EchoMismatch)?;
+                        continue;
+                    }
+                    let result = Self::unpack_return_frame(
+                        command,
+                        Frame::from_bytes(response),
+                        self.validation,
+                    );
+                    self.track_validation(&result);
+                    if let Err(err) = result {
+                        self.retry_or_fail(err)?;
+                        continue;
+                    }
+                    self.retries_used = 0;
+                    return Ok(Some(Frame::from_bytes(response)));
+                }
+                _ => self.retries_used = 0,
             }
         }
     }
 
-    fn get_firmware_version(&mut self) -> nb::Result<[u8; 4], Error<E>> {
-        loop {
-            if let MhZ19CState::Idle = &mut self.state {
-                let uart = self.uart.take().unwrap();
-                self.state = MhZ19CState::GetFirmwareVersion(WriteAndReadResponse::new(
-                    uart,
-                    GET_FIRMWARE_VERSION.as_ref(),
-                    [0u8; 9],
-                    9,
-                ));
-            }
+    /// Returns the number of consecutively received frames with an invalid
+    /// start byte or checksum (saturating at 255).
+    pub fn consecutive_invalid_frames(&self) -> u8 {
+        self.consecutive_invalid_frames
+    }
 
-            self.poll()?;
+    /// Returns `true` if the baud rate of the UART interface likely does not
+    /// match the baud rate of the sensor (9600 baud by default).
+    ///
+    /// A baud rate mismatch garbles all received bytes and manifests as
+    /// [`ValidateFrameError`]s. Thus, a mismatch is assumed if at least
+    /// [`BAUD_MISMATCH_THRESHOLD`] frames in a row failed the validation.
+    /// Occasional invalid frames caused by noise do not trigger the hint.
+    pub fn likely_baud_mismatch(&self) -> bool {
+        self.consecutive_invalid_frames >= BAUD_MISMATCH_THRESHOLD
+    }
 
-            let state = core::mem::take(&mut self.state);
-            if let MhZ19CState::GetFirmwareVersion(future) = state {
-                let (uart, buf) = future.into_return_value();
-                self.uart = Some(uart);
-                let frame = Frame::new(buf);
-                let data = Self::unpack_return_frame(Command::GetFirmwareVersion, &frame)
-                    .map_err(nb::Error::Other)?;
-                let ret_data = [data[0], data[1], data[2], data[3]];
-                return Ok(ret_data);
-            } else {
-                self.recover_uart(state);
-            }
+    /// Returns `true` if a command is in progress, i.e. the last command call
+    /// returned [`nb::Error::WouldBlock`].
+    pub fn is_busy(&self) -> bool {
+        self.pending_command().is_some()
+    }
+
+    /// Returns the command in progress or `None` if the driver is idle.
+    ///
+    /// Calling any other command will complete the pending command first and
+    /// discard its result.
+    pub fn pending_command(&self) -> Option<Command> {
+        match &self.state {
+            MhZ19CState::Idle => None,
+            MhZ19CState::Request(command, _)
+            | MhZ19CState::Send(command, _)
+            | MhZ19CState::RequestInto(command, _) => Some(*command),
         }
     }
 
-    fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+    /// Cancels the command in progress and discards all bytes currently
+    /// available from the UART interface.
+    ///
+    /// Returns the number of discarded bytes. Afterwards the driver is idle
+    /// and a new command can be started without completing the cancelled one
+    /// first. Note that bytes of the cancelled command already written to
+    /// the sensor cannot be recalled and a response arriving after the call
+    /// will not be discarded.
+    pub fn abort(&mut self) -> Result<usize, Error<E>> {
+        self.state = MhZ19CState::Idle;
+        self.retries_used = 0;
+        self.self_test_firmware_version = None;
+        self.discard_available()
+    }
+
+    fn discard_available(&mut self) -> Result<usize, Error<E>> {
+        let mut discarded = 0;
         loop {
-            if let MhZ19CState::Idle = &mut self.state {
-                let uart = self.uart.take().unwrap();
-                let frame: Frame = Command::SetSelfCalibrate(enabled).into();
-                self.state = MhZ19CState::SetSelfCalibrate(WriteAll::new(uart, frame));
+            match self.uart.read() {
+                Ok(_) => discarded += 1,
+                Err(nb::Error::WouldBlock) => return Ok(discarded),
+                Err(nb::Error::Other(err)) => return Err(Error::UartError(err)),
             }
+        }
+    }
 
-            self.poll()?;
+    /// Returns a reference to the UART interface.
+    pub fn uart(&self) -> &U {
+        &self.uart
+    }
 
-            let state = core::mem::take(&mut self.state);
-            if let MhZ19CState::SetSelfCalibrate(future) = state {
-                self.uart = Some(future.into_return_value());
-                return Ok(());
-            } else {
-                self.recover_uart(state);
-            }
-        }
+    /// Returns a mutable reference to the UART interface, e.g. to adjust its
+    /// timeouts.
+    ///
+    /// Reading from or writing to the interface directly while a command is
+    /// in progress (see [`MhZ19C::is_busy`]) will corrupt the command.
+    /// Likewise, bytes left in the receive buffer of the interface will be
+    /// read as part of the next response. Changing the baud rate requires
+    /// the sensor to be reconfigured accordingly.
+    pub fn uart_mut(&mut self) -> &mut U {
+        &mut self.uart
     }
-}
 
-/// Driver for the MH-Z19C sensor with firmware 5 capabilities.
-pub struct MhZ19CFw5<'a, 'b, U, E>
-where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
-{
-    mh_z19c: &'b mut MhZ19C<'a, U, E>,
-}
+    /// Replaces the UART interface, e.g. after a USB serial adapter has been
+    /// re-enumerated, and returns the previous one.
+    ///
+    /// The command in progress is cancelled as its bytes cannot be continued
+    /// on the new interface. The configuration and the cached firmware
+    /// version and detection range are preserved.
+    pub fn replace_uart(&mut self, uart: U) -> U {
+        self.state = MhZ19CState::Idle;
+        self.retries_used = 0;
+        self.self_test_firmware_version = None;
+        core::mem::replace(&mut self.uart, uart)
+    }
 
-impl<'a, 'b, U, E> MhZ19CFw5<'a, 'b, U, E>
-where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
-{
-    /// Reads and returns the CO₂ concentration in parts-per-million (ppm).
-    pub fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
-        BaseApi::read_co2_ppm(self)
+    /// Returns the owned UART interface together with a builder preserving
+    /// the configuration and the cached firmware version and detection
+    /// range.
+    ///
+    /// This allows to release the UART interface (e.g. while waiting for a
+    /// disconnected USB serial adapter to reappear) and to rebuild the driver
+    /// with a new interface later on. The command in progress is cancelled.
+    pub fn into_builder(self) -> (U, MhZ19CBuilder<'a, U, E, P>) {
+        let builder = MhZ19CBuilder::from_driver(&self);
+        (self.uart, builder)
     }
 
-    /// Retrieves the firmware version of the sensor.
-    pub fn get_firmware_version(&mut self) -> nb::Result<[u8; 4], Error<E>> {
-        BaseApi::get_firmware_version(self)
+    /// Returns the owned UART interface.
+    ///
+    /// Note that this might leave the interface with partially written or read
+    /// bytes on the UART interface if not all MH-Z19C commands have been polled
+    /// to completion (i.e. the last command call did not return
+    /// [`nb::Error::WouldBlock`]).
+    pub fn into_inner(self) -> U {
+        self.uart
     }
 
-    /// Activates or deactivates the sensor's self-calibration mode.
+    /// Queries the firmware version to determine the sensor variant and the
+    /// supported API.
     ///
-    /// See the sensor's data sheet for more information on self-calibration
-    /// and hand-operated mode.
-    pub fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
-        BaseApi::set_self_calibrate(self, enabled)
+    /// This allows applications to select the appropriate API at runtime
+    /// without handling [`Error::NotSupportedByFirmware`].
+    pub fn detect(&mut self) -> nb::Result<DetectedSensor, Error<E>> {
+        let firmware_version = self.get_firmware_version()?;
+        let model = P::MODEL;
+        if Capabilities::from_firmware_version(firmware_version)
+            .supports(Command::ReadCo2AndTemperature)
+        {
+            Ok(DetectedSensor::Firmware5 {
+                model,
+                firmware_version,
+            })
+        } else {
+            Ok(DetectedSensor::Legacy {
+                model,
+                firmware_version,
+            })
+        }
     }
 
-    /// Reads the CO₂ concentration and temperature.
-    pub fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
-        Firmware5Api::read_co2_and_temp(self)
+    /// Checks the communication with the sensor, e.g. as part of a power-on
+    /// self-test.
+    ///
+    /// Queries the firmware version and reads the CO₂ concentration. If the
+    /// communication succeeds, the returned report gives the plausibility of
+    /// the reported values (see [`SelfTestReport::passed`]). In particular, a
+    /// reading outside of the detection range is reported instead of
+    /// returning [`Error::OutOfRange`].
+    ///
+    /// The firmware version is always queried from the sensor, even if it has
+    /// been cached already. A command in progress (e.g. a reading started by
+    /// [`MhZ19C::with_read_ahead`]) is completed first and its result
+    /// discarded, so that the report only contains values received during
+    /// the self-test.
+    pub fn self_test(&mut self) -> nb::Result<SelfTestReport, Error<E>> {
+        let firmware_version = match self.self_test_firmware_version {
+            Some(firmware_version) => firmware_version,
+            None => {
+                let firmware_version = self.get_firmware_version()?;
+                self.self_test_firmware_version = Some(firmware_version);
+                firmware_version
+            }
+        };
+        let co2_ppm = match self.read_co2_ppm() {
+            Err(nb::Error::WouldBlock) => return Err(nb::Error::WouldBlock),
+            result => {
+                self.self_test_firmware_version = None;
+                match result {
+                    Ok(co2_ppm) | Err(nb::Error::Other(Error::OutOfRange { co2_ppm, .. })) => {
+                        co2_ppm
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+        };
+        Ok(SelfTestReport {
+            firmware_version,
+            co2_ppm,
+            detection_range_ppm: self.detection_range_ppm,
+        })
     }
-}
 
-impl<'a, 'b, U, E> BaseApi<E> for MhZ19CFw5<'a, 'b, U, E>
-where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
-{
-    fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
-        self.mh_z19c.read_co2_ppm()
+    /// Will make the [`Firmware5Api`] capabilities available.
+    ///
+    /// If the sensor firmware is not at least of version 5, an error will be
+    /// returned. The firmware version will be queried and cached on first
+    /// use.
+    #[cfg(feature = "firmware5")]
+    pub fn upgrade_to_v5<'b>(&'b mut self) -> nb::Result<MhZ19CFw5<'a, 'b, U, E, P>, Error<E>> {
+        self.check_firmware5()?;
+        Ok(MhZ19CFw5 { mh_z19c: self })
     }
 
-    fn get_firmware_version(&mut self) -> nb::Result<[u8; 4], Error<E>> {
-        self.mh_z19c.get_firmware_version()
+    /// Converts the driver into a driver with the [`Firmware5Api`]
+    /// capabilities available.
+    ///
+    /// In contrast to [`MhZ19C::upgrade_to_v5`], the returned driver takes
+    /// ownership and can be stored or moved as needed. If the sensor firmware
+    /// is not at least of version 5 or the firmware version query has not
+    /// completed yet ([`nb::Error::WouldBlock`]), the driver will be returned
+    /// together with the error.
+    #[allow(clippy::type_complexity)]
+    #[cfg(feature = "firmware5")]
+    pub fn into_v5(mut self) -> Result<MhZ19CFw5Owned<'a, U, E, P>, (Self, nb::Error<Error<E>>)> {
+        match self.check_firmware5() {
+            Ok(()) => Ok(MhZ19CFw5Owned { mh_z19c: self }),
+            Err(err) => Err((self, err)),
+        }
     }
 
-    fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
-        self.mh_z19c.set_self_calibrate(enabled)
+    fn cached_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        match self.firmware_version {
+            Some(firmware_version) => Ok(firmware_version),
+            None => self.get_firmware_version(),
+        }
     }
-}
 
-impl<'a, 'b, U, E> Firmware5Api<E> for MhZ19CFw5<'a, 'b, U, E>
-where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
-{
-    fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
-        loop {
-            if let MhZ19CState::Idle = &mut self.mh_z19c.state {
-                let uart = self.mh_z19c.uart.take().unwrap();
-                self.mh_z19c.state = MhZ19CState::ReadCo2AndTemperature(WriteAndReadResponse::new(
-                    uart,
-                    READ_CO2_AND_TEMPERATURE.as_ref(),
-                    [0u8; 9],
-                    9,
-                ));
-            }
+    #[cfg(feature = "firmware5")]
+    fn check_firmware5(&mut self) -> nb::Result<(), Error<E>> {
+        self.check_capabilities(Command::ReadCo2AndTemperature)
+    }
 
-            self.mh_z19c.poll()?;
-
-            let state = core::mem::take(&mut self.mh_z19c.state);
-            if let MhZ19CState::ReadCo2AndTemperature(future) = state {
-                let (uart, buf) = future.into_return_value();
-                self.mh_z19c.uart = Some(uart);
-                let frame = Frame::new(buf);
-                let data =
-                    MhZ19C::<'a, U, E>::unpack_return_frame(Command::ReadCo2AndTemperature, &frame)
-                        .map_err(nb::Error::Other)?;
-
-                let co2_ppm = u16::from_be_bytes(data[2..4].try_into().unwrap());
-                let temp_celsius =
-                    f32::from(u16::from_be_bytes(data[..2].try_into().unwrap())) / 100.0;
-
-                return Ok(Co2AndTemperature {
-                    co2_ppm,
-                    temp_celsius,
-                });
-            } else {
-                self.mh_z19c.recover_uart(state);
-            }
+    /// Checks that the sensor firmware provides the capabilities required by
+    /// `command` (see [`Capabilities::required_for`]).
+    ///
+    /// The firmware version is only queried (and cached) for commands that
+    /// are not supported by all firmware versions.
+    fn check_capabilities(&mut self, command: Command) -> nb::Result<(), Error<E>> {
+        if Capabilities::BASE.supports(command) {
+            return Ok(());
+        }
+        let fw_version = self.cached_firmware_version()?;
+        if Capabilities::from_firmware_version(fw_version).supports(command) {
+            Ok(())
+        } else {
+            Err(nb::Error::Other(Error::NotSupportedByFirmware(fw_version)))
         }
     }
-}
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum Error<T> {
+    #[cfg(feature = "firmware5")]
+    fn execute_read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        self.execute(Command::ReadCo2AndTemperature, |this, data| {
+            let reading = P::decode_co2_and_temperature(data);
+            this.check_range(reading.co2_ppm)?;
+            Ok(this.correct(reading))
+        })
+    }
+
+    /// Executes `command` and decodes the response data with `decode`.
+    ///
+    /// Commands without response will pass zeros as data to `decode`. If
+    /// another command is still in progress, it will be completed first and
+    /// its result discarded.
+    fn execute<T, F>(&mut self, command: Command, decode: F) -> nb::Result<T, Error<E>>
+    where
+        F: FnOnce(&mut Self, &[u8; 6]) -> Result<T, Error<E>>,
+    {
+        Self::ensure_supported(command)?;
+        self.check_capabilities(command)?;
+        loop {
+            if let MhZ19CState::Idle = &mut self.state {
+                self.start(command);
+            }
+
+            self.poll()?;
+
+            match core::mem::take(&mut self.state) {
+                MhZ19CState::Request(pending, future) if pending == command => {
+                    if !future.echo_matches() {
+                        self.retry_or_fail(Error::EchoMismatch)?;
+                        continue;
+                    }
+                    if !command.has_response() {
+                        self.retries_used = 0;
+                        return decode(self, &[0; 6]).map_err(nb::Error::Other);
+                    }
+                    let frame = Frame::new(future.into_inner());
+                    let result = Self::unpack_return_frame(command, &frame, self.validation);
+                    self.track_validation(&result);
+                    let data = match result {
+                        Ok(data) => data,
+                        Err(err) => {
+                            self.retry_or_fail(err)?;
+                            continue;
+                        }
+                    };
+                    self.retries_used = 0;
+                    if self.read_ahead
+                        && matches!(command, Command::ReadCo2 | Command::ReadCo2AndTemperature)
+                    {
+                        self.start(command);
+                        // Errors will be reported when completing the command.
+                        let _ = self.poll();
+                    }
+                    return decode(self, &data).map_err(nb::Error::Other);
+                }
+                MhZ19CState::Send(pending, _) if pending == command => {
+                    return decode(self, &[0; 6]).map_err(nb::Error::Other);
+                }
+                _ => self.retries_used = 0,
+            }
+        }
+    }
+
+    /// Consumes one of the configured retries if `err` indicates a corrupted
+    /// response, so that the caller re-issues the command. Otherwise, `err`
+    /// is returned.
+    fn retry_or_fail(&mut self, err: Error<E>) -> nb::Result<(), Error<E>> {
+        let retryable = matches!(err, Error::ValidateFrameError(_) | Error::EchoMismatch);
+        if retryable && self.retries_used < self.retries {
+            self.retries_used += 1;
+            Ok(())
+        } else {
+            self.retries_used = 0;
+            Err(nb::Error::Other(err))
+        }
+    }
+
+    fn start(&mut self, command: Command) {
+        self.discard_trailing_bytes();
+        let frame: Frame = command.into();
+        self.state = if command.has_response() || self.echo_suppression {
+            let response_len = if command.has_response() { 9 } else { 0 };
+            MhZ19CState::Request(
+                command,
+                WriteAndReadResponse::new(frame, [0u8; 9], response_len)
+                    .with_echo(self.echo_suppression),
+            )
+        } else {
+            MhZ19CState::Send(command, WriteAll::new(frame))
+        };
+    }
+
+    #[cfg(feature = "firmware5")]
+    fn correct(&self, mut reading: Co2AndTemperature) -> Co2AndTemperature {
+        reading.co2_ppm = self.co2_correction.apply(reading.co2_ppm);
+        reading.temp_celsius = reading.raw_temp_celsius + self.temp_offset_celsius;
+        reading
+    }
+
+    fn discard_trailing_bytes(&mut self) {
+        if self.validation.discard_trailing_bytes {
+            // A UART error will be reported again by the command.
+            let _ = self.discard_available();
+        }
+    }
+
+    fn poll(&mut self) -> nb::Result<(), Error<E>> {
+        let uart = &mut self.uart;
+        match &mut self.state {
+            MhZ19CState::Idle => Ok(()),
+            MhZ19CState::Request(_, future) => future.poll(uart),
+            MhZ19CState::Send(_, future) => future.poll(uart),
+            MhZ19CState::RequestInto(_, future) => future.poll_into(uart, &mut [0u8; 9]),
+        }
+        .map_err(|err| err.map(Error::UartError))
+    }
+
+    fn check_range(&self, co2_ppm: u16) -> Result<(), Error<E>> {
+        match self.detection_range_ppm {
+            Some(range_ppm) if co2_ppm >= range_ppm => {
+                Err(Error::OutOfRange { co2_ppm, range_ppm })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn ensure_supported(command: Command) -> nb::Result<(), Error<E>> {
+        if P::supports(command) {
+            Ok(())
+        } else {
+            Err(nb::Error::Other(Error::NotSupportedByDevice(command)))
+        }
+    }
+
+    fn track_validation<T>(&mut self, result: &Result<T, Error<E>>) {
+        self.consecutive_invalid_frames = match result {
+            Err(Error::ValidateFrameError(_)) => self.consecutive_invalid_frames.saturating_add(1),
+            _ => 0,
+        };
+    }
+
+    fn unpack_return_frame(
+        command: Command,
+        frame: &Frame,
+        validation: Validation,
+    ) -> Result<[u8; 6], Error<E>> {
+        frame
+            .validate_with(validation)
+            .map_err(Error::ValidateFrameError)?;
+        if !frame.is_response() {
+            Err(Error::NotAResponse)
+        } else if frame.op_code() != command.op_code() {
+            Err(Error::OpCodeMismatch {
+                expected: command.op_code(),
+                got: frame.op_code(),
+            })
+        } else {
+            Ok(frame.response_data())
+        }
+    }
+}
+
+impl<'a, U, E, P> BaseApi<E> for MhZ19C<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+        self.execute(Command::ReadCo2, |this, data| {
+            let co2_ppm = P::decode_co2_ppm(data);
+            this.check_range(co2_ppm)?;
+            Ok(this.co2_correction.apply(co2_ppm))
+        })
+    }
+
+    fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        self.execute(Command::GetFirmwareVersion, |this, data| {
+            let firmware_version = FirmwareVersion::new([data[0], data[1], data[2], data[3]]);
+            this.firmware_version = Some(firmware_version);
+            Ok(firmware_version)
+        })
+    }
+
+    #[cfg(feature = "calibration")]
+    fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+        self.execute(Command::SetSelfCalibrate(enabled), |_, _| Ok(()))
+    }
+
+    #[cfg(feature = "calibration")]
+    fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        self.execute(Command::GetSelfCalibrate, |_, data| Ok(data[5] != 0))
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        self.execute(Command::CalibrateZero, |_, _| Ok(()))
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.execute(Command::CalibrateSpan(span_ppm), |_, _| Ok(()))
+    }
+
+    fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.execute(Command::SetDetectionRange(range_ppm), |this, _| {
+            this.detection_range_ppm = Some(range_ppm);
+            Ok(())
+        })
+    }
+
+    fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        self.execute(Command::GetDetectionRange, |this, data| {
+            let range_ppm = u16::from_be_bytes([data[2], data[3]]);
+            this.detection_range_ppm = Some(range_ppm);
+            Ok(range_ppm)
+        })
+    }
+}
+
+/// Driver for the MH-Z19C sensor with firmware 5 capabilities.
+#[cfg(feature = "firmware5")]
+pub struct MhZ19CFw5<'a, 'b, U, E, P = MhZ19CProfile>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+{
+    mh_z19c: &'b mut MhZ19C<'a, U, E, P>,
+}
+
+#[cfg(feature = "firmware5")]
+impl<'a, 'b, U, E, P> MhZ19CFw5<'a, 'b, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Returns a reference to the wrapped [`MhZ19C`] driver.
+    pub fn get_ref(&self) -> &MhZ19C<'a, U, E, P> {
+        self.mh_z19c
+    }
+
+    /// Returns a mutable reference to the wrapped [`MhZ19C`] driver, e.g. to
+    /// use methods not provided by the [`Firmware5Api`].
+    pub fn get_mut(&mut self) -> &mut MhZ19C<'a, U, E, P> {
+        self.mh_z19c
+    }
+
+    /// Reads and returns the CO₂ concentration in parts-per-million (ppm).
+    pub fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+        BaseApi::read_co2_ppm(self)
+    }
+
+    /// Retrieves the firmware version of the sensor.
+    pub fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        BaseApi::get_firmware_version(self)
+    }
+
+    /// Activates or deactivates the sensor's self-calibration mode.
+    ///
+    /// See the sensor's data sheet for more information on self-calibration
+    /// and hand-operated mode.
+    #[cfg(feature = "calibration")]
+    pub fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+        BaseApi::set_self_calibrate(self, enabled)
+    }
+
+    /// Retrieves whether the sensor's self-calibration mode is activated.
+    #[cfg(feature = "calibration")]
+    pub fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        BaseApi::get_self_calibrate(self)
+    }
+
+    /// Calibrates the zero point (400 ppm) to the current reading.
+    ///
+    /// The sensor must have been operating in a stable 400 ppm environment
+    /// (e.g. fresh outdoor air) for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        BaseApi::calibrate_zero(self)
+    }
+
+    /// Calibrates the span point to the current reading.
+    ///
+    /// The zero point must be calibrated before the span point and the sensor
+    /// must have been operating in a stable environment of the given
+    /// `span_ppm` concentration for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        BaseApi::calibrate_span(self, span_ppm)
+    }
+
+    /// Sets the detection range (upper limit of measurable CO₂ concentration)
+    /// in ppm.
+    ///
+    /// The MH-Z19C supports ranges of 2000, 5000, and 10000 ppm.
+    pub fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        BaseApi::set_detection_range(self, range_ppm)
+    }
+
+    /// Retrieves the detection range (upper limit of measurable CO₂
+    /// concentration) in ppm.
+    pub fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        BaseApi::get_detection_range(self)
+    }
+
+    /// Reads the CO₂ concentration and temperature.
+    pub fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        Firmware5Api::read_co2_and_temp(self)
+    }
+}
+
+#[cfg(feature = "firmware5")]
+impl<'a, 'b, U, E, P> BaseApi<E> for MhZ19CFw5<'a, 'b, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+        self.mh_z19c.read_co2_ppm()
+    }
+
+    fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        self.mh_z19c.get_firmware_version()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.set_self_calibrate(enabled)
+    }
+
+    #[cfg(feature = "calibration")]
+    fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        self.mh_z19c.get_self_calibrate()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.calibrate_zero()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.calibrate_span(span_ppm)
+    }
+
+    fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.set_detection_range(range_ppm)
+    }
+
+    fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        self.mh_z19c.get_detection_range()
+    }
+}
+
+#[cfg(feature = "firmware5")]
+impl<'a, 'b, U, E, P> Firmware5Api<E> for MhZ19CFw5<'a, 'b, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        self.mh_z19c.execute_read_co2_and_temp()
+    }
+}
+
+/// Driver for the MH-Z19C sensor with firmware 5 capabilities taking
+/// ownership of the [`MhZ19C`] driver.
+///
+/// Returned by [`MhZ19C::into_v5`].
+#[derive(Debug)]
+#[cfg(feature = "firmware5")]
+pub struct MhZ19CFw5Owned<'a, U, E, P = MhZ19CProfile>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+{
+    mh_z19c: MhZ19C<'a, U, E, P>,
+}
+
+#[cfg(feature = "firmware5")]
+impl<'a, U, E, P> MhZ19CFw5Owned<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Returns a reference to the wrapped [`MhZ19C`] driver.
+    pub fn get_ref(&self) -> &MhZ19C<'a, U, E, P> {
+        &self.mh_z19c
+    }
+
+    /// Returns a mutable reference to the wrapped [`MhZ19C`] driver, e.g. to
+    /// use methods not provided by the [`Firmware5Api`].
+    pub fn get_mut(&mut self) -> &mut MhZ19C<'a, U, E, P> {
+        &mut self.mh_z19c
+    }
+
+    /// Reads and returns the CO₂ concentration in parts-per-million (ppm).
+    pub fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+        BaseApi::read_co2_ppm(self)
+    }
+
+    /// Retrieves the firmware version of the sensor.
+    pub fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        BaseApi::get_firmware_version(self)
+    }
+
+    /// Activates or deactivates the sensor's self-calibration mode.
+    ///
+    /// See the sensor's data sheet for more information on self-calibration
+    /// and hand-operated mode.
+    #[cfg(feature = "calibration")]
+    pub fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+        BaseApi::set_self_calibrate(self, enabled)
+    }
+
+    /// Retrieves whether the sensor's self-calibration mode is activated.
+    #[cfg(feature = "calibration")]
+    pub fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        BaseApi::get_self_calibrate(self)
+    }
+
+    /// Calibrates the zero point (400 ppm) to the current reading.
+    ///
+    /// The sensor must have been operating in a stable 400 ppm environment
+    /// (e.g. fresh outdoor air) for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        BaseApi::calibrate_zero(self)
+    }
+
+    /// Calibrates the span point to the current reading.
+    ///
+    /// The zero point must be calibrated before the span point and the sensor
+    /// must have been operating in a stable environment of the given
+    /// `span_ppm` concentration for at least 20 minutes before calibrating.
+    /// See the sensor's data sheet for more information.
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        BaseApi::calibrate_span(self, span_ppm)
+    }
+
+    /// Sets the detection range (upper limit of measurable CO₂ concentration)
+    /// in ppm.
+    ///
+    /// The MH-Z19C supports ranges of 2000, 5000, and 10000 ppm.
+    pub fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        BaseApi::set_detection_range(self, range_ppm)
+    }
+
+    /// Retrieves the detection range (upper limit of measurable CO₂
+    /// concentration) in ppm.
+    pub fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        BaseApi::get_detection_range(self)
+    }
+
+    /// Reads the CO₂ concentration and temperature.
+    pub fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        Firmware5Api::read_co2_and_temp(self)
+    }
+
+    /// Returns the wrapped [`MhZ19C`] driver.
+    pub fn into_inner(self) -> MhZ19C<'a, U, E, P> {
+        self.mh_z19c
+    }
+}
+
+#[cfg(feature = "firmware5")]
+impl<'a, U, E, P> BaseApi<E> for MhZ19CFw5Owned<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+        self.mh_z19c.read_co2_ppm()
+    }
+
+    fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        self.mh_z19c.get_firmware_version()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.set_self_calibrate(enabled)
+    }
+
+    #[cfg(feature = "calibration")]
+    fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        self.mh_z19c.get_self_calibrate()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.calibrate_zero()
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.calibrate_span(span_ppm)
+    }
+
+    fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.mh_z19c.set_detection_range(range_ppm)
+    }
+
+    fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        self.mh_z19c.get_detection_range()
+    }
+}
+
+#[cfg(feature = "firmware5")]
+impl<'a, U, E, P> Firmware5Api<E> for MhZ19CFw5Owned<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        self.mh_z19c.execute_read_co2_and_temp()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error<T> {
     /// The frame of a command or return value was invalid.
     ValidateFrameError(ValidateFrameError),
     /// The received data is not response.
@@ -442,9 +1502,39 @@ pub enum Error<T> {
     UartError(T),
     /// Cannot upgrade to requested firmware version.
     /// Firmware version reported by the sensor will be included.
-    NotSupportedByFirmware([u8; 4]),
+    NotSupportedByFirmware(FirmwareVersion),
+    /// The command is not supported by the sensor model according to its
+    /// [`DeviceProfile`].
+    NotSupportedByDevice(Command),
+    /// The CO₂ reading is at or above the detection range, i.e. the sensor is
+    /// saturated and the reading does not reflect the actual concentration.
+    OutOfRange { co2_ppm: u16, range_ppm: u16 },
+    /// The echo of the transmitted command did not match the command (see
+    /// [`MhZ19C::with_echo_suppression`]).
+    EchoMismatch,
 }
 
+impl<T> Error<T> {
+    /// Returns a numeric code identifying the kind of error.
+    ///
+    /// The codes are stable and allow to report errors without the formatting
+    /// machinery (e.g. as LED blink codes) if the `display` feature is
+    /// disabled.
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::ValidateFrameError(_) => 1,
+            Self::NotAResponse => 2,
+            Self::OpCodeMismatch { .. } => 3,
+            Self::UartError(_) => 4,
+            Self::NotSupportedByFirmware(_) => 5,
+            Self::NotSupportedByDevice(_) => 6,
+            Self::OutOfRange { .. } => 7,
+            Self::EchoMismatch => 8,
+        }
+    }
+}
+
+#[cfg(feature = "display")]
 impl<T: Display> Display for Error<T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -456,24 +1546,32 @@ impl<T: Display> Display for Error<T> {
             ),
             Self::UartError(err) => write!(f, "UART communication error: {err}"),
             Self::NotSupportedByFirmware(version) => {
-                write!(
-                    f,
-                    "not supported by firmware version {}",
-                    core::str::from_utf8(version).unwrap_or("<invalid version string>")
-                )
+                write!(f, "not supported by firmware version {version}")
             }
+            Self::NotSupportedByDevice(command) => {
+                write!(f, "command {command:?} not supported by device")
+            }
+            Self::OutOfRange { co2_ppm, range_ppm } => write!(
+                f,
+                "reading of {co2_ppm} ppm not within detection range of {range_ppm} ppm"
+            ),
+            Self::EchoMismatch => write!(f, "echo does not match the transmitted command"),
         }
     }
 }
 
-#[cfg(std)]
-impl std::error::Error for Error {}
+#[cfg(feature = "std")]
+impl<T: fmt::Debug + Display> std::error::Error for Error<T> {}
 
 #[cfg(test)]
 #[macro_use]
 extern crate std;
 
 #[cfg(test)]
+#[cfg_attr(
+    not(all(feature = "calibration", feature = "firmware5")),
+    allow(unused_imports)
+)]
 mod tests {
     use super::*;
 
@@ -482,8 +1580,10 @@ mod tests {
     use std::vec::Vec;
     use test_support::serial_mock::SerialMock;
     use test_support::{
-        create_serial_mock_returning, FIRMWARE_0400_RESPONSE, FIRMWARE_0515_RESPONSE,
-        READ_CO2_AND_TEMPERATURE_RESPONSE, READ_CO2_RESPONSE, SELF_CALIBRATE_ON_COMMAND,
+        create_serial_mock_returning, CALIBRATE_SPAN_2000_COMMAND, CALIBRATE_ZERO_COMMAND,
+        DETECTION_RANGE_5000_RESPONSE, FIRMWARE_0400_RESPONSE, FIRMWARE_0515_RESPONSE,
+        READ_CO2_5000_RESPONSE, READ_CO2_AND_TEMPERATURE_RESPONSE, READ_CO2_RESPONSE,
+        SELF_CALIBRATE_ON_COMMAND, SELF_CALIBRATE_ON_RESPONSE, SET_DETECTION_RANGE_5000_COMMAND,
     };
 
     #[test]
@@ -492,7 +1592,7 @@ mod tests {
         let mut co2sensor = MhZ19C::new(uart);
         let co2 = block!(co2sensor.read_co2_ppm());
         let uart = co2sensor.into_inner();
-        assert_eq!(uart.write_buf, &*READ_CO2.as_ref());
+        assert_eq!(uart.write_buf, READ_CO2.as_ref());
         assert_eq!(co2, Ok(800));
     }
 
@@ -511,7 +1611,7 @@ mod tests {
 
     #[test]
     fn test_read_co2_invalid_start_byte() {
-        let mut response = READ_CO2_RESPONSE.clone();
+        let mut response = READ_CO2_RESPONSE;
         response[0] = 0x00;
         let uart = create_serial_mock_returning(&response);
         let mut co2sensor = MhZ19C::new(uart);
@@ -524,42 +1624,175 @@ mod tests {
     }
 
     #[test]
-    fn test_read_co2_invalid_checksum() {
-        let mut response = READ_CO2_RESPONSE.clone();
-        response[8] = 0x00;
-        let uart = create_serial_mock_returning(&response);
+    fn test_read_co2_invalid_checksum() {
+        let mut response = READ_CO2_RESPONSE;
+        response[8] = 0x00;
+        let uart = create_serial_mock_returning(&response);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(
+            block!(co2sensor.read_co2_ppm()),
+            Err(Error::ValidateFrameError(
+                ValidateFrameError::InvalidChecksum {
+                    expected: READ_CO2_RESPONSE[8],
+                    actual: 0x00
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_read_co2_retries_invalid_response() {
+        let mut response = READ_CO2_RESPONSE;
+        response[8] = 0x00;
+        let uart = SerialMock::new(
+            response
+                .iter()
+                .chain(READ_CO2_RESPONSE.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 2 * 9],
+        );
+        let mut co2sensor = MhZ19C::builder().retries(1).build(uart);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+        let uart = co2sensor.into_inner();
+        assert_eq!(
+            uart.write_buf,
+            [READ_CO2.as_ref(), READ_CO2.as_ref()].concat()
+        );
+    }
+
+    #[test]
+    fn test_read_co2_retries_exhausted() {
+        let mut response = READ_CO2_RESPONSE;
+        response[0] = 0x00;
+        let uart = SerialMock::new(
+            response
+                .iter()
+                .chain(response.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 2 * 9],
+        );
+        let mut co2sensor = MhZ19C::builder().retries(1).build(uart);
+        assert_eq!(
+            block!(co2sensor.read_co2_ppm()),
+            Err(Error::ValidateFrameError(
+                ValidateFrameError::InvalidStartByte(0x00)
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_set_self_calibrate() -> Result<(), Error<String>> {
+        let uart = create_serial_mock_returning(&[]);
+        let mut co2sensor = MhZ19C::new(uart);
+        block!(co2sensor.set_self_calibrate(true))?;
+        let uart = co2sensor.into_inner();
+        assert_eq!(uart.write_buf, SELF_CALIBRATE_ON_COMMAND.as_ref());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_set_self_calibrate_uart_error() {
+        let uart = SerialMock::new(vec![], vec![Err(nb::Error::Other("No more data.".into()))]);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(
+            block!(co2sensor.set_self_calibrate(true)),
+            Err(Error::UartError("No more data.".into()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_get_self_calibrate() {
+        let uart = create_serial_mock_returning(&SELF_CALIBRATE_ON_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        let enabled = block!(co2sensor.get_self_calibrate());
+        let uart = co2sensor.into_inner();
+        assert_eq!(uart.write_buf, GET_SELF_CALIBRATE.as_ref());
+        assert_eq!(enabled, Ok(true));
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_calibrate_zero() -> Result<(), Error<String>> {
+        let uart = create_serial_mock_returning(&[]);
+        let mut co2sensor = MhZ19C::new(uart);
+        block!(co2sensor.calibrate_zero())?;
+        let uart = co2sensor.into_inner();
+        assert_eq!(uart.write_buf, CALIBRATE_ZERO_COMMAND.as_ref());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_calibrate_span() -> Result<(), Error<String>> {
+        let uart = create_serial_mock_returning(&[]);
+        let mut co2sensor = MhZ19C::new(uart);
+        block!(co2sensor.calibrate_span(2000))?;
+        let uart = co2sensor.into_inner();
+        assert_eq!(uart.write_buf, CALIBRATE_SPAN_2000_COMMAND.as_ref());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_detection_range() -> Result<(), Error<String>> {
+        let uart = create_serial_mock_returning(&[]);
         let mut co2sensor = MhZ19C::new(uart);
-        assert_eq!(
-            block!(co2sensor.read_co2_ppm()),
-            Err(Error::ValidateFrameError(
-                ValidateFrameError::InvalidChecksum {
-                    expected: READ_CO2_RESPONSE[8],
-                    actual: 0x00
-                }
-            ))
-        );
+        block!(co2sensor.set_detection_range(5000))?;
+        assert_eq!(co2sensor.detection_range_ppm(), Some(5000));
+        let uart = co2sensor.into_inner();
+        assert_eq!(uart.write_buf, SET_DETECTION_RANGE_5000_COMMAND.as_ref());
+        Ok(())
     }
 
     #[test]
-    fn test_set_self_calibrate() -> Result<(), Error<String>> {
-        let uart = create_serial_mock_returning(&[]);
+    fn test_get_detection_range() {
+        let uart = create_serial_mock_returning(&DETECTION_RANGE_5000_RESPONSE);
         let mut co2sensor = MhZ19C::new(uart);
-        block!(co2sensor.set_self_calibrate(true))?;
+        let range = block!(co2sensor.get_detection_range());
         let uart = co2sensor.into_inner();
-        assert_eq!(uart.write_buf, &*SELF_CALIBRATE_ON_COMMAND.as_ref());
-        Ok(())
+        assert_eq!(uart.write_buf, GET_DETECTION_RANGE.as_ref());
+        assert_eq!(range, Ok(5000));
     }
 
     #[test]
-    fn test_set_self_calibrate_uart_error() {
-        let uart = SerialMock::new(vec![], vec![Err(nb::Error::Other("No more data.".into()))]);
+    fn test_read_co2_out_of_range() {
+        let uart = SerialMock::new(
+            DETECTION_RANGE_5000_RESPONSE
+                .iter()
+                .chain(READ_CO2_RESPONSE.iter())
+                .chain(READ_CO2_5000_RESPONSE.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 27],
+        );
         let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(co2sensor.detection_range_ppm(), None);
+        block!(co2sensor.get_detection_range()).unwrap();
+        assert_eq!(co2sensor.detection_range_ppm(), Some(5000));
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
         assert_eq!(
-            block!(co2sensor.set_self_calibrate(true)),
-            Err(Error::UartError("No more data.".into()))
+            block!(co2sensor.read_co2_ppm()),
+            Err(Error::OutOfRange {
+                co2_ppm: 5000,
+                range_ppm: 5000
+            })
         );
     }
 
+    #[test]
+    fn test_read_co2_unknown_range() {
+        let uart = create_serial_mock_returning(&READ_CO2_5000_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(5000));
+    }
+
     #[test]
     fn test_into_inner_during_read() {
         let uart = SerialMock::new(vec![], vec![Err(nb::Error::WouldBlock)]);
@@ -569,6 +1802,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "calibration")]
     fn test_ignore_read_co2_result_by_polling_set_self_calibrate() {
         let mut read_data = Vec::with_capacity(10);
         read_data.push(Err(nb::Error::WouldBlock));
@@ -580,6 +1814,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "calibration")]
     fn test_read_co2_without_waiting_for_set_self_calibrate() {
         let mut write_return_values = vec![Ok(()); 2 * 9 + 1];
         write_return_values[0] = Err(nb::Error::WouldBlock);
@@ -595,14 +1830,420 @@ mod tests {
         assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
     }
 
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_set_self_calibrate_with_different_argument_while_pending() {
+        let mut write_return_values = vec![Ok(()); 2 * 9 + 1];
+        write_return_values[0] = Err(nb::Error::WouldBlock);
+        let uart = SerialMock::new(vec![], write_return_values);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(
+            co2sensor.set_self_calibrate(true),
+            Err(nb::Error::WouldBlock)
+        );
+        assert_eq!(
+            co2sensor.pending_command(),
+            Some(Command::SetSelfCalibrate(true))
+        );
+        assert_eq!(block!(co2sensor.set_self_calibrate(false)), Ok(()));
+        assert!(!co2sensor.is_busy());
+        let uart = co2sensor.into_inner();
+        assert_eq!(
+            &uart.write_buf[9..],
+            Frame::from(Command::SetSelfCalibrate(false)).as_ref()
+        );
+    }
+
+    #[test]
+    fn test_execute_into() {
+        let uart = create_serial_mock_returning(&READ_CO2_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        let mut response = [0u8; 9];
+        let frame = block!(co2sensor.execute_into(Command::ReadCo2, &mut response))
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.data(), &READ_CO2_RESPONSE[2..8]);
+        assert_eq!(response, READ_CO2_RESPONSE);
+        assert_eq!(co2sensor.into_inner().write_buf, READ_CO2.as_ref());
+    }
+
+    #[test]
+    fn test_execute_into_without_response() {
+        let uart = create_serial_mock_returning(&[]);
+        let mut co2sensor = MhZ19C::new(uart);
+        let mut response = [0u8; 9];
+        assert_eq!(
+            block!(co2sensor.execute_into(Command::CalibrateZero, &mut response)),
+            Ok(None)
+        );
+        assert_eq!(
+            co2sensor.into_inner().write_buf,
+            CALIBRATE_ZERO_COMMAND.as_ref()
+        );
+    }
+
+    #[test]
+    fn test_execute_into_checks_capabilities() {
+        let uart = create_serial_mock_returning(&FIRMWARE_0400_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        let mut response = [0u8; 9];
+        assert_eq!(
+            block!(co2sensor.execute_into(Command::ReadCo2AndTemperature, &mut response)),
+            Err(Error::NotSupportedByFirmware(FirmwareVersion::new(
+                *b"0400"
+            )))
+        );
+        assert_eq!(
+            co2sensor.into_inner().write_buf,
+            GET_FIRMWARE_VERSION.as_ref()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_dyn_firmware5_api() {
+        fn read<S: Firmware5Api<String>>(mut sensor: S) -> Result<u16, Error<String>> {
+            Ok(block!(sensor.read_co2_and_temp())?.co2_ppm)
+        }
+
+        let uart = SerialMock::new(
+            FIRMWARE_0515_RESPONSE
+                .iter()
+                .chain(READ_CO2_AND_TEMPERATURE_RESPONSE.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 18],
+        );
+        let mut co2sensor = MhZ19C::new(uart).into_v5().unwrap();
+        let sensor: &mut dyn Firmware5Api<String> = &mut co2sensor;
+        assert_eq!(read(sensor), Ok(800));
+    }
+
+    #[test]
+    fn test_read_ahead() {
+        let uart = SerialMock::new(
+            READ_CO2_RESPONSE
+                .iter()
+                .chain(READ_CO2_RESPONSE.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 2 * 9],
+        );
+        let mut co2sensor = MhZ19C::new(uart).with_read_ahead(true);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+        assert_eq!(co2sensor.pending_command(), Some(Command::ReadCo2));
+        assert_eq!(co2sensor.read_co2_ppm(), Ok(800));
+        let uart = co2sensor.into_inner();
+        assert_eq!(uart.write_buf.len(), 2 * 9);
+    }
+
+    #[test]
+    #[cfg(all(feature = "calibration", feature = "firmware5"))]
+    fn test_arbitrary_responses_do_not_panic() {
+        let mut seed: u32 = 1;
+        let mut next_byte = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as u8
+        };
+        for _ in 0..1000 {
+            let mut response: Vec<u8> = (0..9).map(|_| next_byte()).collect();
+            if next_byte() % 2 == 0 {
+                response[0] = 0xff;
+                let checksum = crate::frame::checksum(&response[1..8]);
+                response[8] = checksum;
+            }
+            let uart = SerialMock::new(response.iter().copied().map(Ok).collect(), vec![Ok(()); 9]);
+            let mut co2sensor = MhZ19C::new(uart);
+            let command = next_byte() % 5;
+            for _ in 0..20 {
+                let _ = match command {
+                    0 => co2sensor.read_co2_ppm().map(drop),
+                    1 => co2sensor.read_co2_and_temp().map(drop),
+                    2 => co2sensor.get_firmware_version().map(drop),
+                    3 => co2sensor.get_self_calibrate().map(drop),
+                    _ => co2sensor.get_detection_range().map(drop),
+                };
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_echo_suppression() {
+        let uart = SerialMock::new(
+            CALIBRATE_ZERO_COMMAND
+                .iter()
+                .chain(READ_CO2.as_ref().iter())
+                .chain(READ_CO2_RESPONSE.iter())
+                .chain(READ_CO2_RESPONSE.iter())
+                .chain(READ_CO2_RESPONSE.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 3 * 9],
+        );
+        let mut co2sensor = MhZ19C::new(uart).with_echo_suppression(true);
+        assert_eq!(block!(co2sensor.calibrate_zero()), Ok(()));
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Err(Error::EchoMismatch));
+    }
+
+    #[test]
+    fn test_error_codes_are_distinct() {
+        let errors: [Error<()>; 8] = [
+            Error::ValidateFrameError(ValidateFrameError::InvalidStartByte(0)),
+            Error::NotAResponse,
+            Error::OpCodeMismatch {
+                expected: 0x86,
+                got: 0x84,
+            },
+            Error::UartError(()),
+            Error::NotSupportedByFirmware(FirmwareVersion::new(*b"0400")),
+            Error::NotSupportedByDevice(Command::ReadCo2),
+            Error::OutOfRange {
+                co2_ppm: 5000,
+                range_ppm: 5000,
+            },
+            Error::EchoMismatch,
+        ];
+        let mut codes: Vec<u8> = errors.iter().map(Error::code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0));
+    }
+
+    #[test]
+    fn test_abort() {
+        let mut read_data: Vec<nb::Result<u8, String>> = Vec::with_capacity(20);
+        read_data.extend(READ_CO2_RESPONSE[..3].iter().copied().map(Ok));
+        read_data.push(Err(nb::Error::WouldBlock));
+        read_data.extend(READ_CO2_RESPONSE[3..].iter().copied().map(Ok));
+        read_data.push(Err(nb::Error::WouldBlock));
+        read_data.extend(READ_CO2_RESPONSE.iter().copied().map(Ok));
+        let uart = SerialMock::new(read_data, vec![Ok(()); 2 * 9]);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(co2sensor.read_co2_ppm(), Err(nb::Error::WouldBlock));
+        assert!(co2sensor.is_busy());
+        assert_eq!(co2sensor.pending_command(), Some(Command::ReadCo2));
+        assert_eq!(co2sensor.abort(), Ok(6));
+        assert!(!co2sensor.is_busy());
+        assert_eq!(co2sensor.pending_command(), None);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+    }
+
     #[test]
     fn test_get_firmware_version() {
         let uart = create_serial_mock_returning(&FIRMWARE_0515_RESPONSE);
         let mut co2sensor = MhZ19C::new(uart);
         let firmware = block!(co2sensor.get_firmware_version());
         let uart = co2sensor.into_inner();
-        assert_eq!(uart.write_buf, &*GET_FIRMWARE_VERSION.as_ref());
-        assert_eq!(firmware, Ok(*b"0515"));
+        assert_eq!(uart.write_buf, GET_FIRMWARE_VERSION.as_ref());
+        assert_eq!(firmware, Ok(FirmwareVersion::new(*b"0515")));
+    }
+
+    #[test]
+    fn test_detect() {
+        let uart = create_serial_mock_returning(&FIRMWARE_0515_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(
+            block!(co2sensor.detect()),
+            Ok(DetectedSensor::Firmware5 {
+                model: "MH-Z19C",
+                firmware_version: FirmwareVersion::new(*b"0515")
+            })
+        );
+
+        let uart = create_serial_mock_returning(&FIRMWARE_0400_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        let detected = block!(co2sensor.detect()).unwrap();
+        assert!(!detected.supports_firmware5_api());
+        assert_eq!(detected.firmware_version(), "0400");
+    }
+
+    #[test]
+    fn test_co2_correction() {
+        let reads = READ_CO2_RESPONSE
+            .iter()
+            .chain(READ_CO2_5000_RESPONSE.iter())
+            .copied()
+            .map(Ok)
+            .collect();
+        let mut co2sensor = MhZ19C::builder()
+            .detection_range_ppm(5000)
+            .build(SerialMock::new(reads, vec![Ok(()); 18]))
+            .with_co2_correction(Co2Correction::new(-20, 1.1));
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(860));
+        assert_eq!(
+            block!(co2sensor.read_co2_ppm()),
+            Err(Error::OutOfRange {
+                co2_ppm: 5000,
+                range_ppm: 5000
+            })
+        );
+    }
+
+    #[cfg(feature = "firmware5")]
+    #[test]
+    fn test_temp_offset() {
+        let reads = FIRMWARE_0515_RESPONSE
+            .iter()
+            .chain(READ_CO2_AND_TEMPERATURE_RESPONSE.iter())
+            .copied()
+            .map(Ok)
+            .collect();
+        let mut co2sensor = MhZ19C::builder()
+            .temp_offset_celsius(-2.5)
+            .build(SerialMock::new(reads, vec![Ok(()); 18]));
+        assert_eq!(co2sensor.temp_offset_celsius(), -2.5);
+        assert_eq!(
+            block!(co2sensor.read_co2_and_temp()),
+            Ok(Co2AndTemperature {
+                co2_ppm: 800,
+                temp_celsius: 21.5,
+                raw_temp_celsius: 24.
+            })
+        );
+    }
+
+    #[test]
+    fn test_lenient_validation() {
+        let mut invalid_checksum = READ_CO2_RESPONSE;
+        invalid_checksum[8] = 0x00;
+        let reads = core::iter::once(Err(nb::Error::WouldBlock))
+            .chain(invalid_checksum.iter().copied().map(Ok))
+            .chain([Ok(0x00), Ok(0x42)])
+            .chain(core::iter::once(Err(nb::Error::WouldBlock)))
+            .chain(READ_CO2_5000_RESPONSE.iter().copied().map(Ok))
+            .collect();
+        let mut co2sensor = MhZ19C::builder()
+            .validation(Validation::LENIENT)
+            .build(SerialMock::new(reads, vec![Ok(()); 18]));
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(5000));
+    }
+
+    #[test]
+    fn test_likely_baud_mismatch() {
+        // Bytes received by a 9600 baud UART from a sensor sending with
+        // 19200 baud.
+        let garbled = [0xf8, 0x00, 0x80, 0x78, 0x80, 0x00, 0x00, 0x00, 0x7e];
+        let reads = core::iter::repeat_n(garbled, 3)
+            .flatten()
+            .chain(READ_CO2_RESPONSE)
+            .map(Ok)
+            .collect();
+        let mut co2sensor = MhZ19C::new(SerialMock::new(reads, vec![Ok(()); 36]));
+        for invalid_frames in 1..=3 {
+            assert!(!co2sensor.likely_baud_mismatch());
+            assert!(matches!(
+                block!(co2sensor.read_co2_ppm()),
+                Err(Error::ValidateFrameError(_))
+            ));
+            assert_eq!(co2sensor.consecutive_invalid_frames(), invalid_frames);
+        }
+        assert!(co2sensor.likely_baud_mismatch());
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+        assert!(!co2sensor.likely_baud_mismatch());
+    }
+
+    #[cfg(feature = "firmware5")]
+    #[test]
+    fn test_read_co2_and_temp_any_firmware() {
+        use crate::sim::Simulator;
+
+        let mut simulator = Simulator::new();
+        simulator.set_temp_celsius(23.5);
+        let mut co2sensor = MhZ19C::new(simulator);
+        assert_eq!(
+            block!(co2sensor.read_co2_and_temp_any_firmware()),
+            Ok((
+                Co2AndTemperature::new(400, 23.5),
+                TemperatureEncoding::FixedPoint
+            ))
+        );
+
+        let mut simulator = Simulator::new().with_firmware_version(FirmwareVersion::new(*b"0430"));
+        simulator.set_temp_celsius(23.5);
+        let mut co2sensor = MhZ19C::new(simulator).with_temp_offset_celsius(-2.0);
+        assert_eq!(
+            block!(co2sensor.read_co2_and_temp_any_firmware()),
+            Ok((
+                Co2AndTemperature {
+                    co2_ppm: 400,
+                    temp_celsius: 21.0,
+                    raw_temp_celsius: 23.0
+                },
+                TemperatureEncoding::OffsetByte
+            ))
+        );
+    }
+
+    #[test]
+    fn test_self_test() {
+        let reads = core::iter::once(Err(nb::Error::WouldBlock))
+            .chain(FIRMWARE_0515_RESPONSE.iter().copied().map(Ok))
+            .chain(core::iter::once(Err(nb::Error::WouldBlock)))
+            .chain(READ_CO2_RESPONSE.iter().copied().map(Ok))
+            .collect();
+        let mut co2sensor = MhZ19C::new(SerialMock::new(reads, vec![Ok(()); 18]));
+        let report = block!(co2sensor.self_test()).unwrap();
+        assert_eq!(
+            report,
+            SelfTestReport {
+                firmware_version: FirmwareVersion::new(*b"0515"),
+                co2_ppm: 800,
+                detection_range_ppm: None,
+            }
+        );
+        assert!(report.passed());
+        assert_eq!(co2sensor.into_inner().write_buf.len(), 18);
+    }
+
+    #[test]
+    fn test_self_test_with_read_ahead() {
+        let reads = READ_CO2_RESPONSE
+            .iter()
+            .chain(READ_CO2_RESPONSE.iter())
+            .chain(FIRMWARE_0515_RESPONSE.iter())
+            .copied()
+            .map(Ok)
+            .chain(core::iter::once(Err(nb::Error::WouldBlock)))
+            .chain(READ_CO2_5000_RESPONSE.iter().copied().map(Ok))
+            .collect();
+        let mut co2sensor = MhZ19C::builder()
+            .firmware_version(FirmwareVersion::new(*b"0515"))
+            .read_ahead(true)
+            .build(SerialMock::new(reads, vec![Ok(()); 4 * 9]));
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+        assert_eq!(co2sensor.pending_command(), Some(Command::ReadCo2));
+
+        let report = block!(co2sensor.self_test()).unwrap();
+        assert_eq!(report.firmware_version, FirmwareVersion::new(*b"0515"));
+        assert_eq!(report.co2_ppm, 5000);
+        let uart = co2sensor.into_inner();
+        assert_eq!(&uart.write_buf[2 * 9..3 * 9], GET_FIRMWARE_VERSION.as_ref());
+        assert_eq!(&uart.write_buf[3 * 9..], READ_CO2.as_ref());
+    }
+
+    #[test]
+    fn test_self_test_reports_implausible_reading() {
+        let reads = FIRMWARE_0515_RESPONSE
+            .iter()
+            .chain(READ_CO2_5000_RESPONSE.iter())
+            .copied()
+            .map(Ok)
+            .collect();
+        let mut co2sensor = MhZ19C::builder()
+            .detection_range_ppm(5000)
+            .build(SerialMock::new(reads, vec![Ok(()); 18]));
+        let report = block!(co2sensor.self_test()).unwrap();
+        assert_eq!(report.co2_ppm, 5000);
+        assert!(report.firmware_version_plausible());
+        assert!(!report.co2_plausible());
+        assert!(!report.passed());
     }
 
     #[test]
@@ -619,23 +2260,151 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "firmware5")]
     fn test_upgrade_to_v5() {
         let uart = create_serial_mock_returning(&FIRMWARE_0515_RESPONSE);
         let mut co2sensor = MhZ19C::new(uart);
-        block!(co2sensor.upgrade_to_v5()).unwrap();
+        let mut co2sensor = block!(co2sensor.upgrade_to_v5()).unwrap();
+        assert_eq!(
+            co2sensor.get_ref().firmware_version(),
+            Some(FirmwareVersion::new(*b"0515"))
+        );
+        assert!(!co2sensor.get_mut().is_busy());
+    }
+
+    #[test]
+    fn test_replace_uart() {
+        let uart = SerialMock::new(vec![], vec![Ok(()); 9]);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(co2sensor.read_co2_ppm(), Err(nb::Error::WouldBlock));
+        let old_uart = co2sensor.replace_uart(create_serial_mock_returning(&READ_CO2_RESPONSE));
+        assert_eq!(old_uart.write_buf.len(), 9);
+        assert!(!co2sensor.is_busy());
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+    }
+
+    #[test]
+    fn test_uart_mut() {
+        let uart = create_serial_mock_returning(&READ_CO2_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+        assert_eq!(co2sensor.uart().write_buf.len(), 9);
+        co2sensor.uart_mut().write_buf.clear();
+        assert!(co2sensor.into_inner().write_buf.is_empty());
     }
 
     #[test]
+    #[cfg(feature = "firmware5")]
     fn test_upgrade_to_v5_error() {
         let uart = create_serial_mock_returning(&FIRMWARE_0400_RESPONSE);
         let mut co2sensor = MhZ19C::new(uart);
         assert_eq!(
             block!(co2sensor.upgrade_to_v5()).err(),
-            Some(Error::NotSupportedByFirmware(*b"0400"))
+            Some(Error::NotSupportedByFirmware(FirmwareVersion::new(
+                *b"0400"
+            )))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_into_v5() {
+        let uart = SerialMock::new(
+            FIRMWARE_0515_RESPONSE
+                .iter()
+                .chain(READ_CO2_AND_TEMPERATURE_RESPONSE.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 18],
+        );
+        let mut co2sensor = MhZ19C::new(uart);
+        let mut co2sensor = loop {
+            match co2sensor.into_v5() {
+                Ok(co2sensor) => break co2sensor,
+                Err((sensor, nb::Error::WouldBlock)) => co2sensor = sensor,
+                Err((_, nb::Error::Other(err))) => panic!("{:?}", err),
+            }
+        };
+        assert_eq!(
+            block!(co2sensor.read_co2_and_temp()),
+            Ok(Co2AndTemperature::new(800, 24.))
+        );
+        assert_eq!(co2sensor.get_mut().uart_mut().write_buf.len(), 18);
+        assert!(!co2sensor.get_ref().is_busy());
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_into_v5_error_returns_driver() {
+        let uart = create_serial_mock_returning(&FIRMWARE_0400_RESPONSE);
+        let co2sensor = MhZ19C::new(uart);
+        let (co2sensor, err) = co2sensor.into_v5().unwrap_err();
+        assert_eq!(
+            err,
+            nb::Error::Other(Error::NotSupportedByFirmware(FirmwareVersion::new(
+                *b"0400"
+            )))
+        );
+        assert_eq!(
+            co2sensor.into_inner().write_buf,
+            GET_FIRMWARE_VERSION.as_ref()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_caches_firmware_version() {
+        let uart = create_serial_mock_returning(&FIRMWARE_0515_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(co2sensor.firmware_version(), None);
+        block!(co2sensor.init()).unwrap();
+        assert_eq!(
+            co2sensor.firmware_version(),
+            Some(FirmwareVersion::new(*b"0515"))
+        );
+        assert!(co2sensor
+            .capabilities()
+            .unwrap()
+            .supports(Command::ReadCo2AndTemperature));
+        block!(co2sensor.init()).unwrap();
+        block!(co2sensor.upgrade_to_v5()).unwrap();
+        assert_eq!(
+            co2sensor.into_inner().write_buf,
+            GET_FIRMWARE_VERSION.as_ref()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_read_co2_and_temperature_without_upgrade() {
+        let uart = SerialMock::new(
+            FIRMWARE_0515_RESPONSE
+                .iter()
+                .chain(READ_CO2_AND_TEMPERATURE_RESPONSE.iter())
+                .copied()
+                .map(Ok)
+                .collect(),
+            vec![Ok(()); 18],
+        );
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(
+            block!(co2sensor.read_co2_and_temp()),
+            Ok(Co2AndTemperature::new(800, 24.))
+        );
+
+        let uart = create_serial_mock_returning(&FIRMWARE_0400_RESPONSE);
+        let mut co2sensor = MhZ19C::new(uart);
+        assert_eq!(
+            block!(co2sensor.read_co2_and_temp()),
+            Err(Error::NotSupportedByFirmware(FirmwareVersion::new(
+                *b"0400"
+            )))
         );
     }
 
     #[test]
+    #[cfg(feature = "firmware5")]
     fn test_read_co2_and_temperature() {
         let mut responses: Vec<nb::Result<u8, String>> = FIRMWARE_0515_RESPONSE
             .iter()
@@ -650,10 +2419,7 @@ mod tests {
 
         assert_eq!(
             block!(co2sensor.read_co2_and_temp()),
-            Ok(Co2AndTemperature {
-                co2_ppm: 800,
-                temp_celsius: 24.
-            })
+            Ok(Co2AndTemperature::new(800, 24.))
         );
         assert_eq!(
             block!(co2sensor.read_co2_and_temp()),
@@ -1,5 +1,7 @@
 //! Non-blocking communication state machines for the MH-Z19C UART interface.
 
+#[cfg(feature = "display")]
+use core::fmt::{self, Display, Formatter};
 use embedded_hal::serial::{Read, Write};
 
 /// Trait for a future or state machine used for non-blocking communication.
@@ -8,302 +10,381 @@ use embedded_hal::serial::{Read, Write};
 /// futures). To cancel progress, just stop polling. However, this might leave
 /// you within an undefined state of the UART communication protocol.
 ///
-/// The future must not be polled after it has returned an
-/// [`core::result::Result::Ok`] result.
+/// The UART interface is not owned by the future, but passed on each poll.
+/// Polling the future after it has returned an [`core::result::Result::Ok`]
+/// result has no effect.
 ///
-/// To retrieve the result use [`into_return_value`]. If called before
-/// completing the future this can be used to retrieve owned values when
-/// intending to cancel the future.
+/// To retrieve the buffers use the `into_inner` method of the future.
 ///
-/// * `R`: return value type, wrap anything that is not available before the
-///   completion of the future into an [`core::option::Option`].
+/// * `U`: UART interface type
 /// * `E`: error type
-pub trait NbFuture<R, E> {
+pub trait NbFuture<U, E> {
     /// Poll the future and advance execution if possible.
-    fn poll(&mut self) -> nb::Result<(), E>;
-    /// Turn the future into the owned variable captured by the future and other
-    /// return values.
-    fn into_return_value(self) -> R;
+    fn poll(&mut self, uart: &mut U) -> nb::Result<(), E>;
 }
 
+/// Error returned when creating a future with buffers unsuitable for the
+/// requested operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferError {
+    /// The buffer to write is empty.
+    EmptyWriteBuffer,
+    /// The buffer to read into is smaller than the number of bytes to read.
+    ReadBufferTooSmall { required: usize, available: usize },
+}
+
+#[cfg(feature = "display")]
+impl Display for BufferError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use BufferError::*;
+        match self {
+            EmptyWriteBuffer => write!(f, "empty write buffer"),
+            ReadBufferTooSmall {
+                required,
+                available,
+            } => write!(
+                f,
+                "read buffer too small (required {required} bytes, but got {available})"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferError {}
+
 /// Write all bytes within a buffer.
 ///
-/// * `'a`: buffer lifetime
-/// * `W`: Concrete [`embedded_hal::serial::Write`] type with error type `E`
 /// * `B`: Type of buffer
 #[derive(Debug)]
-pub struct WriteAll<W, E, B>
+pub struct WriteAll<B>
 where
-    W: Write<u8, Error = E>,
     B: AsRef<[u8]>,
 {
-    uart: W,
     buf: B,
     bytes_written: usize,
 }
 
-impl<W, E, B> WriteAll<W, E, B>
+impl<B> WriteAll<B>
 where
-    W: Write<u8, Error = E>,
     B: AsRef<[u8]>,
 {
-    /// Create future to write all bytes in `buf` to `uart`.
+    /// Create future to write all bytes in `buf`.
     ///
-    /// The ownership of the `uart` will be returned from [`Self::poll`] on
-    /// completion.
-    pub fn new(uart: W, buf: B) -> Self {
+    /// An empty `buf` completes immediately without writing anything.
+    pub fn new(buf: B) -> Self {
         Self {
-            uart,
             buf,
             bytes_written: 0,
         }
     }
+
+    /// Create future to write all bytes in `buf`, returning an error if `buf`
+    /// is empty.
+    pub fn try_new(buf: B) -> Result<Self, BufferError> {
+        if buf.as_ref().is_empty() {
+            return Err(BufferError::EmptyWriteBuffer);
+        }
+        Ok(Self::new(buf))
+    }
 }
 
-impl<W, E, B> NbFuture<W, E> for WriteAll<W, E, B>
+impl<W, E, B> NbFuture<W, E> for WriteAll<B>
 where
     W: Write<u8, Error = E>,
     B: AsRef<[u8]>,
 {
-    fn poll(&mut self) -> nb::Result<(), E> {
-        loop {
-            let c = self.buf.as_ref()[self.bytes_written];
-            match self.uart.write(c) {
-                Ok(()) => {
-                    self.bytes_written += 1;
-                    if self.bytes_written >= self.buf.as_ref().len() {
-                        return Ok(());
-                    }
-                }
-                Err(err) => return Err(err),
-            }
+    fn poll(&mut self, uart: &mut W) -> nb::Result<(), E> {
+        while let Some(&c) = self.buf.as_ref().get(self.bytes_written) {
+            uart.write(c)?;
+            self.bytes_written += 1;
         }
-    }
-
-    fn into_return_value(self) -> W {
-        self.uart
+        Ok(())
     }
 }
 
 /// Read multiple bytes.
 ///
-/// * `R`: Concrete [`embedded_hal::serial::Read`] type with error type `E`
 /// * `B`: Type of buffer to write to
 #[derive(Debug)]
-pub struct ReadMultiple<R, E, B>
+pub struct ReadMultiple<B>
 where
-    R: Read<u8, Error = E>,
     B: AsMut<[u8]>,
 {
-    uart: R,
     buf: B,
     bytes_read: usize,
     read_len: usize,
 }
 
-impl<R, E, B> ReadMultiple<R, E, B>
+impl<B> ReadMultiple<B>
 where
-    R: Read<u8, Error = E>,
     B: AsMut<[u8]>,
 {
-    /// Create future to read `read_len` bytes from `uart`.
+    /// Create future to read `read_len` bytes into `buf`.
     ///
-    /// The ownership of the `uart` will be returned together with the bytes
-    /// read from [`Self::poll`] on completion.
-    pub fn new(uart: R, buf: B, read_len: usize) -> Self {
+    /// The `read_len` is limited to the length of `buf`.
+    pub fn new(mut buf: B, read_len: usize) -> Self {
+        let read_len = read_len.min(buf.as_mut().len());
         Self {
-            uart,
             buf,
             bytes_read: 0,
             read_len,
         }
     }
+
+    /// Create future to read `read_len` bytes into `buf`, returning an error
+    /// if `buf` is too small to hold `read_len` bytes.
+    pub fn try_new(mut buf: B, read_len: usize) -> Result<Self, BufferError> {
+        check_read_len(buf.as_mut(), read_len)?;
+        Ok(Self::new(buf, read_len))
+    }
+
+    /// Returns the owned buffer.
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
 }
 
-impl<R, E, B> NbFuture<(R, B), E> for ReadMultiple<R, E, B>
+impl<R, E, B> NbFuture<R, E> for ReadMultiple<B>
 where
     R: Read<u8, Error = E>,
     B: AsMut<[u8]>,
 {
-    fn poll(&mut self) -> nb::Result<(), E> {
-        loop {
-            match self.uart.read() {
-                Ok(c) => {
-                    self.buf.as_mut()[self.bytes_read] = c;
-                    self.bytes_read += 1;
-                    if self.bytes_read >= self.read_len {
-                        return Ok(());
-                    }
-                }
-                Err(err) => return Err(err),
-            }
+    fn poll(&mut self, uart: &mut R) -> nb::Result<(), E> {
+        read_into(uart, self.buf.as_mut(), &mut self.bytes_read, self.read_len)
+    }
+}
+
+fn check_read_len(buf: &[u8], read_len: usize) -> Result<(), BufferError> {
+    if buf.len() < read_len {
+        return Err(BufferError::ReadBufferTooSmall {
+            required: read_len,
+            available: buf.len(),
+        });
+    }
+    Ok(())
+}
+
+fn read_into<R, E>(
+    uart: &mut R,
+    buf: &mut [u8],
+    bytes_read: &mut usize,
+    read_len: usize,
+) -> nb::Result<(), E>
+where
+    R: Read<u8, Error = E>,
+{
+    while *bytes_read < read_len {
+        match buf.get_mut(*bytes_read) {
+            Some(slot) => *slot = uart.read()?,
+            None => break,
+        }
+        *bytes_read += 1;
+    }
+    Ok(())
+}
+
+/// Reads back the echo of written bytes, e.g. on half-duplex or loopback
+/// wiring, and checks that it matches the written bytes.
+#[derive(Debug)]
+struct Echo {
+    enabled: bool,
+    bytes_read: usize,
+    matches: bool,
+}
+
+impl Echo {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            bytes_read: 0,
+            matches: true,
         }
     }
 
-    fn into_return_value(self) -> (R, B) {
-        (self.uart, self.buf)
+    fn poll<R, E>(&mut self, uart: &mut R, written: &[u8]) -> nb::Result<(), E>
+    where
+        R: Read<u8, Error = E>,
+    {
+        if self.enabled {
+            while let Some(&expected) = written.get(self.bytes_read) {
+                let c = uart.read()?;
+                self.matches &= c == expected;
+                self.bytes_read += 1;
+            }
+        }
+        Ok(())
     }
 }
 
 /// Write all bytes within a buffer and read a fixed length response afterwards.
 ///
-/// * `'a`: buffer lifetime
-/// * `U`: Concrete [`embedded_hal::serial::Read`] and
-///   [`embedded_hal::serial::Write`] type with error type `E`
+/// Optionally, an echo of the written bytes is read and checked before the
+/// response.
+///
 /// * `BWrite`: Type of buffer to write
 /// * `BRead`: Type of buffer to read into
 #[derive(Debug)]
-pub struct WriteAndReadResponse<U, E, BWrite, BRead>
+pub struct WriteAndReadResponse<BWrite, BRead>
 where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
     BWrite: AsRef<[u8]>,
     BRead: AsMut<[u8]>,
 {
-    state: Option<WriteAndReadResponseState<U, E, BWrite, BRead>>,
+    write: WriteAll<BWrite>,
+    flushed: bool,
+    echo: Echo,
+    read: ReadMultiple<BRead>,
 }
 
-#[derive(Debug)]
-enum WriteAndReadResponseState<U, E, BWrite, BRead>
+impl<BWrite, BRead> WriteAndReadResponse<BWrite, BRead>
 where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
     BWrite: AsRef<[u8]>,
     BRead: AsMut<[u8]>,
 {
-    Write {
-        future: WriteAll<U, E, BWrite>,
-        read_buf: BRead,
-        response_len: usize,
-    },
-    Flush {
-        uart: U,
+    /// Create future to write `write_buf` bytes and read `response_len` bytes
+    /// into `read_buf` afterwards.
+    ///
+    /// The `response_len` is limited to the length of `read_buf`.
+    pub fn new(write_buf: BWrite, read_buf: BRead, response_len: usize) -> Self {
+        Self {
+            write: WriteAll::new(write_buf),
+            flushed: false,
+            echo: Echo::new(false),
+            read: ReadMultiple::new(read_buf, response_len),
+        }
+    }
+
+    /// Create future to write `write_buf` bytes and read `response_len` bytes
+    /// into `read_buf` afterwards, returning an error if `write_buf` is empty
+    /// or `read_buf` is too small to hold `response_len` bytes.
+    pub fn try_new(
+        write_buf: BWrite,
         read_buf: BRead,
         response_len: usize,
-    },
-    Read {
-        future: ReadMultiple<U, E, BRead>,
-    },
-    Completed {
-        uart: U,
-        read_buf: BRead,
-    },
+    ) -> Result<Self, BufferError> {
+        Ok(Self {
+            write: WriteAll::try_new(write_buf)?,
+            flushed: false,
+            echo: Echo::new(false),
+            read: ReadMultiple::try_new(read_buf, response_len)?,
+        })
+    }
+
+    /// Enables or disables reading the echo of the written bytes before the
+    /// response.
+    pub fn with_echo(mut self, enabled: bool) -> Self {
+        self.echo = Echo::new(enabled);
+        self
+    }
+
+    /// Returns `false` if the echo did not match the written bytes.
+    pub fn echo_matches(&self) -> bool {
+        self.echo.matches
+    }
+
+    /// Returns the owned read buffer.
+    pub fn into_inner(self) -> BRead {
+        self.read.into_inner()
+    }
 }
 
-impl<U, E, BWrite, BRead> WriteAndReadResponseState<U, E, BWrite, BRead>
+impl<U, E, BWrite, BRead> NbFuture<U, E> for WriteAndReadResponse<BWrite, BRead>
 where
     U: Read<u8, Error = E> + Write<u8, Error = E>,
     BWrite: AsRef<[u8]>,
     BRead: AsMut<[u8]>,
 {
-    fn advance(self) -> Result<Self, (Self, nb::Error<E>)> {
-        use WriteAndReadResponseState::*;
-        match self {
-            Write {
-                mut future,
-                read_buf,
-                response_len,
-            } => match future.poll() {
-                Ok(()) => Ok(Flush {
-                    uart: future.into_return_value(),
-                    read_buf,
-                    response_len,
-                }),
-                Err(err) => Err((
-                    Write {
-                        future,
-                        read_buf,
-                        response_len,
-                    },
-                    err,
-                )),
-            },
-            Flush {
-                mut uart,
-                read_buf,
-                response_len,
-            } => match uart.flush() {
-                Ok(()) => Ok(Read {
-                    future: ReadMultiple::new(uart, read_buf, response_len),
-                }),
-                Err(err) => Err((
-                    Flush {
-                        uart,
-                        read_buf,
-                        response_len,
-                    },
-                    err,
-                )),
-            },
-            Read { mut future } => match future.poll() {
-                Ok(()) => {
-                    let (uart, read_buf) = future.into_return_value();
-                    Ok(Completed { uart, read_buf })
-                }
-                Err(err) => Err((Read { future }, err)),
-            },
-            other => Ok(other),
+    fn poll(&mut self, uart: &mut U) -> nb::Result<(), E> {
+        if !self.flushed {
+            self.write.poll(uart)?;
+            uart.flush()?;
+            self.flushed = true;
         }
+        self.echo.poll(uart, self.write.buf.as_ref())?;
+        self.read.poll(uart)
     }
+}
 
-    fn cancel(self) -> (U, BRead) {
-        use WriteAndReadResponseState::*;
-        match self {
-            Write {
-                future, read_buf, ..
-            } => (future.into_return_value(), read_buf),
-            Flush { uart, read_buf, .. } => (uart, read_buf),
-            Read { future } => future.into_return_value(),
-            Completed { uart, read_buf } => (uart, read_buf),
-        }
-    }
+/// Write all bytes within a buffer and read a fixed length response afterwards
+/// into a buffer provided on each poll.
+///
+/// In contrast to [`WriteAndReadResponse`] the future does not own the read
+/// buffer, which allows reading directly into a buffer of the caller.
+///
+/// * `BWrite`: Type of buffer to write
+#[derive(Debug)]
+pub struct WriteAndReadInto<BWrite>
+where
+    BWrite: AsRef<[u8]>,
+{
+    write: WriteAll<BWrite>,
+    flushed: bool,
+    echo: Echo,
+    bytes_read: usize,
+    response_len: usize,
 }
 
-impl<U, E, BWrite, BRead> WriteAndReadResponse<U, E, BWrite, BRead>
+impl<BWrite> WriteAndReadInto<BWrite>
 where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
     BWrite: AsRef<[u8]>,
-    BRead: AsMut<[u8]>,
 {
-    /// Create future to write `buf` bytes to `uart` and read `read_len` bytes
+    /// Create future to write `write_buf` bytes and read `response_len` bytes
     /// afterwards.
-    ///
-    /// The ownership of the `uart` will be returned together with the bytes
-    /// read from [`Self::poll`] on completion.
-    pub fn new(uart: U, write_buf: BWrite, read_buf: BRead, response_len: usize) -> Self {
+    pub fn new(write_buf: BWrite, response_len: usize) -> Self {
         Self {
-            state: Some(WriteAndReadResponseState::Write {
-                future: WriteAll::new(uart, write_buf),
-                read_buf,
-                response_len,
-            }),
+            write: WriteAll::new(write_buf),
+            flushed: false,
+            echo: Echo::new(false),
+            bytes_read: 0,
+            response_len,
         }
     }
-}
 
-impl<U, E, BWrite, BRead> NbFuture<(U, BRead), E> for WriteAndReadResponse<U, E, BWrite, BRead>
-where
-    U: Read<u8, Error = E> + Write<u8, Error = E>,
-    BWrite: AsRef<[u8]>,
-    BRead: AsMut<[u8]>,
-{
-    fn poll(&mut self) -> nb::Result<(), E> {
-        loop {
-            let (result, new_state) = match self.state.take().unwrap().advance() {
-                Ok(new_state) => (Ok(()), new_state),
-                Err((new_state, err)) => (Err(err), new_state),
-            };
+    /// Create future to write `write_buf` bytes and read `response_len` bytes
+    /// afterwards, returning an error if `write_buf` is empty.
+    ///
+    /// The read buffer is only known when polling, use
+    /// [`Self::check_read_buf`] to validate it up front.
+    pub fn try_new(write_buf: BWrite, response_len: usize) -> Result<Self, BufferError> {
+        if write_buf.as_ref().is_empty() {
+            return Err(BufferError::EmptyWriteBuffer);
+        }
+        Ok(Self::new(write_buf, response_len))
+    }
 
-            self.state = Some(new_state);
+    /// Returns an error if `read_buf` is too small to hold the response.
+    pub fn check_read_buf(&self, read_buf: &[u8]) -> Result<(), BufferError> {
+        check_read_len(read_buf, self.response_len)
+    }
 
-            if let Some(WriteAndReadResponseState::Completed { .. }) = self.state {
-                return Ok(());
-            }
+    /// Enables or disables reading the echo of the written bytes before the
+    /// response.
+    pub fn with_echo(mut self, enabled: bool) -> Self {
+        self.echo = Echo::new(enabled);
+        self
+    }
 
-            result?;
-        }
+    /// Returns `false` if the echo did not match the written bytes.
+    pub fn echo_matches(&self) -> bool {
+        self.echo.matches
     }
 
-    fn into_return_value(self) -> (U, BRead) {
-        self.state.unwrap().cancel()
+    /// Poll the future and advance execution if possible.
+    ///
+    /// Received bytes are stored in `read_buf`, reading at most as many
+    /// bytes as fit into it. The same buffer has to be passed on each poll to
+    /// obtain the complete response.
+    pub fn poll_into<U, E>(&mut self, uart: &mut U, read_buf: &mut [u8]) -> nb::Result<(), E>
+    where
+        U: Read<u8, Error = E> + Write<u8, Error = E>,
+    {
+        if !self.flushed {
+            self.write.poll(uart)?;
+            uart.flush()?;
+            self.flushed = true;
+        }
+        self.echo.poll(uart, self.write.buf.as_ref())?;
+        read_into(uart, read_buf, &mut self.bytes_read, self.response_len)
     }
 }
 
@@ -317,7 +398,7 @@ mod tests {
 
     #[test]
     fn test_write_all() -> Result<(), String> {
-        let write_mock = SerialMock::new(
+        let mut write_mock = SerialMock::new(
             vec![],
             vec![
                 Ok(()),
@@ -327,96 +408,199 @@ mod tests {
                 Ok(()),
             ],
         );
-        let buf = ['f' as u8, 'o' as u8, 'o' as u8];
+        let buf = [b'f', b'o', b'o'];
 
-        let mut future = WriteAll::new(write_mock, &buf);
-        block!(future.poll())?;
-        let write_mock = future.into_return_value();
+        let mut future = WriteAll::new(&buf);
+        block!(future.poll(&mut write_mock))?;
         assert_eq!(write_mock.write_buf, buf);
         Ok(())
     }
 
     #[test]
     fn test_write_all_error_propagation() {
-        let write_mock =
+        let mut write_mock =
             SerialMock::new(vec![], vec![Err(nb::Error::Other("expected error".into()))]);
         let buf = [0u8; 1];
 
-        let mut future = WriteAll::new(write_mock, &buf);
+        let mut future = WriteAll::new(&buf);
         assert_eq!(
-            block!(future.poll()).unwrap_err(),
+            block!(future.poll(&mut write_mock)).unwrap_err(),
             String::from("expected error")
         );
     }
 
     #[test]
     fn test_read_multiple() -> Result<(), String> {
-        let read_mock = SerialMock::new(
+        let mut read_mock = SerialMock::new(
             vec![
-                Ok('f' as u8),
-                Ok('o' as u8),
+                Ok(b'f'),
+                Ok(b'o'),
                 Err(nb::Error::WouldBlock),
                 Err(nb::Error::WouldBlock),
-                Ok('o' as u8),
+                Ok(b'o'),
             ],
             vec![],
         );
         let buf = [0u8; 3];
 
-        let mut future = ReadMultiple::new(read_mock, buf, 3);
-        block!(future.poll())?;
-        let (_, buf) = future.into_return_value();
-        assert_eq!(buf, ['f' as u8, 'o' as u8, 'o' as u8]);
+        let mut future = ReadMultiple::new(buf, 3);
+        block!(future.poll(&mut read_mock))?;
+        let buf = future.into_inner();
+        assert_eq!(buf, [b'f', b'o', b'o']);
         Ok(())
     }
 
     #[test]
     fn test_read_multiple_error_propagation() {
-        let read_mock =
+        let mut read_mock =
             SerialMock::new(vec![Err(nb::Error::Other("expected error".into()))], vec![]);
         let buf = [0u8; 1];
 
-        let mut future = ReadMultiple::new(read_mock, buf, 1);
+        let mut future = ReadMultiple::new(buf, 1);
         assert_eq!(
-            block!(future.poll()).unwrap_err(),
+            block!(future.poll(&mut read_mock)).unwrap_err(),
             String::from("expected error")
         );
     }
 
     #[test]
     fn test_write_and_read_response() -> Result<(), String> {
-        let serial_mock = SerialMock::new(
-            vec![
-                Ok('o' as u8),
-                Err(nb::Error::WouldBlock),
-                Ok('u' as u8),
-                Ok('t' as u8),
-            ],
+        let mut serial_mock = SerialMock::new(
+            vec![Ok(b'o'), Err(nb::Error::WouldBlock), Ok(b'u'), Ok(b't')],
             vec![Ok(()), Err(nb::Error::WouldBlock), Ok(())],
         );
-        let write_buf = ['i' as u8, 'n' as u8];
+        let write_buf = [b'i', b'n'];
         let read_buf = [0u8; 3];
 
-        let mut future = WriteAndReadResponse::new(serial_mock, &write_buf, read_buf, 3);
-        block!(future.poll())?;
-        let (serial_mock, read_buf) = future.into_return_value();
+        let mut future = WriteAndReadResponse::new(&write_buf, read_buf, 3);
+        block!(future.poll(&mut serial_mock))?;
+        let read_buf = future.into_inner();
         assert_eq!(serial_mock.write_buf, write_buf);
-        assert_eq!(read_buf, ['o' as u8, 'u' as u8, 't' as u8]);
+        assert_eq!(read_buf, [b'o', b'u', b't']);
         assert_eq!(serial_mock.flushed_up_to, 2);
         Ok(())
     }
 
     #[test]
     fn test_write_and_read_response_error_propagation() {
-        let serial_mock =
+        let mut serial_mock =
             SerialMock::new(vec![], vec![Err(nb::Error::Other("expected error".into()))]);
-        let write_buf = ['i' as u8, 'n' as u8];
+        let write_buf = [b'i', b'n'];
         let read_buf = [0u8; 3];
 
-        let mut future = WriteAndReadResponse::new(serial_mock, &write_buf, read_buf, 3);
+        let mut future = WriteAndReadResponse::new(&write_buf, read_buf, 3);
         assert_eq!(
-            block!(future.poll()).unwrap_err(),
+            block!(future.poll(&mut serial_mock)).unwrap_err(),
             String::from("expected error")
         );
     }
+
+    #[test]
+    fn test_write_and_read_into() -> Result<(), String> {
+        let mut serial_mock = SerialMock::new(
+            vec![Ok(b'o'), Err(nb::Error::WouldBlock), Ok(b'u'), Ok(b't')],
+            vec![Ok(()), Err(nb::Error::WouldBlock), Ok(())],
+        );
+        let write_buf = [b'i', b'n'];
+        let mut read_buf = [0u8; 3];
+
+        let mut future = WriteAndReadInto::new(&write_buf, 3);
+        block!(future.poll_into(&mut serial_mock, &mut read_buf))?;
+        assert_eq!(serial_mock.write_buf, write_buf);
+        assert_eq!(read_buf, [b'o', b'u', b't']);
+        assert_eq!(serial_mock.flushed_up_to, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_and_read_response_with_echo() -> Result<(), String> {
+        let mut serial_mock = SerialMock::new(
+            vec![Ok(b'i'), Err(nb::Error::WouldBlock), Ok(b'n'), Ok(b'o')],
+            vec![Ok(()), Ok(())],
+        );
+        let write_buf = [b'i', b'n'];
+        let read_buf = [0u8; 1];
+
+        let mut future = WriteAndReadResponse::new(&write_buf, read_buf, 1).with_echo(true);
+        block!(future.poll(&mut serial_mock))?;
+        assert!(future.echo_matches());
+        assert_eq!(future.into_inner(), [b'o']);
+
+        let mut serial_mock = SerialMock::new(vec![Ok(b'x'), Ok(b'n')], vec![Ok(()), Ok(())]);
+        let mut future = WriteAndReadInto::new(&write_buf, 0).with_echo(true);
+        block!(future.poll_into(&mut serial_mock, &mut []))?;
+        assert!(!future.echo_matches());
+        Ok(())
+    }
+
+    #[test]
+    fn test_polling_completed_future_has_no_effect() -> Result<(), String> {
+        let mut serial_mock = SerialMock::new(vec![Ok(b'o')], vec![Ok(())]);
+        let write_buf = [b'i'];
+        let read_buf = [0u8; 1];
+
+        let mut future = WriteAndReadResponse::new(&write_buf, read_buf, 1);
+        block!(future.poll(&mut serial_mock))?;
+        block!(future.poll(&mut serial_mock))?;
+        assert_eq!(serial_mock.write_buf, write_buf);
+        assert_eq!(future.into_inner(), [b'o']);
+        Ok(())
+    }
+
+    #[test]
+    fn test_degenerate_buffers_do_not_panic() -> Result<(), String> {
+        let mut serial_mock = SerialMock::new(vec![Ok(b'o'), Ok(b'u')], vec![]);
+        let mut future = WriteAll::new([]);
+        block!(future.poll(&mut serial_mock))?;
+
+        let mut future = ReadMultiple::new([0u8; 1], 2);
+        block!(future.poll(&mut serial_mock))?;
+        assert_eq!(future.into_inner(), [b'o']);
+        Ok(())
+    }
+
+    #[test]
+    fn test_degenerate_buffers_are_reported() {
+        let empty: [u8; 0] = [];
+        assert_eq!(
+            WriteAll::try_new(empty).err(),
+            Some(BufferError::EmptyWriteBuffer)
+        );
+        assert!(WriteAll::try_new([0u8; 1]).is_ok());
+
+        assert_eq!(
+            ReadMultiple::try_new([0u8; 1], 2).err(),
+            Some(BufferError::ReadBufferTooSmall {
+                required: 2,
+                available: 1
+            })
+        );
+        assert!(ReadMultiple::try_new([0u8; 2], 2).is_ok());
+
+        assert_eq!(
+            WriteAndReadResponse::try_new(empty, [0u8; 9], 9).err(),
+            Some(BufferError::EmptyWriteBuffer)
+        );
+        assert_eq!(
+            WriteAndReadResponse::try_new([0u8; 9], [0u8; 8], 9).err(),
+            Some(BufferError::ReadBufferTooSmall {
+                required: 9,
+                available: 8
+            })
+        );
+
+        assert_eq!(
+            WriteAndReadInto::try_new(empty, 9).err(),
+            Some(BufferError::EmptyWriteBuffer)
+        );
+        let future = WriteAndReadInto::new([0u8; 9], 9);
+        assert!(future.check_read_buf(&[0u8; 9]).is_ok());
+        assert_eq!(
+            future.check_read_buf(&[0u8; 4]),
+            Err(BufferError::ReadBufferTooSmall {
+                required: 9,
+                available: 4
+            })
+        );
+    }
 }
@@ -0,0 +1,106 @@
+//! Compact binary encoding of readings.
+//!
+//! The encoding uses a fixed layout of big-endian integers to forward
+//! readings over links with small payloads (e.g. LoRa) and decode them on the
+//! receiving side with this crate:
+//!
+//! | Bytes  | Content                                               |
+//! |--------|-------------------------------------------------------|
+//! | 0..2   | CO₂ concentration in ppm (`u16`)                      |
+//! | 2..4   | Temperature in 0.01 °C (`i16`)                        |
+//!
+//! Timestamped readings are prefixed with the timestamp in seconds (`u32`,
+//! e.g. seconds since the Unix epoch or since boot):
+//!
+//! | Bytes  | Content                                               |
+//! |--------|-------------------------------------------------------|
+//! | 0..4   | Timestamp in seconds (`u32`)                          |
+//! | 4..8   | Reading as above                                      |
+//!
+//! ```
+//! # use mh_z19c::payload;
+//! # use mh_z19c::Co2AndTemperature;
+//! let reading = Co2AndTemperature::new(800, 24.5);
+//! let bytes = payload::encode(&reading);
+//! assert_eq!(bytes, [0x03, 0x20, 0x09, 0x92]);
+//! assert_eq!(payload::decode(&bytes), reading);
+//! ```
+
+use crate::Co2AndTemperature;
+
+/// Length of an encoded reading in bytes.
+pub const READING_LEN: usize = 4;
+
+/// Length of an encoded timestamped reading in bytes.
+pub const TIMESTAMPED_READING_LEN: usize = 8;
+
+/// Encodes a `reading`.
+///
+/// The temperature is rounded to 0.01 °C and saturates at the limits of the
+/// encoding (-327.68 °C and 327.67 °C). Only the temperature with the offset
+/// applied is encoded, the decoded raw temperature equals it.
+pub fn encode(reading: &Co2AndTemperature) -> [u8; READING_LEN] {
+    let [co2_high, co2_low] = reading.co2_ppm.to_be_bytes();
+    let centi_celsius = reading.temp_celsius * 100.0;
+    let rounding = if centi_celsius < 0.0 { -0.5 } else { 0.5 };
+    let [temp_high, temp_low] = ((centi_celsius + rounding) as i16).to_be_bytes();
+    [co2_high, co2_low, temp_high, temp_low]
+}
+
+/// Decodes a reading encoded with [`encode`].
+pub fn decode(bytes: &[u8; READING_LEN]) -> Co2AndTemperature {
+    let [co2_high, co2_low, temp_high, temp_low] = *bytes;
+    Co2AndTemperature::new(
+        u16::from_be_bytes([co2_high, co2_low]),
+        f32::from(i16::from_be_bytes([temp_high, temp_low])) / 100.0,
+    )
+}
+
+/// Encodes a `reading` taken at `timestamp_s` seconds.
+pub fn encode_timestamped(
+    timestamp_s: u32,
+    reading: &Co2AndTemperature,
+) -> [u8; TIMESTAMPED_READING_LEN] {
+    let [t0, t1, t2, t3] = timestamp_s.to_be_bytes();
+    let [r0, r1, r2, r3] = encode(reading);
+    [t0, t1, t2, t3, r0, r1, r2, r3]
+}
+
+/// Decodes the timestamp in seconds and the reading encoded with
+/// [`encode_timestamped`].
+pub fn decode_timestamped(bytes: &[u8; TIMESTAMPED_READING_LEN]) -> (u32, Co2AndTemperature) {
+    let [t0, t1, t2, t3, r0, r1, r2, r3] = *bytes;
+    (
+        u32::from_be_bytes([t0, t1, t2, t3]),
+        decode(&[r0, r1, r2, r3]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let reading = Co2AndTemperature::new(5000, -12.34);
+        let bytes = encode(&reading);
+        assert_eq!(bytes, [0x13, 0x88, 0xfb, 0x2e]);
+        assert_eq!(decode(&bytes), reading);
+    }
+
+    #[test]
+    fn test_temperature_is_rounded_and_saturated() {
+        let reading = |temp_celsius| Co2AndTemperature::new(400, temp_celsius);
+        assert_eq!(decode(&encode(&reading(20.004))).temp_celsius, 20.0);
+        assert_eq!(decode(&encode(&reading(20.006))).temp_celsius, 20.01);
+        assert_eq!(decode(&encode(&reading(1000.0))).temp_celsius, 327.67);
+    }
+
+    #[test]
+    fn test_timestamped_round_trip() {
+        let reading = Co2AndTemperature::new(800, 24.5);
+        let bytes = encode_timestamped(1_676_592_000, &reading);
+        assert_eq!(&bytes[..4], &1_676_592_000u32.to_be_bytes());
+        assert_eq!(decode_timestamped(&bytes), (1_676_592_000, reading));
+    }
+}
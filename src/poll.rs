@@ -0,0 +1,173 @@
+//! Poll-style API without [`nb`] types.
+//!
+//! The methods of the driver return [`nb::Result`] to signal that an
+//! operation has not completed yet. The [`Polling`] wrapper provides the same
+//! operations returning [`Poll`] from the core library instead, e.g. for
+//! applications built around [`core::task`] or using a HAL that does not use
+//! [`nb`]:
+//!
+//! ```
+//! # use core::task::Poll;
+//! # use mh_z19c::sim::Simulator;
+//! # use mh_z19c::MhZ19C;
+//! let mut co2sensor = MhZ19C::new(Simulator::new());
+//! let co2_ppm = loop {
+//!     if let Poll::Ready(result) = co2sensor.polling().read_co2_ppm() {
+//!         break result?;
+//!     }
+//!     // Do something else in the meantime.
+//! };
+//! # assert_eq!(co2_ppm, 400);
+//! # Ok::<(), mh_z19c::Error<core::convert::Infallible>>(())
+//! ```
+//!
+//! The [`nb`] based methods remain available and both APIs can be mixed, as
+//! the wrapper only borrows the driver.
+
+use crate::firmware::FirmwareVersion;
+use crate::profile::{DeviceProfile, MhZ19CProfile};
+#[cfg(feature = "firmware5")]
+use crate::Co2AndTemperature;
+use crate::{Error, MhZ19C};
+use core::task::Poll;
+use embedded_hal::serial::{Read, Write};
+
+/// Converts the [`nb::Result`] of a non-blocking operation into a [`Poll`].
+///
+/// [`nb::Error::WouldBlock`] becomes [`Poll::Pending`], everything else
+/// [`Poll::Ready`].
+pub fn into_poll<T, E>(result: nb::Result<T, E>) -> Poll<Result<T, E>> {
+    match result {
+        Ok(value) => Poll::Ready(Ok(value)),
+        Err(nb::Error::Other(err)) => Poll::Ready(Err(err)),
+        Err(nb::Error::WouldBlock) => Poll::Pending,
+    }
+}
+
+/// Driver wrapper returning [`Poll`] instead of [`nb::Result`].
+///
+/// Obtained with [`MhZ19C::polling`]. Like with the [`nb`] based API, an
+/// operation is started by the first call and has to be polled by calling
+/// the same method again until it returns [`Poll::Ready`].
+pub struct Polling<'a, 'b, U, E, P = MhZ19CProfile>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+{
+    mh_z19c: &'b mut MhZ19C<'a, U, E, P>,
+}
+
+impl<'a, U, E, P> MhZ19C<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Returns a wrapper providing the API of the driver with [`Poll`]
+    /// instead of [`nb::Result`] return values.
+    pub fn polling<'b>(&'b mut self) -> Polling<'a, 'b, U, E, P> {
+        Polling { mh_z19c: self }
+    }
+}
+
+impl<'a, 'b, U, E, P> Polling<'a, 'b, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Returns a reference to the wrapped [`MhZ19C`] driver.
+    pub fn get_ref(&self) -> &MhZ19C<'a, U, E, P> {
+        self.mh_z19c
+    }
+
+    /// Returns a mutable reference to the wrapped [`MhZ19C`] driver.
+    pub fn get_mut(&mut self) -> &mut MhZ19C<'a, U, E, P> {
+        self.mh_z19c
+    }
+
+    /// See [`MhZ19C::read_co2_ppm`].
+    pub fn read_co2_ppm(&mut self) -> Poll<Result<u16, Error<E>>> {
+        into_poll(self.mh_z19c.read_co2_ppm())
+    }
+
+    /// See [`MhZ19C::read_co2_and_temp`].
+    #[cfg(feature = "firmware5")]
+    pub fn read_co2_and_temp(&mut self) -> Poll<Result<Co2AndTemperature, Error<E>>> {
+        into_poll(self.mh_z19c.read_co2_and_temp())
+    }
+
+    /// See [`MhZ19C::get_firmware_version`].
+    pub fn get_firmware_version(&mut self) -> Poll<Result<FirmwareVersion, Error<E>>> {
+        into_poll(self.mh_z19c.get_firmware_version())
+    }
+
+    /// See [`MhZ19C::set_self_calibrate`].
+    #[cfg(feature = "calibration")]
+    pub fn set_self_calibrate(&mut self, enabled: bool) -> Poll<Result<(), Error<E>>> {
+        into_poll(self.mh_z19c.set_self_calibrate(enabled))
+    }
+
+    /// See [`MhZ19C::get_self_calibrate`].
+    #[cfg(feature = "calibration")]
+    pub fn get_self_calibrate(&mut self) -> Poll<Result<bool, Error<E>>> {
+        into_poll(self.mh_z19c.get_self_calibrate())
+    }
+
+    /// See [`MhZ19C::calibrate_zero`].
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_zero(&mut self) -> Poll<Result<(), Error<E>>> {
+        into_poll(self.mh_z19c.calibrate_zero())
+    }
+
+    /// See [`MhZ19C::calibrate_span`].
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_span(&mut self, span_ppm: u16) -> Poll<Result<(), Error<E>>> {
+        into_poll(self.mh_z19c.calibrate_span(span_ppm))
+    }
+
+    /// See [`MhZ19C::set_detection_range`].
+    pub fn set_detection_range(&mut self, range_ppm: u16) -> Poll<Result<(), Error<E>>> {
+        into_poll(self.mh_z19c.set_detection_range(range_ppm))
+    }
+
+    /// See [`MhZ19C::get_detection_range`].
+    pub fn get_detection_range(&mut self) -> Poll<Result<u16, Error<E>>> {
+        into_poll(self.mh_z19c.get_detection_range())
+    }
+
+    /// See [`MhZ19C::init`].
+    pub fn init(&mut self) -> Poll<Result<(), Error<E>>> {
+        into_poll(self.mh_z19c.init())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::serial_mock::SerialMock;
+    use test_support::READ_CO2_RESPONSE;
+
+    #[test]
+    fn test_into_poll() {
+        assert_eq!(into_poll::<_, ()>(Ok(1)), Poll::Ready(Ok(1)));
+        assert_eq!(
+            into_poll::<u8, _>(Err(nb::Error::Other(2))),
+            Poll::Ready(Err(2))
+        );
+        assert_eq!(
+            into_poll::<u8, u8>(Err(nb::Error::WouldBlock)),
+            Poll::Pending
+        );
+    }
+
+    #[test]
+    fn test_polling_read_co2() {
+        let reads = core::iter::once(Err(nb::Error::WouldBlock))
+            .chain(READ_CO2_RESPONSE.iter().map(|&byte| Ok(byte)))
+            .collect();
+        let uart = SerialMock::new(reads, vec![Ok(()); 9]);
+        let mut co2sensor = MhZ19C::new(uart);
+        let mut polling = co2sensor.polling();
+        assert_eq!(polling.read_co2_ppm(), Poll::Pending);
+        assert_eq!(polling.read_co2_ppm(), Poll::Ready(Ok(800)));
+        assert!(!polling.get_ref().is_busy());
+    }
+}
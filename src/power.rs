@@ -0,0 +1,171 @@
+//! Power control of the sensor with warm-up tracking.
+//!
+//! Switching off the supply of the sensor between measurements (e.g. with a
+//! MOSFET controlled by an output pin) is the main way to save power in
+//! battery powered deployments. After powering on, the sensor needs to
+//! preheat before providing valid readings.
+//!
+//! Time is passed as a monotonic timestamp in milliseconds by the
+//! application, so that any clock source can be used.
+
+#[cfg(feature = "display")]
+use core::fmt::{self, Display};
+use embedded_hal::digital::v2::OutputPin;
+
+/// Preheat time of the MH-Z19C in milliseconds according to the data sheet.
+pub const WARM_UP_MS: u64 = 60_000;
+
+/// The sensor is powered off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoweredOff;
+
+#[cfg(feature = "display")]
+impl Display for PoweredOff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the sensor is powered off")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PoweredOff {}
+
+/// Controls the power supply of the sensor via an output pin and tracks the
+/// warm-up time after powering on.
+#[derive(Debug)]
+pub struct PowerControl<P> {
+    pin: P,
+    active_low: bool,
+    warm_up_ms: u64,
+    powered_on_at_ms: Option<u64>,
+}
+
+impl<P: OutputPin> PowerControl<P> {
+    /// Create a new instance for a `pin` that powers the sensor on with a
+    /// high level. The sensor will be powered off initially.
+    pub fn new(pin: P) -> Result<Self, P::Error> {
+        Self::with_polarity(pin, false)
+    }
+
+    /// Create a new instance for a `pin` that powers the sensor on with a low
+    /// level (e.g. driving a high-side P-channel MOSFET). The sensor will be
+    /// powered off initially.
+    pub fn new_active_low(pin: P) -> Result<Self, P::Error> {
+        Self::with_polarity(pin, true)
+    }
+
+    fn with_polarity(pin: P, active_low: bool) -> Result<Self, P::Error> {
+        let mut power_control = Self {
+            pin,
+            active_low,
+            warm_up_ms: WARM_UP_MS,
+            powered_on_at_ms: None,
+        };
+        power_control.power_off()?;
+        Ok(power_control)
+    }
+
+    /// Sets the warm-up time in milliseconds (defaults to [`WARM_UP_MS`]).
+    pub fn with_warm_up_ms(mut self, warm_up_ms: u64) -> Self {
+        self.warm_up_ms = warm_up_ms;
+        self
+    }
+
+    fn set_level(&mut self, on: bool) -> Result<(), P::Error> {
+        if on != self.active_low {
+            self.pin.set_high()
+        } else {
+            self.pin.set_low()
+        }
+    }
+
+    /// Powers the sensor on at the timestamp `now_ms`.
+    ///
+    /// If the sensor is already powered on, the warm-up continues unaffected.
+    pub fn power_on(&mut self, now_ms: u64) -> Result<(), P::Error> {
+        self.set_level(true)?;
+        if self.powered_on_at_ms.is_none() {
+            self.powered_on_at_ms = Some(now_ms);
+        }
+        Ok(())
+    }
+
+    /// Powers the sensor off.
+    ///
+    /// Note that the UART lines of the microcontroller should not drive the
+    /// sensor while it is powered off.
+    pub fn power_off(&mut self) -> Result<(), P::Error> {
+        self.set_level(false)?;
+        self.powered_on_at_ms = None;
+        Ok(())
+    }
+
+    /// Returns `true` if the sensor is powered on.
+    pub fn is_powered_on(&self) -> bool {
+        self.powered_on_at_ms.is_some()
+    }
+
+    /// Returns the remaining warm-up time in milliseconds at the timestamp
+    /// `now_ms`, or `None` if the sensor is powered off.
+    pub fn remaining_warm_up_ms(&self, now_ms: u64) -> Option<u64> {
+        self.powered_on_at_ms
+            .map(|on_ms| on_ms.saturating_add(self.warm_up_ms).saturating_sub(now_ms))
+    }
+
+    /// Returns `Ok(())` if the sensor is powered on and warmed up at the
+    /// timestamp `now_ms`, [`nb::Error::WouldBlock`] while it is warming up,
+    /// and [`PoweredOff`] if it is powered off.
+    pub fn poll_ready(&self, now_ms: u64) -> nb::Result<(), PoweredOff> {
+        match self.remaining_warm_up_ms(now_ms) {
+            None => Err(nb::Error::Other(PoweredOff)),
+            Some(0) => Ok(()),
+            Some(_) => Err(nb::Error::WouldBlock),
+        }
+    }
+
+    /// Returns the owned output pin.
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::pin_mock::PinMock;
+
+    #[test]
+    fn test_warm_up() {
+        let mut power = PowerControl::new(PinMock::default()).unwrap();
+        assert_eq!(power.poll_ready(0), Err(nb::Error::Other(PoweredOff)));
+
+        power.power_on(1_000).unwrap();
+        assert!(power.is_powered_on());
+        assert_eq!(power.remaining_warm_up_ms(1_000), Some(WARM_UP_MS));
+        assert_eq!(power.poll_ready(30_000), Err(nb::Error::WouldBlock));
+        power.power_on(30_000).unwrap();
+        assert_eq!(power.poll_ready(61_000), Ok(()));
+
+        power.power_off().unwrap();
+        assert_eq!(power.remaining_warm_up_ms(61_000), None);
+        assert_eq!(power.into_inner().levels, vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn test_active_low() {
+        let mut power = PowerControl::new_active_low(PinMock::default())
+            .unwrap()
+            .with_warm_up_ms(10);
+        power.power_on(0).unwrap();
+        assert_eq!(power.poll_ready(10), Ok(()));
+        assert_eq!(power.into_inner().levels, vec![true, false]);
+    }
+    #[test]
+    fn test_warm_up_saturates() {
+        let mut power = PowerControl::new(PinMock::default())
+            .unwrap()
+            .with_warm_up_ms(u64::MAX);
+        power.power_on(1_000).unwrap();
+        assert_eq!(power.remaining_warm_up_ms(1_000), Some(u64::MAX - 1_000));
+        assert_eq!(power.poll_ready(u64::MAX), Ok(()));
+    }
+}
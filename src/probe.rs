@@ -0,0 +1,176 @@
+//! Verifying the presence of the sensor when constructing the driver.
+//!
+//! Wiring mistakes (e.g. swapped RX/TX lines or a missing supply) otherwise
+//! only surface on the first reading. Probing sends the harmless firmware
+//! version query and only returns the driver if a valid response has been
+//! received. The [`Delay`] backoff determines how long to wait between polls
+//! for the response:
+//!
+//! ```
+//! # use mh_z19c::backoff::Delay;
+//! # use mh_z19c::sim::Simulator;
+//! # use mh_z19c::MhZ19C;
+//! # struct SystemDelay;
+//! # impl embedded_hal::blocking::delay::DelayUs<u32> for SystemDelay {
+//! #     fn delay_us(&mut self, _us: u32) {}
+//! # }
+//! let mut backoff = Delay::new(SystemDelay, 1_000).with_max_us(50_000);
+//! let co2sensor = match MhZ19C::probe(Simulator::new(), &mut backoff) {
+//!     Ok(co2sensor) => co2sensor,
+//!     Err((_uart, err)) => panic!("MH-Z19C not found: {err}"),
+//! };
+//! assert!(co2sensor.firmware_version().is_some());
+//! ```
+
+use crate::backoff::{Backoff, Delay};
+use crate::builder::MhZ19CBuilder;
+use crate::profile::DeviceProfile;
+use crate::{Error, MhZ19C};
+#[cfg(feature = "display")]
+use core::fmt::{self, Display};
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::serial::{Read, Write};
+
+/// Time in milliseconds to wait for the response of the sensor when probing.
+pub const PROBE_TIMEOUT_MS: u16 = 500;
+
+/// Reason why probing the sensor failed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProbeError<E> {
+    /// No complete response has been received within [`PROBE_TIMEOUT_MS`]
+    /// (e.g. the sensor is not connected or powered, the RX and TX lines are
+    /// swapped, or the baud rate does not match).
+    NoResponse,
+    /// The communication failed or an invalid response has been received.
+    Communication(Error<E>),
+}
+
+#[cfg(feature = "display")]
+impl<E: Display> Display for ProbeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoResponse => write!(
+                f,
+                "no response from sensor within {PROBE_TIMEOUT_MS} ms, check wiring and power"
+            ),
+            Self::Communication(err) => write!(f, "probing sensor failed: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + Display> std::error::Error for ProbeError<E> {}
+
+impl<'a, U, E> MhZ19C<'a, U, E>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+{
+    /// Create a new instance after verifying that the sensor responds on the
+    /// `uart` interface.
+    ///
+    /// See [`MhZ19CBuilder::probe`].
+    #[allow(clippy::type_complexity)]
+    pub fn probe<D: DelayUs<u32>>(
+        uart: U,
+        backoff: &mut Delay<D>,
+    ) -> Result<Self, (U, ProbeError<E>)> {
+        Self::builder().probe(uart, backoff)
+    }
+}
+
+impl<'a, U, E, P> MhZ19CBuilder<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Builds the driver after verifying that the sensor responds on the
+    /// `uart` interface.
+    ///
+    /// The firmware version is queried (and cached) polling the UART
+    /// interface with the `backoff` between polls for up to
+    /// [`PROBE_TIMEOUT_MS`]. On failure, the pending bytes are discarded and
+    /// the UART interface is returned together with the error.
+    #[allow(clippy::type_complexity)]
+    pub fn probe<D: DelayUs<u32>>(
+        self,
+        uart: U,
+        backoff: &mut Delay<D>,
+    ) -> Result<MhZ19C<'a, U, E, P>, (U, ProbeError<E>)> {
+        let mut driver = self.build(uart);
+        let timeout_us = u32::from(PROBE_TIMEOUT_MS) * 1000;
+        let mut waited_us: u32 = 0;
+        backoff.reset();
+        let err = loop {
+            match driver.get_firmware_version() {
+                Ok(_) => return Ok(driver),
+                Err(nb::Error::Other(err)) => break ProbeError::Communication(err),
+                Err(nb::Error::WouldBlock) if waited_us >= timeout_us => {
+                    break ProbeError::NoResponse
+                }
+                Err(nb::Error::WouldBlock) => {
+                    waited_us = waited_us.saturating_add(backoff.current_us());
+                    backoff.backoff();
+                }
+            }
+        };
+        // The probe already failed, a UART error while discarding would not
+        // add any information.
+        let _ = driver.abort();
+        Err((driver.into_inner(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firmware::FirmwareVersion;
+    use crate::sim::Simulator;
+    use std::string::String;
+    use test_support::delay_mock::DelayMock;
+    use test_support::serial_mock::SerialMock;
+
+    #[test]
+    fn test_probe_returns_driver_if_sensor_responds() {
+        let mut backoff = Delay::new(DelayMock::default(), 1_000);
+        let co2sensor = MhZ19C::probe(Simulator::new(), &mut backoff).unwrap();
+        assert_eq!(
+            co2sensor.firmware_version(),
+            Some(FirmwareVersion::new(*b"0515"))
+        );
+    }
+
+    #[test]
+    fn test_probe_returns_uart_if_sensor_does_not_respond() {
+        let mut backoff = Delay::new(DelayMock::default(), 1_000);
+        let uart = SerialMock::new(vec![], vec![Ok(()); 9]);
+        let (uart, err) = MhZ19C::probe(uart, &mut backoff).unwrap_err();
+        assert_eq!(err, ProbeError::NoResponse);
+        assert_eq!(backoff.into_inner().total_ms(), u32::from(PROBE_TIMEOUT_MS));
+        assert_eq!(uart.write_buf.len(), 9);
+    }
+
+    #[test]
+    fn test_probe_backs_off_exponentially() {
+        let mut backoff = Delay::new(DelayMock::default(), 1_000).with_max_us(64_000);
+        let uart = SerialMock::new(vec![], vec![Ok(()); 9]);
+        let (_, err) = MhZ19C::probe(uart, &mut backoff).unwrap_err();
+        assert_eq!(err, ProbeError::NoResponse);
+        let delays_us = backoff.into_inner().delays_us;
+        assert_eq!(delays_us[..3], [1_000, 2_000, 4_000]);
+        assert!(delays_us.iter().sum::<u32>() >= u32::from(PROBE_TIMEOUT_MS) * 1000);
+        assert!(delays_us.len() < 20);
+    }
+
+    #[test]
+    fn test_probe_reports_invalid_response() {
+        let mut response = test_support::FIRMWARE_0515_RESPONSE;
+        response[8] ^= 0xff;
+        let uart = SerialMock::new(response.iter().copied().map(Ok).collect(), vec![Ok(()); 9]);
+        let mut backoff = Delay::new(DelayMock::default(), 1_000);
+        let (_, err) = MhZ19C::probe(uart, &mut backoff).unwrap_err();
+        assert!(matches!(
+            err,
+            ProbeError::<String>::Communication(Error::ValidateFrameError(_))
+        ));
+    }
+}
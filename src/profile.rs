@@ -0,0 +1,140 @@
+//! Device profiles capturing the behavior specific to a Winsen NDIR sensor.
+//!
+//! The frame, command, and transport handling of the driver is shared by the
+//! Winsen NDIR CO₂ sensors using the same serial protocol. The differences
+//! between the sensor models (supported commands, detection ranges, decoding
+//! of responses) are described by a [`DeviceProfile`] that is passed as type
+//! parameter to the [`crate::MhZ19C`] driver. By default, the
+//! [`MhZ19CProfile`] is used.
+
+#[cfg(doc)]
+use crate::capabilities::TemperatureEncoding;
+use crate::command::Command;
+use crate::Co2AndTemperature;
+
+/// Device-specific behavior of a Winsen NDIR CO₂ sensor.
+pub trait DeviceProfile {
+    /// Name of the sensor model.
+    const MODEL: &'static str;
+
+    /// Detection ranges (upper limits of measurable CO₂ concentration) in
+    /// ppm supported by the sensor.
+    const DETECTION_RANGES_PPM: &'static [u16];
+
+    /// Returns whether the sensor supports the `command`.
+    ///
+    /// Commands that are not supported will be rejected by the driver with
+    /// [`crate::Error::NotSupportedByDevice`] without sending them.
+    fn supports(command: Command) -> bool {
+        let _ = command;
+        true
+    }
+
+    /// Decodes the CO₂ concentration in ppm from the response data (without
+    /// op code) to [`Command::ReadCo2`].
+    fn decode_co2_ppm(data: &[u8; 6]) -> u16 {
+        u16::from_be_bytes([data[0], data[1]])
+    }
+
+    /// Decodes the CO₂ concentration and the temperature encoded with
+    /// [`TemperatureEncoding::OffsetByte`] from the response data (without op
+    /// code) to [`Command::ReadCo2`].
+    fn decode_co2_and_offset_temperature(data: &[u8; 6]) -> Co2AndTemperature {
+        Co2AndTemperature::new(Self::decode_co2_ppm(data), f32::from(data[2]) - 40.0)
+    }
+
+    /// Decodes the response data (without op code) to
+    /// [`Command::ReadCo2AndTemperature`].
+    fn decode_co2_and_temperature(data: &[u8; 6]) -> Co2AndTemperature {
+        Co2AndTemperature::new(
+            u16::from_be_bytes([data[2], data[3]]),
+            f32::from(u16::from_be_bytes([data[0], data[1]])) / 100.0,
+        )
+    }
+}
+
+/// Profile of the Winsen MH-Z19C sensor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MhZ19CProfile;
+
+impl DeviceProfile for MhZ19CProfile {
+    const MODEL: &'static str = "MH-Z19C";
+    const DETECTION_RANGES_PPM: &'static [u16] = &[2000, 5000, 10000];
+}
+
+#[cfg(test)]
+#[cfg_attr(
+    not(all(feature = "calibration", feature = "firmware5")),
+    allow(unused_imports)
+)]
+mod tests {
+    use super::*;
+    use crate::{Error, MhZ19C};
+    use nb::block;
+    use test_support::serial_mock::SerialMock;
+    use test_support::{create_serial_mock_returning, FIRMWARE_0515_RESPONSE, READ_CO2_RESPONSE};
+
+    #[derive(Debug)]
+    struct FixedRangeProfile;
+
+    impl DeviceProfile for FixedRangeProfile {
+        const MODEL: &'static str = "fixed range test sensor";
+        const DETECTION_RANGES_PPM: &'static [u16] = &[5000];
+
+        fn supports(command: Command) -> bool {
+            !matches!(
+                command,
+                Command::SetDetectionRange(_) | Command::GetDetectionRange
+            )
+        }
+
+        fn decode_co2_ppm(data: &[u8; 6]) -> u16 {
+            u16::from_be_bytes([data[0], data[1]]) / 2
+        }
+    }
+
+    #[test]
+    fn test_uses_profile_to_decode() {
+        let uart = create_serial_mock_returning(&READ_CO2_RESPONSE);
+        let mut co2sensor = MhZ19C::<_, _, FixedRangeProfile>::with_profile(uart);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(400));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_command() {
+        let uart = create_serial_mock_returning(&[]);
+        let mut co2sensor = MhZ19C::<_, _, FixedRangeProfile>::with_profile(uart);
+        assert_eq!(
+            block!(co2sensor.get_detection_range()),
+            Err(Error::NotSupportedByDevice(Command::GetDetectionRange))
+        );
+        assert!(co2sensor.into_inner().write_buf.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_upgrade_to_v5_keeps_profile() {
+        let uart = SerialMock::new(
+            FIRMWARE_0515_RESPONSE.iter().copied().map(Ok).collect(),
+            vec![Ok(()); 9],
+        );
+        let mut co2sensor = MhZ19C::<_, _, FixedRangeProfile>::with_profile(uart);
+        let mut co2sensor = block!(co2sensor.upgrade_to_v5()).unwrap();
+        assert_eq!(
+            block!(co2sensor.set_detection_range(2000)),
+            Err(Error::NotSupportedByDevice(Command::SetDetectionRange(
+                2000
+            )))
+        );
+    }
+
+    #[test]
+    fn test_mh_z19c_profile_decoding() {
+        let data = [0x09, 0x60, 0x03, 0x20, 0x00, 0x00];
+        assert_eq!(MhZ19CProfile::decode_co2_ppm(&data), 2400);
+        assert_eq!(
+            MhZ19CProfile::decode_co2_and_temperature(&data),
+            Co2AndTemperature::new(800, 24.0)
+        );
+    }
+}
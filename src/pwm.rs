@@ -0,0 +1,148 @@
+//! Decoding of the PWM output of the sensor and cross-checking it with the
+//! readings obtained via the serial interface.
+//!
+//! Measuring the pulse widths depends on the hardware (e.g. timer input
+//! capture) and is left to the application. According to the data sheet, the
+//! PWM output has a cycle of 1004 ms, starting with a high level of 2 ms and
+//! ending with a low level of 2 ms. The CO₂ concentration is given by the
+//! remaining high level time relative to the 1000 ms of the cycle in between.
+
+#[cfg(feature = "display")]
+use core::fmt::{self, Display};
+
+/// Nominal duration of a PWM cycle in microseconds.
+pub const CYCLE_MICROS: u32 = 1_004_000;
+/// Duration of the fixed high and low levels at the start and end of a cycle
+/// in microseconds.
+const FIXED_LEVEL_MICROS: u32 = 2_000;
+/// Tolerated deviation of the measured cycle from the nominal cycle.
+const CYCLE_TOLERANCE_MICROS: u32 = 50_000;
+
+/// Converts the measured high and low level durations of a PWM cycle in
+/// microseconds to the CO₂ concentration in ppm for the given detection range
+/// (upper limit) in ppm.
+///
+/// Returns `None` if the measured cycle deviates by more than 5 % from the
+/// nominal [`CYCLE_MICROS`].
+pub fn pulse_to_ppm(high_micros: u32, low_micros: u32, range_ppm: u16) -> Option<u16> {
+    let cycle_micros = high_micros.checked_add(low_micros)?;
+    if cycle_micros.abs_diff(CYCLE_MICROS) > CYCLE_TOLERANCE_MICROS {
+        return None;
+    }
+    let high_micros = high_micros.saturating_sub(FIXED_LEVEL_MICROS);
+    let measured_micros = cycle_micros - 2 * FIXED_LEVEL_MICROS;
+    let ppm = u64::from(high_micros) * u64::from(range_ppm) / u64::from(measured_micros);
+    Some(ppm.min(u64::from(range_ppm)) as u16)
+}
+
+/// Disagreement between the readings of the serial interface and the PWM
+/// output exceeding the tolerance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// CO₂ concentration read via the serial interface in ppm.
+    pub uart_ppm: u16,
+    /// CO₂ concentration decoded from the PWM output in ppm.
+    pub pwm_ppm: u16,
+    /// Tolerated difference in ppm.
+    pub tolerance_ppm: u16,
+}
+
+#[cfg(feature = "display")]
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor fault: UART reading of {} ppm and PWM reading of {} ppm differ by more than {} ppm",
+            self.uart_ppm, self.pwm_ppm, self.tolerance_ppm
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Divergence {}
+
+/// Checks the consistency of the readings obtained via the serial interface
+/// and the PWM output.
+///
+/// The tolerated difference is the sum of an absolute and a relative
+/// tolerance (relative to the larger reading), resembling the accuracy
+/// specification of the sensor (e.g. ±(50 ppm + 5 %)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConsistencyCheck {
+    absolute_ppm: u16,
+    relative_percent: u8,
+}
+
+impl Default for ConsistencyCheck {
+    /// Returns a check with twice the tolerance of the accuracy specified in
+    /// the data sheet (±(50 ppm + 5 %)) to account for both channels.
+    fn default() -> Self {
+        Self::new(100, 10)
+    }
+}
+
+impl ConsistencyCheck {
+    /// Create a new instance with the given absolute tolerance in ppm and
+    /// relative tolerance in percent.
+    pub fn new(absolute_ppm: u16, relative_percent: u8) -> Self {
+        Self {
+            absolute_ppm,
+            relative_percent,
+        }
+    }
+
+    /// Returns the tolerated difference in ppm for the given readings.
+    pub fn tolerance_ppm(&self, uart_ppm: u16, pwm_ppm: u16) -> u16 {
+        let relative = u32::from(uart_ppm.max(pwm_ppm)) * u32::from(self.relative_percent) / 100;
+        (u32::from(self.absolute_ppm) + relative).min(u32::from(u16::MAX)) as u16
+    }
+
+    /// Compares the readings of both channels and returns the reading of the
+    /// serial interface if they agree within the tolerance.
+    pub fn check(&self, uart_ppm: u16, pwm_ppm: u16) -> Result<u16, Divergence> {
+        let tolerance_ppm = self.tolerance_ppm(uart_ppm, pwm_ppm);
+        if uart_ppm.abs_diff(pwm_ppm) <= tolerance_ppm {
+            Ok(uart_ppm)
+        } else {
+            Err(Divergence {
+                uart_ppm,
+                pwm_ppm,
+                tolerance_ppm,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pulse_to_ppm() {
+        assert_eq!(pulse_to_ppm(2_000, 1_002_000, 5000), Some(0));
+        assert_eq!(pulse_to_ppm(162_000, 842_000, 5000), Some(800));
+        assert_eq!(pulse_to_ppm(1_002_000, 2_000, 5000), Some(5000));
+        assert_eq!(pulse_to_ppm(102_000, 902_000, 2000), Some(200));
+    }
+
+    #[test]
+    fn test_pulse_to_ppm_rejects_invalid_cycle() {
+        assert_eq!(pulse_to_ppm(100_000, 100_000, 5000), None);
+        assert_eq!(pulse_to_ppm(u32::MAX, 1, 5000), None);
+    }
+
+    #[test]
+    fn test_consistency_check() {
+        let check = ConsistencyCheck::new(50, 5);
+        assert_eq!(check.tolerance_ppm(800, 1000), 100);
+        assert_eq!(check.check(800, 880), Ok(800));
+        assert_eq!(
+            check.check(800, 1000),
+            Err(Divergence {
+                uart_ppm: 800,
+                pwm_ppm: 1000,
+                tolerance_ppm: 100
+            })
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Sensor-agnostic abstraction of CO₂ sensors.
+//!
+//! The [`Co2Sensor`] trait covers the functionality common to CO₂ sensors
+//! (e.g. the Sensirion SCD30 or SCD41 besides the MH-Z19C). Application
+//! logic written against it can be used with any sensor driver implementing
+//! the trait, including the [`crate::MhZ19C`] driver with the
+//! [`crate::sim::Simulator`] for testing.
+//!
+//! ```
+//! # use mh_z19c::sensor::Co2Sensor;
+//! # use mh_z19c::sim::Simulator;
+//! # use mh_z19c::MhZ19C;
+//! # use nb::block;
+//! fn ventilation_needed<S: Co2Sensor>(sensor: &mut S) -> Result<bool, S::Error> {
+//!     Ok(block!(sensor.read_co2_ppm())? > 1000)
+//! }
+//!
+//! let mut co2sensor = MhZ19C::new(Simulator::new());
+//! assert_eq!(ventilation_needed(&mut co2sensor), Ok(false));
+//! ```
+
+use crate::profile::DeviceProfile;
+use crate::{Error, MhZ19C};
+use embedded_hal::serial::{Read, Write};
+
+#[cfg(feature = "firmware5")]
+use crate::capabilities::Capabilities;
+#[cfg(feature = "firmware5")]
+use crate::command::Command;
+
+/// Measurement of a [`Co2Sensor`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Measurement {
+    /// CO₂ concentration in parts per million (ppm).
+    pub co2_ppm: u16,
+    /// Temperature in degrees Celsius (°C) if supported by the sensor.
+    pub temp_celsius: Option<f32>,
+}
+
+/// Functionality common to CO₂ sensors.
+pub trait Co2Sensor {
+    /// Error type of the sensor driver.
+    type Error;
+
+    /// Reads and returns the CO₂ concentration in parts-per-million (ppm).
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Self::Error>;
+
+    /// Reads the CO₂ concentration and, if supported, the temperature.
+    fn read_measurement(&mut self) -> nb::Result<Measurement, Self::Error>;
+
+    /// Returns whether the measurements include the temperature, or `None` if
+    /// this is not known before communicating with the sensor.
+    fn supports_temperature(&self) -> Option<bool>;
+}
+
+impl<T> Co2Sensor for &mut T
+where
+    T: Co2Sensor + ?Sized,
+{
+    type Error = T::Error;
+
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Self::Error> {
+        (**self).read_co2_ppm()
+    }
+
+    fn read_measurement(&mut self) -> nb::Result<Measurement, Self::Error> {
+        (**self).read_measurement()
+    }
+
+    fn supports_temperature(&self) -> Option<bool> {
+        (**self).supports_temperature()
+    }
+}
+
+/// The temperature is read with the firmware 5 API if supported by the
+/// sensor. Otherwise, only the CO₂ concentration is read. The firmware
+/// version will be queried and cached on first use.
+impl<'a, U, E, P> Co2Sensor for MhZ19C<'a, U, E, P>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    type Error = Error<E>;
+
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Self::Error> {
+        MhZ19C::read_co2_ppm(self)
+    }
+
+    #[cfg(feature = "firmware5")]
+    fn read_measurement(&mut self) -> nb::Result<Measurement, Self::Error> {
+        let firmware_version = self.cached_firmware_version()?;
+        if P::supports(Command::ReadCo2AndTemperature)
+            && Capabilities::from_firmware_version(firmware_version)
+                .supports(Command::ReadCo2AndTemperature)
+        {
+            let reading = self.execute_read_co2_and_temp()?;
+            Ok(Measurement {
+                co2_ppm: reading.co2_ppm,
+                temp_celsius: Some(reading.temp_celsius),
+            })
+        } else {
+            self.read_co2_ppm().map(|co2_ppm| Measurement {
+                co2_ppm,
+                temp_celsius: None,
+            })
+        }
+    }
+
+    #[cfg(not(feature = "firmware5"))]
+    fn read_measurement(&mut self) -> nb::Result<Measurement, Self::Error> {
+        self.read_co2_ppm().map(|co2_ppm| Measurement {
+            co2_ppm,
+            temp_celsius: None,
+        })
+    }
+
+    #[cfg(feature = "firmware5")]
+    fn supports_temperature(&self) -> Option<bool> {
+        if !P::supports(Command::ReadCo2AndTemperature) {
+            return Some(false);
+        }
+        self.capabilities()
+            .map(|capabilities| capabilities.supports(Command::ReadCo2AndTemperature))
+    }
+
+    #[cfg(not(feature = "firmware5"))]
+    fn supports_temperature(&self) -> Option<bool> {
+        Some(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firmware::FirmwareVersion;
+    use crate::sim::Simulator;
+    use nb::block;
+
+    #[cfg(feature = "firmware5")]
+    #[test]
+    fn test_read_measurement_with_temperature() {
+        let mut co2sensor = MhZ19C::new(Simulator::new());
+        assert_eq!(co2sensor.supports_temperature(), None);
+        assert_eq!(
+            block!(co2sensor.read_measurement()),
+            Ok(Measurement {
+                co2_ppm: 400,
+                temp_celsius: Some(20.0)
+            })
+        );
+        assert_eq!(co2sensor.supports_temperature(), Some(true));
+    }
+
+    #[test]
+    fn test_read_measurement_without_temperature() {
+        let simulator = Simulator::new().with_firmware_version(FirmwareVersion::new(*b"0400"));
+        let mut co2sensor = MhZ19C::new(simulator);
+        let sensor: &mut dyn Co2Sensor<Error = _> = &mut co2sensor;
+        assert_eq!(
+            block!(sensor.read_measurement()),
+            Ok(Measurement {
+                co2_ppm: 400,
+                temp_celsius: None
+            })
+        );
+        assert_eq!(block!(sensor.read_co2_ppm()), Ok(400));
+        assert_eq!(sensor.supports_temperature(), Some(false));
+    }
+}
@@ -0,0 +1,432 @@
+//! Sharing a driver between multiple tasks.
+//!
+//! A [`SharedMhZ19C`] owns the driver and hands out [`SharedHandle`]s to the
+//! tasks that need to communicate with the sensor (e.g. a display task and an
+//! uplink task). Each handle implements the [`BaseApi`] and commands of
+//! different handles are serialized: while the command of one handle is in
+//! progress, the other handles will get [`nb::Error::WouldBlock`] until it
+//! has completed.
+//!
+//! The synchronization primitive is given by the [`DriverMutex`] type
+//! parameter. It is implemented for [`RefCell`] to share the driver between
+//! tasks running on the same thread (e.g. cooperatively scheduled tasks) and,
+//! with the `std` feature, for [`std::sync::Mutex`] to share the driver
+//! between threads. The [`SharedRefCell`] and [`SharedMutex`] aliases name
+//! the resulting types, similar to the bus managers of the `shared-bus`
+//! crate for I²C drivers.
+//!
+//! ```
+//! # use mh_z19c::{BaseApi, MhZ19C};
+//! # use mh_z19c::shared::{SharedMhZ19C, SharedRefCell};
+//! # use nb::block;
+//! # use test_support::serial_mock::SerialMock;
+//! # use test_support::READ_CO2_RESPONSE;
+//! # fn main() -> Result<(), mh_z19c::Error<String>> {
+//! # let uart = SerialMock::new(
+//! #     std::iter::once(Err(nb::Error::WouldBlock))
+//! #         .chain(READ_CO2_RESPONSE.iter().copied().map(Ok))
+//! #         .collect(),
+//! #     vec![Ok(()); 9],
+//! # );
+//! let shared: SharedRefCell<_, _> = SharedMhZ19C::new(MhZ19C::new(uart));
+//! let mut display_task = shared.handle();
+//! let mut uplink_task = shared.handle();
+//! assert_eq!(display_task.read_co2_ppm(), Err(nb::Error::WouldBlock));
+//! assert_eq!(uplink_task.read_co2_ppm(), Err(nb::Error::WouldBlock));
+//! assert_eq!(block!(display_task.read_co2_ppm())?, 800);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::firmware::FirmwareVersion;
+use crate::profile::{DeviceProfile, MhZ19CProfile};
+use crate::{BaseApi, Error, MhZ19C};
+use core::cell::RefCell;
+use embedded_hal::serial::{Read, Write};
+
+#[cfg(feature = "firmware5")]
+use crate::Co2AndTemperature;
+
+/// Mutual exclusion primitive protecting the shared driver.
+pub trait DriverMutex {
+    /// Type of the protected data.
+    type Data;
+
+    /// Creates the mutex protecting `data`.
+    fn create(data: Self::Data) -> Self;
+
+    /// Calls `f` with exclusive access to the protected data, waiting for
+    /// the data to become available if necessary.
+    ///
+    /// May panic or deadlock if the data is already locked by the calling
+    /// thread.
+    fn lock<R, F: FnOnce(&mut Self::Data) -> R>(&self, f: F) -> R;
+
+    /// Calls `f` with exclusive access to the protected data if it is not
+    /// locked, otherwise returns `None` without calling `f`.
+    fn try_lock<R, F: FnOnce(&mut Self::Data) -> R>(&self, f: F) -> Option<R>;
+
+    /// Consumes the mutex and returns the protected data.
+    fn into_inner(self) -> Self::Data;
+}
+
+impl<T> DriverMutex for RefCell<T> {
+    type Data = T;
+
+    fn create(data: T) -> Self {
+        RefCell::new(data)
+    }
+
+    fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        f(&mut self.borrow_mut())
+    }
+
+    fn try_lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        self.try_borrow_mut().ok().map(|mut data| f(&mut data))
+    }
+
+    fn into_inner(self) -> T {
+        RefCell::into_inner(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> DriverMutex for std::sync::Mutex<T> {
+    type Data = T;
+
+    fn create(data: T) -> Self {
+        std::sync::Mutex::new(data)
+    }
+
+    fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        // A panic while holding the lock cannot leave the driver in an
+        // inconsistent state, so poisoning is ignored.
+        let mut guard = std::sync::Mutex::lock(self).unwrap_or_else(|err| err.into_inner());
+        f(&mut guard)
+    }
+
+    fn try_lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        let mut guard = match std::sync::Mutex::try_lock(self) {
+            Ok(guard) => guard,
+            Err(std::sync::TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => return None,
+        };
+        Some(f(&mut guard))
+    }
+
+    fn into_inner(self) -> T {
+        std::sync::Mutex::into_inner(self).unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Driver protected by the [`DriverMutex`] of a [`SharedMhZ19C`] together
+/// with the handle owning the command in progress.
+#[derive(Debug)]
+pub struct Arbitrated<D> {
+    driver: D,
+    owner: Option<usize>,
+    handles: usize,
+}
+
+/// Driver shared between multiple [`SharedHandle`]s.
+///
+/// * `M`: [`DriverMutex`] protecting the driver
+#[derive(Debug)]
+pub struct SharedMhZ19C<M> {
+    mutex: M,
+    release: fn(&M, usize),
+}
+
+impl<'a, U, E, P, M> SharedMhZ19C<M>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+    M: DriverMutex<Data = Arbitrated<MhZ19C<'a, U, E, P>>>,
+{
+    /// Create a new instance sharing the `driver`.
+    pub fn new(driver: MhZ19C<'a, U, E, P>) -> Self {
+        Self {
+            mutex: M::create(Arbitrated {
+                driver,
+                owner: None,
+                handles: 0,
+            }),
+            release: release::<U, E, P, M>,
+        }
+    }
+
+    /// Returns a new handle to issue commands to the shared driver.
+    pub fn handle(&self) -> SharedHandle<'_, M> {
+        let id = self.mutex.lock(|arbitrated| {
+            let id = arbitrated.handles;
+            arbitrated.handles = arbitrated.handles.wrapping_add(1);
+            id
+        });
+        SharedHandle { shared: self, id }
+    }
+
+    /// Returns the driver.
+    ///
+    /// A command might still be in progress.
+    pub fn into_inner(self) -> MhZ19C<'a, U, E, P> {
+        self.mutex.into_inner().driver
+    }
+}
+
+/// Driver shared between tasks on the same thread.
+pub type SharedRefCell<'a, U, E, P = MhZ19CProfile> =
+    SharedMhZ19C<RefCell<Arbitrated<MhZ19C<'a, U, E, P>>>>;
+
+/// Driver shared between threads.
+#[cfg(feature = "std")]
+pub type SharedMutex<'a, U, E, P = MhZ19CProfile> =
+    SharedMhZ19C<std::sync::Mutex<Arbitrated<MhZ19C<'a, U, E, P>>>>;
+
+/// Handle to issue commands to the driver of a [`SharedMhZ19C`].
+///
+/// Dropping a handle while its command is in progress aborts the command (see
+/// [`MhZ19C::abort`]) to allow the other handles to proceed.
+#[derive(Debug)]
+pub struct SharedHandle<'s, M> {
+    shared: &'s SharedMhZ19C<M>,
+    id: usize,
+}
+
+impl<'s, 'a, U, E, P, M> SharedHandle<'s, M>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+    M: DriverMutex<Data = Arbitrated<MhZ19C<'a, U, E, P>>>,
+{
+    /// Reads the CO₂ concentration and temperature.
+    ///
+    /// See [`MhZ19C::read_co2_and_temp`].
+    #[cfg(feature = "firmware5")]
+    pub fn read_co2_and_temp(&mut self) -> nb::Result<Co2AndTemperature, Error<E>> {
+        self.run(MhZ19C::read_co2_and_temp)
+    }
+
+    /// Calls `f` with exclusive access to the driver.
+    ///
+    /// Returns [`nb::Error::WouldBlock`] without calling `f` while a command
+    /// of another handle is in progress. Commands issued within `f` are not
+    /// interleaved with commands of other handles. Thus, a sequence of
+    /// commands (e.g. a calibration routine) can be run by completing the
+    /// commands within `f` with [`nb::block!`].
+    ///
+    /// The driver stays locked while `f` runs. Commands issued through other
+    /// handles within `f` return [`nb::Error::WouldBlock`] and cannot
+    /// complete before `f` returns, so they must not be awaited within `f`.
+    /// Creating or dropping handles within `f` is not supported and panics
+    /// ([`SharedRefCell`]) or deadlocks ([`SharedMutex`]).
+    pub fn with_driver<T, F>(&mut self, f: F) -> nb::Result<T, Error<E>>
+    where
+        F: FnOnce(&mut MhZ19C<'a, U, E, P>) -> nb::Result<T, Error<E>>,
+    {
+        self.run(f)
+    }
+
+    fn run<T, F>(&mut self, f: F) -> nb::Result<T, Error<E>>
+    where
+        F: FnOnce(&mut MhZ19C<'a, U, E, P>) -> nb::Result<T, Error<E>>,
+    {
+        let id = self.id;
+        let result = self.shared.mutex.try_lock(|arbitrated| {
+            match arbitrated.owner {
+                Some(owner) if owner != id && arbitrated.driver.is_busy() => {
+                    return Err(nb::Error::WouldBlock);
+                }
+                _ => arbitrated.owner = Some(id),
+            }
+            let result = f(&mut arbitrated.driver);
+            if !matches!(result, Err(nb::Error::WouldBlock)) {
+                arbitrated.owner = None;
+            }
+            result
+        });
+        // The driver is locked by another thread or, within `with_driver`,
+        // by another handle.
+        result.unwrap_or(Err(nb::Error::WouldBlock))
+    }
+}
+
+impl<'s, M> Drop for SharedHandle<'s, M> {
+    fn drop(&mut self) {
+        (self.shared.release)(&self.shared.mutex, self.id);
+    }
+}
+
+/// Aborts the command in progress if it is owned by the handle `id`.
+fn release<'a, U, E, P, M>(mutex: &M, id: usize)
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+    M: DriverMutex<Data = Arbitrated<MhZ19C<'a, U, E, P>>>,
+{
+    mutex.lock(|arbitrated| {
+        if arbitrated.owner == Some(id) {
+            arbitrated.owner = None;
+            // A UART error will be reported again by the next command.
+            let _ = arbitrated.driver.abort();
+        }
+    });
+}
+
+impl<'s, 'a, U, E, P, M> BaseApi<E> for SharedHandle<'s, M>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+    M: DriverMutex<Data = Arbitrated<MhZ19C<'a, U, E, P>>>,
+{
+    fn read_co2_ppm(&mut self) -> nb::Result<u16, Error<E>> {
+        self.run(MhZ19C::read_co2_ppm)
+    }
+
+    fn get_firmware_version(&mut self) -> nb::Result<FirmwareVersion, Error<E>> {
+        self.run(MhZ19C::get_firmware_version)
+    }
+
+    #[cfg(feature = "calibration")]
+    fn set_self_calibrate(&mut self, enabled: bool) -> nb::Result<(), Error<E>> {
+        self.run(|driver| driver.set_self_calibrate(enabled))
+    }
+
+    #[cfg(feature = "calibration")]
+    fn get_self_calibrate(&mut self) -> nb::Result<bool, Error<E>> {
+        self.run(MhZ19C::get_self_calibrate)
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        self.run(MhZ19C::calibrate_zero)
+    }
+
+    #[cfg(feature = "calibration")]
+    fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.run(|driver| driver.calibrate_span(span_ppm))
+    }
+
+    fn set_detection_range(&mut self, range_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.run(|driver| driver.set_detection_range(range_ppm))
+    }
+
+    fn get_detection_range(&mut self) -> nb::Result<u16, Error<E>> {
+        self.run(MhZ19C::get_detection_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nb::block;
+    use std::string::String;
+    use std::vec::Vec;
+    use test_support::serial_mock::SerialMock;
+    use test_support::{DETECTION_RANGE_5000_RESPONSE, READ_CO2_RESPONSE};
+
+    fn mock_returning(responses: &[&[u8; 9]]) -> SerialMock {
+        let reads: Vec<nb::Result<u8, String>> = responses
+            .iter()
+            .flat_map(|response| {
+                core::iter::once(Err(nb::Error::WouldBlock)).chain(response.iter().copied().map(Ok))
+            })
+            .collect();
+        SerialMock::new(reads, vec![Ok(()); 9 * responses.len()])
+    }
+
+    #[test]
+    fn test_serializes_commands_of_handles() {
+        let shared: SharedRefCell<_, String> = SharedMhZ19C::new(MhZ19C::new(mock_returning(&[
+            &READ_CO2_RESPONSE,
+            &DETECTION_RANGE_5000_RESPONSE,
+        ])));
+        let mut reader = shared.handle();
+        let mut configurator = shared.handle();
+
+        assert_eq!(reader.read_co2_ppm(), Err(nb::Error::WouldBlock));
+        assert_eq!(
+            configurator.get_detection_range(),
+            Err(nb::Error::WouldBlock)
+        );
+        assert_eq!(reader.read_co2_ppm(), Ok(800));
+        assert_eq!(block!(configurator.get_detection_range()), Ok(5000));
+    }
+
+    #[test]
+    fn test_dropping_handle_releases_command() {
+        let shared: SharedRefCell<_, String> = SharedMhZ19C::new(MhZ19C::new(mock_returning(&[
+            &READ_CO2_RESPONSE,
+            &READ_CO2_RESPONSE,
+        ])));
+        let mut reader = shared.handle();
+        let mut other = shared.handle();
+
+        assert_eq!(reader.read_co2_ppm(), Err(nb::Error::WouldBlock));
+        drop(reader);
+        assert_eq!(block!(other.read_co2_ppm()), Ok(800));
+        drop(other);
+        assert!(!shared.into_inner().is_busy());
+    }
+
+    #[test]
+    fn test_with_driver_runs_command_sequence() {
+        let shared: SharedRefCell<_, String> = SharedMhZ19C::new(MhZ19C::new(mock_returning(&[
+            &DETECTION_RANGE_5000_RESPONSE,
+            &READ_CO2_RESPONSE,
+        ])));
+        let mut routine = shared.handle();
+        let mut reader = shared.handle();
+
+        assert_eq!(
+            routine.with_driver(|driver| {
+                let range_ppm = block!(driver.get_detection_range())?;
+                let co2_ppm = block!(driver.read_co2_ppm())?;
+                Ok((range_ppm, co2_ppm))
+            }),
+            Ok((5000, 800))
+        );
+        assert!(!reader.with_driver(|driver| Ok(driver.is_busy())).unwrap());
+    }
+
+    #[test]
+    fn test_nested_command_within_with_driver_would_block() {
+        let shared: SharedRefCell<_, String> =
+            SharedMhZ19C::new(MhZ19C::new(mock_returning(&[&READ_CO2_RESPONSE])));
+        let mut routine = shared.handle();
+        let mut reader = shared.handle();
+
+        assert_eq!(
+            routine.with_driver(|_| Ok(reader.read_co2_ppm())),
+            Ok(Err(nb::Error::WouldBlock))
+        );
+        assert_eq!(block!(reader.read_co2_ppm()), Ok(800));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_mutex_nested_command_within_with_driver_would_block() {
+        let shared: SharedMutex<_, String> =
+            SharedMhZ19C::new(MhZ19C::new(mock_returning(&[&READ_CO2_RESPONSE])));
+        let mut routine = shared.handle();
+        let mut reader = shared.handle();
+
+        assert_eq!(
+            routine.with_driver(|_| Ok(reader.read_co2_ppm())),
+            Ok(Err(nb::Error::WouldBlock))
+        );
+        assert_eq!(block!(reader.read_co2_ppm()), Ok(800));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_mutex() {
+        let shared: SharedMutex<_, String> =
+            SharedMhZ19C::new(MhZ19C::new(mock_returning(&[&READ_CO2_RESPONSE])));
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut reader = shared.handle();
+                assert_eq!(block!(reader.read_co2_ppm()), Ok(800));
+            });
+        });
+    }
+}
@@ -0,0 +1,674 @@
+//! Simulator of the MH-Z19C sensor.
+//!
+//! The [`Simulator`] implements the [`embedded_hal::serial`] traits and
+//! answers the commands written to it like an MH-Z19C sensor would. It can be
+//! passed to the [`crate::MhZ19C`] driver in place of a real UART interface
+//! to develop and test applications without the sensor hardware.
+//!
+//! By default, the simulated readings are constant until changed with
+//! [`Simulator::set_co2_ppm`]. An [`Environment`] can be attached to simulate
+//! realistic dynamics instead (baseline concentration, sensor drift, noise,
+//! warm-up, and the response to emissions and ventilation). Like the
+//! [`crate::power`] module, time is passed as a monotonic timestamp in
+//! milliseconds by the application:
+//!
+//! ```
+//! # use mh_z19c::sim::{Environment, Simulator};
+//! # use mh_z19c::MhZ19C;
+//! # use nb::block;
+//! let environment = Environment::new(420, 0).with_noise(5, 1);
+//! let mut co2sensor = MhZ19C::new(Simulator::new().with_environment(environment));
+//! co2sensor.uart_mut().advance(60_000);
+//! let co2_ppm = block!(co2sensor.read_co2_ppm())?;
+//! assert!((415..=425).contains(&co2_ppm));
+//! # Ok::<(), mh_z19c::Error<core::convert::Infallible>>(())
+//! ```
+
+use crate::capabilities::Capabilities;
+use crate::command::Command;
+use crate::firmware::FirmwareVersion;
+use crate::frame::{checksum, Frame, FRAME_LEN, START_BYTE};
+use crate::power::WARM_UP_MS;
+use core::convert::Infallible;
+use embedded_hal::serial::{Read, Write};
+
+/// Simulated MH-Z19C sensor on the other end of a UART interface.
+///
+/// Like the real sensor, the simulator ignores frames with an invalid
+/// checksum, unknown commands, and the combined CO₂ and temperature command
+/// if the simulated firmware version is below 5. Commands that do not return
+/// a value are not answered. CO₂ readings are limited to the detection range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulator {
+    co2_ppm: u16,
+    temp_celsius: f32,
+    firmware_version: FirmwareVersion,
+    self_calibrate: bool,
+    detection_range_ppm: u16,
+    environment: Option<Environment>,
+    received: [u8; FRAME_LEN],
+    received_len: usize,
+    response: [u8; FRAME_LEN],
+    response_pos: usize,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulator {
+    /// Returns a simulator of a sensor with firmware version 0515 in fresh
+    /// air (400 ppm CO₂ at 20 °C) with self-calibration enabled and a
+    /// detection range of 5000 ppm.
+    pub fn new() -> Self {
+        Self {
+            co2_ppm: 400,
+            temp_celsius: 20.0,
+            firmware_version: FirmwareVersion::new(*b"0515"),
+            self_calibrate: true,
+            detection_range_ppm: 5000,
+            environment: None,
+            received: [0; FRAME_LEN],
+            received_len: 0,
+            response: [0; FRAME_LEN],
+            response_pos: FRAME_LEN,
+        }
+    }
+
+    /// Sets the simulated firmware version.
+    pub fn with_firmware_version(mut self, firmware_version: FirmwareVersion) -> Self {
+        self.firmware_version = firmware_version;
+        self
+    }
+
+    /// Attaches the `environment` determining the simulated CO₂
+    /// concentration (see [`Simulator::advance`]).
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Returns the attached environment, if any.
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+
+    /// Returns the attached environment mutably, e.g. to trigger emissions or
+    /// ventilation.
+    pub fn environment_mut(&mut self) -> Option<&mut Environment> {
+        self.environment.as_mut()
+    }
+
+    /// Advances the attached environment to the timestamp `now_ms` and sets
+    /// the simulated CO₂ concentration to its reading.
+    ///
+    /// Does nothing if no environment is attached.
+    pub fn advance(&mut self, now_ms: u64) {
+        if let Some(environment) = &mut self.environment {
+            self.co2_ppm = environment.reading_ppm(now_ms);
+        }
+    }
+
+    /// Returns the simulated CO₂ concentration in ppm.
+    pub fn co2_ppm(&self) -> u16 {
+        self.co2_ppm
+    }
+
+    /// Sets the simulated CO₂ concentration in ppm.
+    pub fn set_co2_ppm(&mut self, co2_ppm: u16) {
+        self.co2_ppm = co2_ppm;
+    }
+
+    /// Returns the simulated temperature in °C.
+    pub fn temp_celsius(&self) -> f32 {
+        self.temp_celsius
+    }
+
+    /// Sets the simulated temperature in °C.
+    ///
+    /// The temperature is reported with a resolution of 0.01 °C and must be
+    /// within 0 °C and 655.35 °C.
+    pub fn set_temp_celsius(&mut self, temp_celsius: f32) {
+        self.temp_celsius = temp_celsius;
+    }
+
+    /// Returns the simulated firmware version.
+    pub fn firmware_version(&self) -> FirmwareVersion {
+        self.firmware_version
+    }
+
+    /// Returns whether self-calibration is enabled.
+    pub fn self_calibrate(&self) -> bool {
+        self.self_calibrate
+    }
+
+    /// Returns the detection range (upper limit) in ppm.
+    pub fn detection_range_ppm(&self) -> u16 {
+        self.detection_range_ppm
+    }
+
+    fn reading_ppm(&self) -> u16 {
+        self.co2_ppm.min(self.detection_range_ppm)
+    }
+
+    fn calibrate(&mut self, reference_ppm: u16) {
+        self.co2_ppm = reference_ppm;
+        if let Some(environment) = &mut self.environment {
+            environment.calibrate(environment.now_ms(), reference_ppm);
+        }
+    }
+
+    fn receive(&mut self, byte: u8) {
+        if self.received_len == 0 && byte != START_BYTE {
+            return;
+        }
+        if let Some(slot) = self.received.get_mut(self.received_len) {
+            *slot = byte;
+            self.received_len += 1;
+        }
+        if self.received_len >= FRAME_LEN {
+            self.received_len = 0;
+            let frame = Frame::new(self.received);
+            if frame.validate().is_ok() && !frame.is_response() {
+                self.execute(&frame);
+            }
+        }
+    }
+
+    fn execute(&mut self, frame: &Frame) {
+        let [_, _, _, arg0, arg1, _, arg3, arg4, _] = frame.clone().into_inner();
+        let op_code = frame.op_code();
+        let response = match op_code {
+            op if op == Command::ReadCo2.op_code() => {
+                let [high, low] = self.reading_ppm().to_be_bytes();
+                // Firmware below version 5 reports the temperature with an
+                // offset of 40 °C.
+                let temp = if Capabilities::from_firmware_version(self.firmware_version)
+                    .supports(Command::ReadCo2AndTemperature)
+                {
+                    0
+                } else {
+                    (self.temp_celsius + 40.0) as u8
+                };
+                Some([high, low, temp, 0, 0, 0])
+            }
+            op if op == Command::ReadCo2AndTemperature.op_code()
+                && Capabilities::from_firmware_version(self.firmware_version)
+                    .supports(Command::ReadCo2AndTemperature) =>
+            {
+                let [temp_high, temp_low] = ((self.temp_celsius * 100.0) as u16).to_be_bytes();
+                let [high, low] = self.reading_ppm().to_be_bytes();
+                Some([temp_high, temp_low, high, low, 0, 0])
+            }
+            op if op == Command::GetFirmwareVersion.op_code() => {
+                let [a, b, c, d] = self.firmware_version.into_inner();
+                Some([a, b, c, d, 0, 0])
+            }
+            op if op == Command::GetSelfCalibrate.op_code() => {
+                Some([0, 0, 0, 0, 0, self.self_calibrate.into()])
+            }
+            op if op == Command::GetDetectionRange.op_code() => {
+                let [high, low] = self.detection_range_ppm.to_be_bytes();
+                Some([0, 0, high, low, 0, 0])
+            }
+            op if op == Command::SetSelfCalibrate(true).op_code() => {
+                self.self_calibrate = arg0 == 0xa0;
+                None
+            }
+            op if op == Command::CalibrateZero.op_code() => {
+                self.calibrate(400);
+                None
+            }
+            op if op == Command::CalibrateSpan(0).op_code() => {
+                self.calibrate(u16::from_be_bytes([arg0, arg1]));
+                None
+            }
+            op if op == Command::SetDetectionRange(0).op_code() => {
+                self.detection_range_ppm = u16::from_be_bytes([arg3, arg4]);
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(data) = response {
+            self.response[0] = START_BYTE;
+            self.response[1] = op_code;
+            self.response[2..8].copy_from_slice(&data);
+            self.response[8] = checksum(&self.response[1..8]);
+            self.response_pos = 0;
+        }
+    }
+}
+
+impl Read<u8> for Simulator {
+    type Error = Infallible;
+
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        match self.response.get(self.response_pos) {
+            Some(&byte) => {
+                self.response_pos += 1;
+                Ok(byte)
+            }
+            None => Err(nb::Error::WouldBlock),
+        }
+    }
+}
+
+impl Write<u8> for Simulator {
+    type Error = Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.receive(word);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Time constant in milliseconds of the decay of emitted CO₂ towards the
+/// baseline without active ventilation (infiltration of a closed room).
+pub const AIR_EXCHANGE_TIME_CONSTANT_MS: u64 = 2 * 60 * 60_000;
+
+/// Time constant in milliseconds of the decay of emitted CO₂ towards the
+/// baseline during active ventilation (e.g. an open window).
+pub const VENTILATION_TIME_CONSTANT_MS: u64 = 5 * 60_000;
+
+/// Time constant in milliseconds of the sensor's response to concentration
+/// changes, corresponding to the response time (T90) of about 2 minutes
+/// according to the data sheet.
+pub const RESPONSE_TIME_CONSTANT_MS: u64 = 50_000;
+
+/// Maximum step in milliseconds in which the environment is integrated.
+const STEP_MS: u64 = 1000;
+
+/// Simple physical model of the CO₂ concentration around a simulated sensor
+/// and the sensor's response to it.
+///
+/// The concentration consists of a constant baseline (e.g. the outdoor
+/// concentration) and an excess caused by emissions (e.g. occupants of a
+/// room, see [`Environment::emit`]). The excess decays exponentially towards
+/// the baseline with the air exchange time constant, or the shorter
+/// ventilation time constant during ventilation events (see
+/// [`Environment::ventilate`]).
+///
+/// The reading of the sensor follows the concentration with a first-order lag
+/// and deviates from it by a drift growing linearly since the last
+/// calibration and by uniformly distributed noise. During the warm-up after
+/// powering on, the reading ramps linearly from a start value to the actual
+/// reading.
+///
+/// The model is deterministic for a given noise seed and sequence of calls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Environment {
+    baseline_ppm: f32,
+    excess_ppm: f32,
+    sensed_ppm: f32,
+    offset_ppm: f32,
+    drift_ppm_per_day: f32,
+    drift_since_ms: u64,
+    noise_ppm: u16,
+    noise_state: u32,
+    powered_on_at_ms: u64,
+    warm_up_ms: u64,
+    warm_up_start_ppm: u16,
+    air_exchange_time_constant_ms: u64,
+    ventilation_time_constant_ms: u64,
+    ventilating_until_ms: u64,
+    response_time_constant_ms: u64,
+    now_ms: u64,
+}
+
+impl Environment {
+    /// Returns an environment at the `baseline_ppm` concentration around a
+    /// sensor powered on at the timestamp `powered_on_at_ms`.
+    ///
+    /// Initially, there is no drift and no noise, and the reading ramps up
+    /// from 0 ppm during the warm-up time [`WARM_UP_MS`].
+    pub fn new(baseline_ppm: u16, powered_on_at_ms: u64) -> Self {
+        Self {
+            baseline_ppm: baseline_ppm.into(),
+            excess_ppm: 0.0,
+            sensed_ppm: baseline_ppm.into(),
+            offset_ppm: 0.0,
+            drift_ppm_per_day: 0.0,
+            drift_since_ms: powered_on_at_ms,
+            noise_ppm: 0,
+            noise_state: 1,
+            powered_on_at_ms,
+            warm_up_ms: WARM_UP_MS,
+            warm_up_start_ppm: 0,
+            air_exchange_time_constant_ms: AIR_EXCHANGE_TIME_CONSTANT_MS,
+            ventilation_time_constant_ms: VENTILATION_TIME_CONSTANT_MS,
+            ventilating_until_ms: powered_on_at_ms,
+            response_time_constant_ms: RESPONSE_TIME_CONSTANT_MS,
+            now_ms: powered_on_at_ms,
+        }
+    }
+
+    /// Sets the drift of the readings in ppm per day since the last
+    /// calibration. The drift may be negative.
+    pub fn with_drift_ppm_per_day(mut self, drift_ppm_per_day: f32) -> Self {
+        self.drift_ppm_per_day = drift_ppm_per_day;
+        self
+    }
+
+    /// Adds uniformly distributed noise of up to `amplitude_ppm` to the
+    /// readings. The noise is generated by a pseudo-random number generator
+    /// initialized with `seed`.
+    pub fn with_noise(mut self, amplitude_ppm: u16, seed: u32) -> Self {
+        self.noise_ppm = amplitude_ppm;
+        // The xorshift generator requires a non-zero state.
+        self.noise_state = seed.max(1);
+        self
+    }
+
+    /// Sets the warm-up time in milliseconds and the reading at power on.
+    pub fn with_warm_up(mut self, warm_up_ms: u64, start_ppm: u16) -> Self {
+        self.warm_up_ms = warm_up_ms;
+        self.warm_up_start_ppm = start_ppm;
+        self
+    }
+
+    /// Sets the time constant in milliseconds of the decay towards the
+    /// baseline without ventilation (default:
+    /// [`AIR_EXCHANGE_TIME_CONSTANT_MS`]).
+    pub fn with_air_exchange_time_constant_ms(mut self, time_constant_ms: u64) -> Self {
+        self.air_exchange_time_constant_ms = time_constant_ms;
+        self
+    }
+
+    /// Sets the time constant in milliseconds of the decay towards the
+    /// baseline during ventilation (default: [`VENTILATION_TIME_CONSTANT_MS`]).
+    pub fn with_ventilation_time_constant_ms(mut self, time_constant_ms: u64) -> Self {
+        self.ventilation_time_constant_ms = time_constant_ms;
+        self
+    }
+
+    /// Sets the time constant in milliseconds of the sensor's response to
+    /// concentration changes (default: [`RESPONSE_TIME_CONSTANT_MS`]).
+    pub fn with_response_time_constant_ms(mut self, time_constant_ms: u64) -> Self {
+        self.response_time_constant_ms = time_constant_ms;
+        self
+    }
+
+    /// Returns the timestamp the environment has been advanced to.
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    /// Returns the actual CO₂ concentration in ppm around the sensor.
+    pub fn concentration_ppm(&self) -> u16 {
+        to_ppm(self.baseline_ppm + self.excess_ppm)
+    }
+
+    /// Returns whether a ventilation event is active.
+    pub fn is_ventilating(&self) -> bool {
+        self.now_ms < self.ventilating_until_ms
+    }
+
+    /// Advances the environment to the timestamp `now_ms`.
+    ///
+    /// Timestamps before the current one are ignored.
+    pub fn advance(&mut self, now_ms: u64) {
+        while self.now_ms < now_ms {
+            let mut step_ms = (now_ms - self.now_ms).min(STEP_MS);
+            let time_constant_ms = if self.is_ventilating() {
+                step_ms = step_ms.min(self.ventilating_until_ms - self.now_ms);
+                self.ventilation_time_constant_ms
+            } else {
+                self.air_exchange_time_constant_ms
+            };
+            self.excess_ppm -= self.excess_ppm * decay(step_ms, time_constant_ms);
+            let concentration_ppm = self.baseline_ppm + self.excess_ppm;
+            self.sensed_ppm += (concentration_ppm - self.sensed_ppm)
+                * decay(step_ms, self.response_time_constant_ms);
+            self.now_ms += step_ms;
+        }
+    }
+
+    /// Emits `amount_ppm` of CO₂ at the timestamp `now_ms`, raising the
+    /// concentration instantly (e.g. for occupants entering a room).
+    pub fn emit(&mut self, now_ms: u64, amount_ppm: u16) {
+        self.advance(now_ms);
+        self.excess_ppm += f32::from(amount_ppm);
+    }
+
+    /// Starts a ventilation event of `duration_ms` at the timestamp
+    /// `now_ms`, e.g. opening a window.
+    pub fn ventilate(&mut self, now_ms: u64, duration_ms: u64) {
+        self.advance(now_ms);
+        self.ventilating_until_ms = now_ms + duration_ms;
+    }
+
+    /// Calibrates the sensor at the timestamp `now_ms`, so that it reads
+    /// `reference_ppm` regardless of the actual concentration, and resets
+    /// the drift.
+    pub fn calibrate(&mut self, now_ms: u64, reference_ppm: u16) {
+        self.advance(now_ms);
+        self.offset_ppm = f32::from(reference_ppm) - self.sensed_ppm;
+        self.drift_since_ms = self.now_ms;
+    }
+
+    /// Advances the environment to the timestamp `now_ms` and returns the
+    /// reading of the sensor in ppm, including drift and noise.
+    pub fn reading_ppm(&mut self, now_ms: u64) -> u16 {
+        self.advance(now_ms);
+        let drift_days = (self.now_ms - self.drift_since_ms) as f32 / (24 * 60 * 60_000) as f32;
+        let reading_ppm =
+            self.sensed_ppm + self.offset_ppm + self.drift_ppm_per_day * drift_days + self.noise();
+        let warmed_up_ms = self.now_ms.saturating_sub(self.powered_on_at_ms);
+        if warmed_up_ms < self.warm_up_ms {
+            let progress = warmed_up_ms as f32 / self.warm_up_ms as f32;
+            let start_ppm = f32::from(self.warm_up_start_ppm);
+            to_ppm(start_ppm + (reading_ppm - start_ppm) * progress)
+        } else {
+            to_ppm(reading_ppm)
+        }
+    }
+
+    fn noise(&mut self) -> f32 {
+        if self.noise_ppm == 0 {
+            return 0.0;
+        }
+        // xorshift32
+        self.noise_state ^= self.noise_state << 13;
+        self.noise_state ^= self.noise_state >> 17;
+        self.noise_state ^= self.noise_state << 5;
+        let range = 2 * u32::from(self.noise_ppm) + 1;
+        (self.noise_state % range) as f32 - f32::from(self.noise_ppm)
+    }
+}
+
+/// Returns the fraction of the deviation from the equilibrium decaying within
+/// `step_ms` for the `time_constant_ms`, approximating the exponential decay
+/// with an implicit Euler step.
+fn decay(step_ms: u64, time_constant_ms: u64) -> f32 {
+    step_ms as f32 / (time_constant_ms + step_ms) as f32
+}
+
+fn to_ppm(ppm: f32) -> u16 {
+    // Casts saturate, negative values become 0.
+    (ppm + 0.5) as u16
+}
+
+#[cfg(test)]
+#[cfg_attr(
+    not(all(feature = "calibration", feature = "firmware5")),
+    allow(unused_imports)
+)]
+mod tests {
+    use super::*;
+    use crate::{Error, MhZ19C};
+    use nb::block;
+
+    #[test]
+    fn test_read_co2() {
+        let mut simulator = Simulator::new();
+        simulator.set_co2_ppm(800);
+        let mut co2sensor = MhZ19C::new(simulator);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(800));
+    }
+
+    #[test]
+    fn test_read_co2_is_limited_to_detection_range() {
+        let mut simulator = Simulator::new();
+        simulator.set_co2_ppm(6000);
+        let mut co2sensor = MhZ19C::new(simulator);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(5000));
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_read_co2_and_temp() {
+        let mut simulator = Simulator::new();
+        simulator.set_co2_ppm(800);
+        simulator.set_temp_celsius(24.5);
+        let mut co2sensor = MhZ19C::new(simulator);
+        let mut co2sensor = block!(co2sensor.upgrade_to_v5()).unwrap();
+        let reading = block!(co2sensor.read_co2_and_temp()).unwrap();
+        assert_eq!(reading.co2_ppm, 800);
+        assert_eq!(reading.temp_celsius, 24.5);
+    }
+
+    #[test]
+    #[cfg(feature = "firmware5")]
+    fn test_firmware_version() {
+        let simulator = Simulator::new().with_firmware_version(FirmwareVersion::new(*b"0400"));
+        let mut co2sensor = MhZ19C::new(simulator);
+        assert_eq!(
+            block!(co2sensor.get_firmware_version()),
+            Ok(FirmwareVersion::new(*b"0400"))
+        );
+        assert!(matches!(
+            block!(co2sensor.upgrade_to_v5()),
+            Err(Error::NotSupportedByFirmware(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_self_calibrate() {
+        let mut co2sensor = MhZ19C::new(Simulator::new());
+        block!(co2sensor.set_self_calibrate(false)).unwrap();
+        assert_eq!(block!(co2sensor.get_self_calibrate()), Ok(false));
+        block!(co2sensor.set_self_calibrate(true)).unwrap();
+        assert_eq!(block!(co2sensor.get_self_calibrate()), Ok(true));
+    }
+
+    #[test]
+    fn test_detection_range() {
+        let mut co2sensor = MhZ19C::new(Simulator::new());
+        block!(co2sensor.set_detection_range(2000)).unwrap();
+        assert_eq!(block!(co2sensor.get_detection_range()), Ok(2000));
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_calibration() {
+        let mut simulator = Simulator::new();
+        simulator.set_co2_ppm(450);
+        let mut co2sensor = MhZ19C::new(simulator);
+        block!(co2sensor.calibrate_zero()).unwrap();
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(400));
+        block!(co2sensor.calibrate_span(2000)).unwrap();
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(2000));
+    }
+
+    #[test]
+    fn test_ignores_invalid_frames() {
+        let mut simulator = Simulator::new();
+        let mut invalid = Frame::from(Command::ReadCo2).into_inner();
+        invalid[8] = invalid[8].wrapping_add(1);
+        for &byte in [0x12, 0x34].iter().chain(invalid.iter()) {
+            simulator.write(byte).unwrap();
+        }
+        assert_eq!(simulator.read(), Err(nb::Error::WouldBlock));
+
+        for &byte in Frame::from(Command::ReadCo2).as_ref() {
+            simulator.write(byte).unwrap();
+        }
+        assert_eq!(simulator.read(), Ok(START_BYTE));
+    }
+
+    #[test]
+    fn test_environment_warm_up() {
+        let mut environment = Environment::new(400, 1_000).with_warm_up(60_000, 100);
+        assert_eq!(environment.reading_ppm(1_000), 100);
+        assert_eq!(environment.reading_ppm(31_000), 250);
+        assert_eq!(environment.reading_ppm(61_000), 400);
+    }
+
+    #[test]
+    fn test_environment_emission_and_ventilation() {
+        const HOUR_MS: u64 = 60 * 60_000;
+        let mut closed = Environment::new(400, 0).with_warm_up(0, 0);
+        closed.emit(0, 1000);
+        assert_eq!(closed.concentration_ppm(), 1400);
+        // The reading lags behind the concentration.
+        assert_eq!(closed.reading_ppm(0), 400);
+        assert!(closed.reading_ppm(5 * 60_000) > 1300);
+
+        let mut ventilated = closed.clone();
+        ventilated.ventilate(5 * 60_000, 15 * 60_000);
+        assert!(ventilated.is_ventilating());
+        closed.advance(HOUR_MS);
+        ventilated.advance(HOUR_MS);
+        assert!(!ventilated.is_ventilating());
+        assert!(closed.concentration_ppm() > 1000);
+        assert!(ventilated.concentration_ppm() < 500);
+        assert!(ventilated.concentration_ppm() > 400);
+    }
+
+    #[test]
+    fn test_environment_drift_and_calibration() {
+        const DAY_MS: u64 = 24 * 60 * 60_000;
+        let mut environment = Environment::new(400, 0)
+            .with_warm_up(0, 0)
+            .with_drift_ppm_per_day(-10.0);
+        assert_eq!(environment.reading_ppm(DAY_MS), 390);
+        assert_eq!(environment.reading_ppm(3 * DAY_MS), 370);
+        environment.calibrate(3 * DAY_MS, 400);
+        assert_eq!(environment.reading_ppm(3 * DAY_MS), 400);
+        assert_eq!(environment.reading_ppm(4 * DAY_MS), 390);
+    }
+
+    #[test]
+    fn test_environment_noise() {
+        let environment = Environment::new(400, 0)
+            .with_warm_up(0, 0)
+            .with_noise(10, 42);
+        let readings = |mut environment: Environment| {
+            (0..100)
+                .map(|i| environment.reading_ppm(i * 1000))
+                .collect::<std::vec::Vec<_>>()
+        };
+        let noisy = readings(environment.clone());
+        assert!(noisy.iter().all(|ppm| (390..=410).contains(ppm)));
+        assert!(noisy.iter().any(|&ppm| ppm != 400));
+        assert_eq!(readings(environment), noisy);
+    }
+
+    #[test]
+    #[cfg(feature = "calibration")]
+    fn test_simulator_with_environment() {
+        let environment = Environment::new(400, 0)
+            .with_warm_up(0, 0)
+            .with_response_time_constant_ms(0);
+        let mut co2sensor = MhZ19C::new(Simulator::new().with_environment(environment));
+        co2sensor
+            .uart_mut()
+            .environment_mut()
+            .unwrap()
+            .emit(10_000, 100);
+        co2sensor.uart_mut().advance(11_000);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(500));
+        block!(co2sensor.calibrate_zero()).unwrap();
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(400));
+        co2sensor.uart_mut().advance(12_000);
+        assert_eq!(block!(co2sensor.read_co2_ppm()), Ok(400));
+    }
+}
@@ -0,0 +1,177 @@
+//! Timestamped readings (requires the `std` feature).
+//!
+//! ```
+//! # use mh_z19c::timestamp::Timestamped;
+//! # use mh_z19c::Co2AndTemperature;
+//! # use std::time::{Duration, UNIX_EPOCH};
+//! let reading = Timestamped::new(
+//!     UNIX_EPOCH + Duration::from_secs(1_676_637_296),
+//!     Co2AndTemperature::new(800, 24.5),
+//! );
+//! assert_eq!(
+//!     reading.to_json(),
+//!     r#"{"timestamp":"2023-02-17T12:34:56Z","co2_ppm":800,"temp_celsius":24.5}"#
+//! );
+//! ```
+
+use crate::payload::{self, TIMESTAMPED_READING_LEN};
+use crate::Co2AndTemperature;
+use core::convert::TryFrom;
+use std::string::String;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A `value` (e.g. a reading) together with the `time` it was taken.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timestamped<T> {
+    /// Time the value was taken.
+    pub time: SystemTime,
+    /// The timestamped value.
+    pub value: T,
+}
+
+impl<T> Timestamped<T> {
+    /// Returns the `value` timestamped with `time`.
+    pub fn new(time: SystemTime, value: T) -> Self {
+        Self { time, value }
+    }
+
+    /// Returns the `value` timestamped with the current time.
+    pub fn now(value: T) -> Self {
+        Self::new(SystemTime::now(), value)
+    }
+
+    /// Returns the timestamp in seconds since the Unix epoch.
+    pub fn unix_timestamp_s(&self) -> i64 {
+        unix_timestamp_s(self.time)
+    }
+
+    /// Returns the timestamp formatted according to RFC 3339 (see
+    /// [`format_rfc3339`]).
+    pub fn rfc3339(&self) -> String {
+        format_rfc3339(self.time)
+    }
+}
+
+impl Timestamped<Co2AndTemperature> {
+    /// Serializes the reading as JSON object with the keys `timestamp`
+    /// (RFC 3339), `co2_ppm`, and `temp_celsius`.
+    pub fn to_json(&self) -> String {
+        std::format!(
+            r#"{{"timestamp":"{}","co2_ppm":{},"temp_celsius":{}}}"#,
+            self.rfc3339(),
+            self.value.co2_ppm,
+            self.value.temp_celsius
+        )
+    }
+
+    /// Serializes the reading as comma-separated values in the order
+    /// timestamp (RFC 3339), CO₂ concentration in ppm, and temperature in °C.
+    pub fn to_csv(&self) -> String {
+        std::format!(
+            "{},{},{}",
+            self.rfc3339(),
+            self.value.co2_ppm,
+            self.value.temp_celsius
+        )
+    }
+
+    /// Encodes the reading with [`payload::encode_timestamped`] using the
+    /// seconds since the Unix epoch as timestamp.
+    ///
+    /// Returns `None` if the timestamp cannot be represented as `u32`.
+    pub fn to_payload(&self) -> Option<[u8; TIMESTAMPED_READING_LEN]> {
+        let timestamp_s = u32::try_from(self.unix_timestamp_s()).ok()?;
+        Some(payload::encode_timestamped(timestamp_s, &self.value))
+    }
+
+    /// Decodes a reading encoded with [`Self::to_payload`].
+    pub fn from_payload(bytes: &[u8; TIMESTAMPED_READING_LEN]) -> Self {
+        let (timestamp_s, reading) = payload::decode_timestamped(bytes);
+        Self::new(
+            UNIX_EPOCH + std::time::Duration::from_secs(u64::from(timestamp_s)),
+            reading,
+        )
+    }
+}
+
+fn unix_timestamp_s(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_else(|err| -(err.duration().as_secs() as i64))
+}
+
+/// Formats `time` as RFC 3339 timestamp in UTC with second precision (e.g.
+/// `2023-02-17T12:34:56Z`).
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let secs = unix_timestamp_s(time);
+    let (days, secs_of_day) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    std::format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// Converts days since the Unix epoch into a (year, month, day) date of the
+/// proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Algorithm from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn reading() -> Timestamped<Co2AndTemperature> {
+        Timestamped::new(
+            UNIX_EPOCH + Duration::from_secs(1_676_637_296),
+            Co2AndTemperature::new(800, 24.5),
+        )
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH + Duration::from_secs(1_676_637_296)),
+            "2023-02-17T12:34:56Z"
+        );
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH + Duration::from_secs(951_825_600)),
+            "2000-02-29T12:00:00Z"
+        );
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH - Duration::from_secs(1)),
+            "1969-12-31T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        assert_eq!(reading().to_csv(), "2023-02-17T12:34:56Z,800,24.5");
+    }
+
+    #[test]
+    fn test_payload_round_trip() {
+        let reading = reading();
+        let bytes = reading.to_payload().unwrap();
+        assert_eq!(Timestamped::from_payload(&bytes), reading);
+
+        let before_epoch = Timestamped::new(UNIX_EPOCH - Duration::from_secs(1), reading.value);
+        assert_eq!(before_epoch.unix_timestamp_s(), -1);
+        assert_eq!(before_epoch.to_payload(), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Typestate layer enforcing the warm-up and calibration preconditions.
+//!
+//! The sensor needs to preheat after powering on before providing valid
+//! readings, and calibrating it requires a stable environment for at least
+//! 20 minutes. The [`Sensor`] wrapper encodes these preconditions in its
+//! state type parameter, so that misuse is rejected at compile time:
+//!
+//! * [`WarmingUp`]: The sensor is preheating, no commands can be issued.
+//! * [`Ready`]: The sensor provides readings. The readings are tracked to
+//!   verify the stability of the environment.
+//! * [`Stable`]: The readings have been stable for at least [`STABLE_MS`],
+//!   calibration commands can be issued.
+//!
+//! The stability is only verified by readings, so readings have to be taken
+//! at least every [`MAX_READING_INTERVAL_MS`] in the [`Ready`] state.
+//!
+//! Like the [`crate::power`] module, time is passed as a monotonic timestamp
+//! in milliseconds by the application.
+//!
+//! ```
+//! # use mh_z19c::sim::Simulator;
+//! # use mh_z19c::typestate::Sensor;
+//! # use mh_z19c::MhZ19C;
+//! # use nb::block;
+//! let sensor = Sensor::warming_up(MhZ19C::new(Simulator::new()), 0);
+//! let mut sensor = sensor.ready(60_000).unwrap();
+//! let co2_ppm = block!(sensor.read_co2_ppm(60_000))?;
+//! # Ok::<(), mh_z19c::Error<core::convert::Infallible>>(())
+//! ```
+
+use crate::power::WARM_UP_MS;
+use crate::profile::DeviceProfile;
+use crate::{Error, MhZ19C};
+use embedded_hal::serial::{Read, Write};
+
+/// Time in milliseconds the readings have to be stable before calibrating.
+pub const STABLE_MS: u64 = 20 * 60_000;
+
+/// Maximum deviation in ppm of readings considered stable.
+pub const STABILITY_TOLERANCE_PPM: u16 = 20;
+
+/// Maximum time in milliseconds between readings verifying the stability.
+/// A longer gap starts a new stable period.
+pub const MAX_READING_INTERVAL_MS: u64 = 60_000;
+
+/// State of a preheating sensor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WarmingUp {
+    powered_on_at_ms: u64,
+}
+
+/// State of a sensor providing readings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ready {
+    reference_ppm: Option<u16>,
+    stable_since_ms: u64,
+    last_reading_ms: u64,
+}
+
+impl Ready {
+    fn new(now_ms: u64) -> Self {
+        Self {
+            reference_ppm: None,
+            stable_since_ms: now_ms,
+            last_reading_ms: now_ms,
+        }
+    }
+}
+
+/// State of a sensor in a verified stable environment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stable {
+    _private: (),
+}
+
+/// Driver wrapper with the state `S` enforcing the preconditions of the
+/// sensor.
+#[derive(Debug)]
+pub struct Sensor<D, S> {
+    driver: D,
+    state: S,
+}
+
+impl<D, S> Sensor<D, S> {
+    /// Returns the driver.
+    pub fn into_inner(self) -> D {
+        self.driver
+    }
+}
+
+impl<'a, U, E, P> Sensor<MhZ19C<'a, U, E, P>, WarmingUp>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Wraps the `driver` of a sensor that has been powered on at the
+    /// timestamp `powered_on_at_ms`.
+    pub fn warming_up(driver: MhZ19C<'a, U, E, P>, powered_on_at_ms: u64) -> Self {
+        Self {
+            driver,
+            state: WarmingUp { powered_on_at_ms },
+        }
+    }
+
+    /// Returns the remaining warm-up time in milliseconds at the timestamp
+    /// `now_ms`.
+    pub fn remaining_warm_up_ms(&self, now_ms: u64) -> u64 {
+        self.state
+            .powered_on_at_ms
+            .saturating_add(WARM_UP_MS)
+            .saturating_sub(now_ms)
+    }
+
+    /// Transitions to the [`Ready`] state if the warm-up has completed at the
+    /// timestamp `now_ms`. Otherwise, the sensor is returned unchanged.
+    #[allow(clippy::result_large_err)]
+    pub fn ready(self, now_ms: u64) -> Result<Sensor<MhZ19C<'a, U, E, P>, Ready>, Self> {
+        if self.remaining_warm_up_ms(now_ms) > 0 {
+            return Err(self);
+        }
+        Ok(Sensor {
+            driver: self.driver,
+            state: Ready::new(now_ms),
+        })
+    }
+}
+
+impl<'a, U, E, P> Sensor<MhZ19C<'a, U, E, P>, Ready>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Reads and returns the CO₂ concentration in parts-per-million (ppm)
+    /// at the timestamp `now_ms`.
+    ///
+    /// A reading deviating by more than [`STABILITY_TOLERANCE_PPM`] from the
+    /// first reading of the current stable period, or taken more than
+    /// [`MAX_READING_INTERVAL_MS`] after the previous reading, starts a new
+    /// stable period.
+    pub fn read_co2_ppm(&mut self, now_ms: u64) -> nb::Result<u16, Error<E>> {
+        let co2_ppm = self.driver.read_co2_ppm()?;
+        let gap_ms = now_ms.saturating_sub(self.state.last_reading_ms);
+        match self.state.reference_ppm {
+            Some(reference_ppm)
+                if co2_ppm.abs_diff(reference_ppm) <= STABILITY_TOLERANCE_PPM
+                    && gap_ms <= MAX_READING_INTERVAL_MS => {}
+            _ => {
+                self.state.reference_ppm = Some(co2_ppm);
+                self.state.stable_since_ms = now_ms;
+            }
+        }
+        self.state.last_reading_ms = now_ms;
+        Ok(co2_ppm)
+    }
+
+    /// Returns the time in milliseconds covered by stable readings at the
+    /// timestamp `now_ms`, i.e. from the first to the last reading of the
+    /// current stable period.
+    ///
+    /// Returns `None` if no reading has been taken yet or the last reading
+    /// has been taken more than [`MAX_READING_INTERVAL_MS`] before `now_ms`.
+    pub fn stable_for_ms(&self, now_ms: u64) -> Option<u64> {
+        let Ready {
+            reference_ppm,
+            stable_since_ms,
+            last_reading_ms,
+        } = self.state;
+        reference_ppm
+            .filter(|_| now_ms.saturating_sub(last_reading_ms) <= MAX_READING_INTERVAL_MS)
+            .map(|_| last_reading_ms.saturating_sub(stable_since_ms))
+    }
+
+    /// Transitions to the [`Stable`] state if the readings have been stable
+    /// for at least [`STABLE_MS`] at the timestamp `now_ms` (see
+    /// [`Sensor::stable_for_ms`]). Otherwise, the sensor is returned
+    /// unchanged.
+    #[allow(clippy::result_large_err)]
+    pub fn stable(self, now_ms: u64) -> Result<Sensor<MhZ19C<'a, U, E, P>, Stable>, Self> {
+        match self.stable_for_ms(now_ms) {
+            Some(stable_ms) if stable_ms >= STABLE_MS => Ok(Sensor {
+                driver: self.driver,
+                state: Stable { _private: () },
+            }),
+            _ => Err(self),
+        }
+    }
+}
+
+impl<'a, U, E, P> Sensor<MhZ19C<'a, U, E, P>, Stable>
+where
+    U: Read<u8, Error = E> + Write<u8, Error = E>,
+    P: DeviceProfile,
+{
+    /// Calibrates the zero point (400 ppm) to the current reading.
+    ///
+    /// See [`crate::BaseApi::calibrate_zero`].
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_zero(&mut self) -> nb::Result<(), Error<E>> {
+        self.driver.calibrate_zero()
+    }
+
+    /// Calibrates the span point to the current reading.
+    ///
+    /// See [`crate::BaseApi::calibrate_span`].
+    #[cfg(feature = "calibration")]
+    pub fn calibrate_span(&mut self, span_ppm: u16) -> nb::Result<(), Error<E>> {
+        self.driver.calibrate_span(span_ppm)
+    }
+
+    /// Transitions back to the [`Ready`] state at the timestamp `now_ms`,
+    /// e.g. after a calibration or to take further readings. The stability
+    /// has to be verified again before the next calibration.
+    pub fn into_ready(self, now_ms: u64) -> Sensor<MhZ19C<'a, U, E, P>, Ready> {
+        Sensor {
+            driver: self.driver,
+            state: Ready::new(now_ms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::Simulator;
+    use nb::block;
+
+    type ReadySensor = Sensor<MhZ19C<'static, Simulator, core::convert::Infallible>, Ready>;
+
+    fn ready_sensor() -> ReadySensor {
+        let sensor = Sensor::warming_up(MhZ19C::new(Simulator::new()), 1_000);
+        assert_eq!(sensor.remaining_warm_up_ms(31_000), 30_000);
+        let sensor = sensor.ready(31_000).unwrap_err();
+        sensor.ready(61_000).unwrap()
+    }
+
+    /// Takes readings every [`MAX_READING_INTERVAL_MS`] from `from_ms` to
+    /// `to_ms` (both inclusive).
+    fn read_until(sensor: &mut ReadySensor, from_ms: u64, to_ms: u64) {
+        let mut now_ms = from_ms;
+        while now_ms < to_ms {
+            block!(sensor.read_co2_ppm(now_ms)).unwrap();
+            now_ms += MAX_READING_INTERVAL_MS;
+        }
+        block!(sensor.read_co2_ppm(to_ms)).unwrap();
+    }
+
+    #[test]
+    fn test_stable_after_stable_readings() {
+        let mut sensor = ready_sensor();
+        assert_eq!(sensor.stable_for_ms(61_000), None);
+        read_until(&mut sensor, 61_000, 61_000 + STABLE_MS - 1);
+        let mut sensor = sensor.stable(61_000 + STABLE_MS - 1).unwrap_err();
+        sensor
+            .driver
+            .uart
+            .set_co2_ppm(400 + STABILITY_TOLERANCE_PPM);
+        assert_eq!(block!(sensor.read_co2_ppm(61_000 + STABLE_MS)), Ok(420));
+        assert_eq!(sensor.stable_for_ms(61_000 + STABLE_MS), Some(STABLE_MS));
+        assert!(sensor.stable(61_000 + STABLE_MS).is_ok());
+    }
+
+    #[test]
+    fn test_single_reading_is_not_stable() {
+        let mut sensor = ready_sensor();
+        assert_eq!(block!(sensor.read_co2_ppm(61_000)), Ok(400));
+        assert_eq!(sensor.stable_for_ms(61_000 + STABLE_MS), None);
+        assert!(sensor.stable(61_000 + STABLE_MS).is_err());
+    }
+
+    #[test]
+    fn test_outdated_readings_are_not_stable() {
+        let mut sensor = ready_sensor();
+        read_until(&mut sensor, 61_000, 61_000 + STABLE_MS);
+        let now_ms = 61_000 + STABLE_MS + MAX_READING_INTERVAL_MS;
+        assert_eq!(sensor.stable_for_ms(now_ms), Some(STABLE_MS));
+        assert!(sensor.stable(now_ms + 1).is_err());
+    }
+
+    #[test]
+    fn test_gap_between_readings_restarts_stable_period() {
+        let mut sensor = ready_sensor();
+        read_until(&mut sensor, 61_000, 61_000 + STABLE_MS / 2);
+        let resumed_ms = 61_000 + STABLE_MS / 2 + MAX_READING_INTERVAL_MS + 1;
+        read_until(&mut sensor, resumed_ms, 61_000 + STABLE_MS);
+        assert_eq!(
+            sensor.stable_for_ms(61_000 + STABLE_MS),
+            Some(61_000 + STABLE_MS - resumed_ms)
+        );
+        assert!(sensor.stable(61_000 + STABLE_MS).is_err());
+    }
+
+    #[test]
+    fn test_deviating_reading_restarts_stable_period() {
+        let mut sensor = ready_sensor();
+        assert_eq!(block!(sensor.read_co2_ppm(61_000)), Ok(400));
+        sensor
+            .driver
+            .uart
+            .set_co2_ppm(400 + STABILITY_TOLERANCE_PPM + 1);
+        read_until(&mut sensor, 100_000, 100_000 + STABLE_MS - 1);
+        assert_eq!(
+            sensor.stable_for_ms(100_000 + STABLE_MS - 1),
+            Some(STABLE_MS - 1)
+        );
+        assert!(sensor.stable(100_000 + STABLE_MS - 1).is_err());
+    }
+
+    #[test]
+    fn test_remaining_warm_up_saturates() {
+        let sensor = Sensor::warming_up(MhZ19C::new(Simulator::new()), u64::MAX - 1);
+        assert_eq!(sensor.remaining_warm_up_ms(u64::MAX - 1), 1);
+        assert!(sensor.ready(u64::MAX).is_ok());
+    }
+
+    #[cfg(feature = "calibration")]
+    #[test]
+    fn test_calibrate_when_stable() {
+        let mut sensor = ready_sensor();
+        sensor.driver.uart.set_co2_ppm(450);
+        read_until(&mut sensor, 61_000, 61_000 + STABLE_MS);
+        let mut sensor = sensor.stable(61_000 + STABLE_MS).unwrap();
+        assert_eq!(block!(sensor.calibrate_zero()), Ok(()));
+        let mut sensor = sensor.into_ready(61_000 + STABLE_MS);
+        assert_eq!(sensor.stable_for_ms(61_000 + STABLE_MS), None);
+        assert_eq!(block!(sensor.read_co2_ppm(61_000 + STABLE_MS)), Ok(400));
+    }
+}
@@ -0,0 +1,25 @@
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use std::vec::Vec;
+
+#[derive(Debug, Default)]
+pub struct DelayMock {
+    pub delays_us: Vec<u32>,
+}
+
+impl DelayMock {
+    pub fn total_ms(&self) -> u32 {
+        self.delays_us.iter().sum::<u32>() / 1000
+    }
+}
+
+impl DelayMs<u16> for DelayMock {
+    fn delay_ms(&mut self, ms: u16) {
+        self.delays_us.push(u32::from(ms) * 1000);
+    }
+}
+
+impl DelayUs<u32> for DelayMock {
+    fn delay_us(&mut self, us: u32) {
+        self.delays_us.push(us);
+    }
+}
@@ -1,14 +1,26 @@
 use crate::serial_mock::SerialMock;
 
+pub mod delay_mock;
+pub mod pin_mock;
 pub mod serial_mock;
 
+pub static DETECTION_RANGE_5000_RESPONSE: [u8; 9] =
+    [0xff, 0x9b, 0x00, 0x00, 0x13, 0x88, 0x00, 0x00, 0xca];
 pub static FIRMWARE_0400_RESPONSE: [u8; 9] = [0xff, 0xa0, 0x30, 0x34, 0x30, 0x30, 0x00, 0x00, 0x9c];
 pub static FIRMWARE_0515_RESPONSE: [u8; 9] = [0xff, 0xa0, 0x30, 0x35, 0x31, 0x35, 0x00, 0x00, 0x95];
 pub static READ_CO2_RESPONSE: [u8; 9] = [0xff, 0x86, 0x03, 0x20, 0x12, 0x34, 0x56, 0x78, 0x43];
+pub static READ_CO2_5000_RESPONSE: [u8; 9] = [0xff, 0x86, 0x13, 0x88, 0x00, 0x00, 0x00, 0x00, 0xdf];
 pub static READ_CO2_AND_TEMPERATURE_RESPONSE: [u8; 9] =
     [0xff, 0x85, 0x09, 0x60, 0x03, 0x20, 0x56, 0x78, 0x21];
+pub static SELF_CALIBRATE_ON_RESPONSE: [u8; 9] =
+    [0xff, 0x7d, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x82];
+pub static CALIBRATE_SPAN_2000_COMMAND: [u8; 9] =
+    [0xff, 0x01, 0x88, 0x07, 0xd0, 0x00, 0x00, 0x00, 0xa0];
+pub static CALIBRATE_ZERO_COMMAND: [u8; 9] = [0xff, 0x01, 0x87, 0x00, 0x00, 0x00, 0x00, 0x00, 0x78];
 pub static SELF_CALIBRATE_ON_COMMAND: [u8; 9] =
     [0xff, 0x01, 0x79, 0xa0, 0x00, 0x00, 0x00, 0x00, 0xe6];
+pub static SET_DETECTION_RANGE_5000_COMMAND: [u8; 9] =
+    [0xff, 0x01, 0x99, 0x00, 0x00, 0x00, 0x13, 0x88, 0xcb];
 
 pub fn create_serial_mock_returning(read_data: &[u8]) -> SerialMock {
     SerialMock::new(
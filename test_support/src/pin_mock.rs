@@ -0,0 +1,25 @@
+use embedded_hal::digital::v2::OutputPin;
+use std::vec::Vec;
+
+#[derive(Debug, Default)]
+pub struct PinMock {
+    pub levels: Vec<bool>,
+    pub fail_low: bool,
+}
+
+impl OutputPin for PinMock {
+    type Error = ();
+
+    fn set_low(&mut self) -> Result<(), ()> {
+        if self.fail_low {
+            return Err(());
+        }
+        self.levels.push(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), ()> {
+        self.levels.push(true);
+        Ok(())
+    }
+}
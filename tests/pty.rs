@@ -0,0 +1,184 @@
+//! End-to-end tests of the [`std::io`] adapter over a pseudo terminal (PTY).
+//!
+//! The simulator is attached to the master side of the PTY, while the driver
+//! communicates through the slave device configured with `stty` like a
+//! serial device.
+#![cfg(all(feature = "std", feature = "firmware5", target_os = "linux"))]
+
+use embedded_hal::serial::{Read, Write};
+use mh_z19c::baud::detect_baud_rate;
+use mh_z19c::firmware::FirmwareVersion;
+use mh_z19c::io::IoUart;
+use mh_z19c::sim::Simulator;
+use mh_z19c::{Co2AndTemperature, MhZ19C};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read as _, Write as _};
+use std::os::raw::{c_char, c_int};
+use std::os::unix::io::FromRawFd;
+use std::process::Command;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const O_RDWR: c_int = 0o2;
+const O_NOCTTY: c_int = 0o400;
+
+/// Time to wait for a response before failing the test.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+extern "C" {
+    fn posix_openpt(flags: c_int) -> c_int;
+    fn grantpt(fd: c_int) -> c_int;
+    fn unlockpt(fd: c_int) -> c_int;
+    fn ptsname_r(fd: c_int, buf: *mut c_char, buflen: usize) -> c_int;
+}
+
+/// PTY with the simulator attached to the master side.
+struct SimulatedPty {
+    slave_path: String,
+    // Keeps the slave side open while the driver reopens the device, as
+    // reads on the master side fail once all slave descriptors are closed.
+    slave: File,
+    simulator: JoinHandle<()>,
+}
+
+impl SimulatedPty {
+    fn open(mut simulator: Simulator) -> io::Result<Self> {
+        // SAFETY: The returned descriptor is checked and owned by `master`.
+        let fd = unsafe { posix_openpt(O_RDWR | O_NOCTTY) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is a valid descriptor not owned by anything else.
+        let mut master = unsafe { File::from_raw_fd(fd) };
+        let mut name = [0 as c_char; 64];
+        // SAFETY: `fd` is a valid PTY master and `name` outlives the call.
+        let result = unsafe {
+            if grantpt(fd) != 0 || unlockpt(fd) != 0 {
+                -1
+            } else {
+                ptsname_r(fd, name.as_mut_ptr(), name.len())
+            }
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let slave_path: String = name
+            .iter()
+            .take_while(|&&c| c != 0)
+            .map(|&c| c as u8 as char)
+            .collect();
+        let slave = open_slave(&slave_path)?;
+        configure(&slave_path, 9600)?;
+
+        let simulator = thread::spawn(move || {
+            let mut buf = [0u8; 64];
+            // Ends with an error once the slave side has been closed.
+            while let Ok(len @ 1..) = master.read(&mut buf) {
+                for &byte in buf.iter().take(len) {
+                    let _ = simulator.write(byte);
+                }
+                let response: Vec<u8> = std::iter::from_fn(|| simulator.read().ok()).collect();
+                if master.write_all(&response).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            slave_path,
+            slave,
+            simulator,
+        })
+    }
+
+    fn open_uart(&self) -> io::Result<IoUart<File>> {
+        open_slave(&self.slave_path).map(IoUart::new)
+    }
+
+    fn close(self, uart: IoUart<File>) {
+        drop(uart);
+        drop(self.slave);
+        self.simulator.join().unwrap();
+    }
+}
+
+fn open_slave(path: &str) -> io::Result<File> {
+    OpenOptions::new().read(true).write(true).open(path)
+}
+
+/// Configures the PTY slave like a serial device with a read timeout of
+/// 100 ms.
+fn configure(path: &str, baud_rate: u32) -> io::Result<()> {
+    let status = Command::new("stty")
+        .args(["-F", path, &baud_rate.to_string()])
+        .args(["raw", "-echo", "cs8", "-cstopb", "-parenb", "clocal"])
+        .args(["min", "0", "time", "1"])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("failed to configure {path}")))
+    }
+}
+
+fn wait<T, E: std::fmt::Debug>(mut f: impl FnMut() -> nb::Result<T, E>) -> T {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        match f() {
+            Ok(value) => return value,
+            Err(nb::Error::Other(err)) => panic!("{:?}", err),
+            Err(nb::Error::WouldBlock) => assert!(Instant::now() < deadline, "timed out"),
+        }
+    }
+}
+
+#[test]
+fn test_read_over_pty() {
+    let mut simulator = Simulator::new();
+    simulator.set_co2_ppm(812);
+    simulator.set_temp_celsius(23.5);
+    let pty = SimulatedPty::open(simulator).unwrap();
+    let mut co2sensor = MhZ19C::new(pty.open_uart().unwrap());
+
+    assert_eq!(
+        wait(|| co2sensor.get_firmware_version()),
+        FirmwareVersion::new(*b"0515")
+    );
+    assert_eq!(wait(|| co2sensor.read_co2_ppm()), 812);
+    assert_eq!(
+        wait(|| co2sensor.read_co2_and_temp()),
+        Co2AndTemperature::new(812, 23.5)
+    );
+
+    pty.close(co2sensor.into_inner());
+}
+
+#[test]
+fn test_commands_without_response_over_pty() {
+    let pty = SimulatedPty::open(Simulator::new()).unwrap();
+    let mut co2sensor = MhZ19C::new(pty.open_uart().unwrap());
+
+    wait(|| co2sensor.set_detection_range(2000));
+    assert_eq!(wait(|| co2sensor.get_detection_range()), 2000);
+
+    pty.close(co2sensor.into_inner());
+}
+
+#[test]
+fn test_detect_baud_rate_over_pty() {
+    let pty = SimulatedPty::open(Simulator::new()).unwrap();
+
+    let (baud_rate, co2sensor) = detect_baud_rate(&[9600, 19200], |baud_rate| {
+        configure(&pty.slave_path, baud_rate)?;
+        pty.open_uart()
+    })
+    .unwrap()
+    .expect("sensor not detected");
+    assert_eq!(baud_rate, 9600);
+    assert_eq!(
+        co2sensor.firmware_version(),
+        Some(FirmwareVersion::new(*b"0515"))
+    );
+
+    pty.close(co2sensor.into_inner());
+}
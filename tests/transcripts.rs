@@ -0,0 +1,209 @@
+//! Replays the byte transcripts in `tests/transcripts` through the driver.
+//!
+//! See `tests/transcripts/README.md` for the transcript format.
+#![cfg(all(feature = "calibration", feature = "firmware5"))]
+
+use mh_z19c::frame::Validation;
+use mh_z19c::{Error, MhZ19C};
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+use test_support::serial_mock::SerialMock;
+
+/// Number of polls after which a call is considered to hang, e.g. because
+/// the transcript lacks received bytes.
+const MAX_POLLS: usize = 1000;
+
+#[derive(Debug, Default)]
+struct Call {
+    line: usize,
+    method: String,
+    argument: Option<String>,
+    tx: Vec<u8>,
+    rx: Vec<Vec<u8>>,
+    expected: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Transcript {
+    lenient: bool,
+    echo: bool,
+    calls: Vec<Call>,
+}
+
+fn parse_hex(line: usize, bytes: &str) -> Vec<u8> {
+    bytes
+        .split_whitespace()
+        .map(|byte| {
+            u8::from_str_radix(byte, 16)
+                .unwrap_or_else(|_| panic!("line {}: invalid byte {:?}", line, byte))
+        })
+        .collect()
+}
+
+fn parse(content: &str) -> Transcript {
+    let mut transcript = Transcript::default();
+    for (index, line) in content.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (directive, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        if directive == "call" {
+            let mut words = rest.split_whitespace();
+            transcript.calls.push(Call {
+                line: line_no,
+                method: words.next().expect("missing method").to_string(),
+                argument: words.next().map(str::to_string),
+                ..Call::default()
+            });
+            continue;
+        }
+        if directive == "config" {
+            match rest {
+                "lenient" => transcript.lenient = true,
+                "echo" => transcript.echo = true,
+                _ => panic!("line {}: unknown config {:?}", line_no, rest),
+            }
+            continue;
+        }
+        let call = transcript
+            .calls
+            .last_mut()
+            .unwrap_or_else(|| panic!("line {}: {} before first call", line_no, directive));
+        match directive {
+            "tx" => call.tx.extend(parse_hex(line_no, rest)),
+            "rx" => call.rx.push(parse_hex(line_no, rest)),
+            "expect" => call.expected = Some(rest.to_string()),
+            _ => panic!("line {}: unknown directive {:?}", line_no, directive),
+        }
+    }
+    transcript
+}
+
+fn serial_mock(transcript: &Transcript) -> SerialMock {
+    // Each burst is preceded by a `WouldBlock`, so that bytes are not
+    // received before the corresponding command has been sent.
+    let reads = transcript
+        .calls
+        .iter()
+        .flat_map(|call| &call.rx)
+        .flat_map(|burst| {
+            std::iter::once(Err(nb::Error::WouldBlock)).chain(burst.iter().map(|&byte| Ok(byte)))
+        })
+        .collect();
+    let tx_len: usize = transcript.calls.iter().map(|call| call.tx.len()).sum();
+    SerialMock::new(reads, vec![Ok(()); tx_len])
+}
+
+fn poll<T, F>(mut f: F) -> Result<T, Error<String>>
+where
+    F: FnMut() -> nb::Result<T, Error<String>>,
+{
+    for _ in 0..MAX_POLLS {
+        match f() {
+            Err(nb::Error::WouldBlock) => continue,
+            Err(nb::Error::Other(err)) => return Err(err),
+            Ok(value) => return Ok(value),
+        }
+    }
+    panic!("call did not complete");
+}
+
+fn format_result<T: Debug>(result: Result<T, Error<String>>) -> String {
+    match result {
+        Ok(value) => format!("ok {:?}", value),
+        Err(err) => format!("err {:?}", err),
+    }
+}
+
+fn argument<T: std::str::FromStr>(call: &Call) -> T {
+    call.argument
+        .as_deref()
+        .and_then(|argument| argument.parse().ok())
+        .unwrap_or_else(|| panic!("line {}: missing or invalid argument", call.line))
+}
+
+fn execute(co2sensor: &mut MhZ19C<SerialMock, String>, call: &Call) -> String {
+    match call.method.as_str() {
+        "read_co2_ppm" => format_result(poll(|| co2sensor.read_co2_ppm())),
+        "read_co2_and_temp" => format_result(poll(|| co2sensor.read_co2_and_temp())),
+        "read_co2_and_temp_any_firmware" => {
+            format_result(poll(|| co2sensor.read_co2_and_temp_any_firmware()))
+        }
+        "get_firmware_version" => match poll(|| co2sensor.get_firmware_version()) {
+            Ok(version) => format!("ok {}", version),
+            Err(err) => format_result::<()>(Err(err)),
+        },
+        "get_detection_range" => format_result(poll(|| co2sensor.get_detection_range())),
+        "set_detection_range" => {
+            let range_ppm = argument(call);
+            format_result(poll(|| co2sensor.set_detection_range(range_ppm)))
+        }
+        "get_self_calibrate" => format_result(poll(|| co2sensor.get_self_calibrate())),
+        "set_self_calibrate" => {
+            let enabled = argument::<String>(call) == "on";
+            format_result(poll(|| co2sensor.set_self_calibrate(enabled)))
+        }
+        "calibrate_zero" => format_result(poll(|| co2sensor.calibrate_zero())),
+        "calibrate_span" => {
+            let span_ppm = argument(call);
+            format_result(poll(|| co2sensor.calibrate_span(span_ppm)))
+        }
+        "self_test" => format_result(poll(|| co2sensor.self_test())),
+        method => panic!("line {}: unknown method {:?}", call.line, method),
+    }
+}
+
+fn replay(path: &Path) {
+    let content = fs::read_to_string(path).unwrap();
+    let transcript = parse(&content);
+    let validation = if transcript.lenient {
+        Validation::LENIENT
+    } else {
+        Validation::STRICT
+    };
+    let mut co2sensor = MhZ19C::builder()
+        .validation(validation)
+        .echo_suppression(transcript.echo)
+        .build(serial_mock(&transcript));
+
+    for call in &transcript.calls {
+        let sent_before = co2sensor.uart().write_buf.len();
+        let result = execute(&mut co2sensor, call);
+        let sent = co2sensor.uart().write_buf.get(sent_before..).unwrap();
+        assert_eq!(
+            sent,
+            &call.tx[..],
+            "{}:{}: transmitted bytes",
+            path.display(),
+            call.line
+        );
+        if let Some(expected) = &call.expected {
+            assert_eq!(
+                &result,
+                expected,
+                "{}:{}: result",
+                path.display(),
+                call.line
+            );
+        }
+    }
+}
+
+#[test]
+fn test_replay_transcripts() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/transcripts");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        replay(&path);
+    }
+}